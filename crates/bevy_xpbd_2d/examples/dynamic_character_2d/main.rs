@@ -14,7 +14,7 @@ mod plugin;
 
 use bevy::{prelude::*, render::render_resource::PrimitiveTopology, sprite::MaterialMesh2dBundle};
 use bevy_xpbd_2d::{math::*, prelude::*};
-use plugin::*;
+use plugin::{CharacterControllerBundle, CharacterControllerPlugin};
 
 fn main() {
     App::new()
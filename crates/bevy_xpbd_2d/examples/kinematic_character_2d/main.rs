@@ -5,9 +5,9 @@
 //! - Basic directional movement and jumping
 //! - Support for both keyboard and gamepad input
 //! - A configurable maximum slope angle
-//! - Collision response for kinematic bodies
+//! - Move-and-slide collision response using the built-in `CharacterController`
 //!
-//! The character controller logic is contained within the `plugin` module.
+//! The input and movement logic is contained within the `plugin` module.
 //!
 //! For a dynamic character controller, see the `dynamic_character_2d` example.
 
@@ -22,7 +22,7 @@ fn main() {
         .add_plugins((
             DefaultPlugins,
             PhysicsPlugins::default(),
-            CharacterControllerPlugin,
+            CharacterMovementPlugin,
         ))
         .insert_resource(ClearColor(Color::rgb(0.05, 0.05, 0.1)))
         .insert_resource(Gravity(Vector::NEG_Y * 1000.0))
@@ -1,29 +1,24 @@
-use bevy::{ecs::query::Has, prelude::*};
-use bevy_xpbd_2d::{math::*, prelude::*, SubstepSchedule, SubstepSet};
+use bevy::prelude::*;
+use bevy_xpbd_2d::{math::*, prelude::*};
 
-pub struct CharacterControllerPlugin;
+/// Handles input, gravity and damping for character controllers.
+///
+/// Collisions are handled by the built-in [`CharacterControllerPlugin`] that is a part of [`PhysicsPlugins`].
+pub struct CharacterMovementPlugin;
 
-impl Plugin for CharacterControllerPlugin {
+impl Plugin for CharacterMovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<MovementAction>()
-            .add_systems(
-                Update,
-                (
-                    keyboard_input,
-                    gamepad_input,
-                    update_grounded,
-                    apply_deferred,
-                    apply_gravity,
-                    movement,
-                    apply_movement_damping,
-                )
-                    .chain(),
+        app.add_event::<MovementAction>().add_systems(
+            Update,
+            (
+                keyboard_input,
+                gamepad_input,
+                apply_gravity,
+                movement,
+                apply_movement_damping,
             )
-            .add_systems(
-                // Run collision handling in substep schedule
-                SubstepSchedule,
-                kinematic_controller_collisions.in_set(SubstepSet::SolveUserConstraints),
-            );
+                .chain(),
+        );
     }
 }
 
@@ -34,14 +29,6 @@ pub enum MovementAction {
     Jump,
 }
 
-/// A marker component indicating that an entity is using a character controller.
-#[derive(Component)]
-pub struct CharacterController;
-
-/// A marker component indicating that an entity is on the ground.
-#[derive(Component)]
-#[component(storage = "SparseSet")]
-pub struct Grounded;
 /// The acceleration used for character movement.
 #[derive(Component)]
 pub struct MovementAcceleration(Scalar);
@@ -58,12 +45,6 @@ pub struct JumpImpulse(Scalar);
 #[derive(Component)]
 pub struct ControllerGravity(Vector);
 
-/// The maximum angle a slope can have for a character controller
-/// to be able to climb and jump. If the slope is steeper than this angle,
-/// the character will slide down.
-#[derive(Component)]
-pub struct MaxSlopeAngle(Scalar);
-
 /// A bundle that contains the components needed for a basic
 /// kinematic character controller.
 #[derive(Bundle)]
@@ -71,7 +52,6 @@ pub struct CharacterControllerBundle {
     character_controller: CharacterController,
     rigid_body: RigidBody,
     collider: Collider,
-    ground_caster: ShapeCaster,
     gravity: ControllerGravity,
     movement: MovementBundle,
 }
@@ -82,43 +62,30 @@ pub struct MovementBundle {
     acceleration: MovementAcceleration,
     damping: MovementDampingFactor,
     jump_impulse: JumpImpulse,
-    max_slope_angle: MaxSlopeAngle,
 }
 
 impl MovementBundle {
-    pub const fn new(
-        acceleration: Scalar,
-        damping: Scalar,
-        jump_impulse: Scalar,
-        max_slope_angle: Scalar,
-    ) -> Self {
+    pub const fn new(acceleration: Scalar, damping: Scalar, jump_impulse: Scalar) -> Self {
         Self {
             acceleration: MovementAcceleration(acceleration),
             damping: MovementDampingFactor(damping),
             jump_impulse: JumpImpulse(jump_impulse),
-            max_slope_angle: MaxSlopeAngle(max_slope_angle),
         }
     }
 }
 
 impl Default for MovementBundle {
     fn default() -> Self {
-        Self::new(30.0, 0.9, 7.0, PI * 0.45)
+        Self::new(30.0, 0.9, 7.0)
     }
 }
 
 impl CharacterControllerBundle {
     pub fn new(collider: Collider, gravity: Vector) -> Self {
-        // Create shape caster as a slightly smaller version of collider
-        let mut caster_shape = collider.clone();
-        caster_shape.set_scale(Vector::ONE * 0.99, 10);
-
         Self {
-            character_controller: CharacterController,
+            character_controller: CharacterController::default(),
             rigid_body: RigidBody::Kinematic,
             collider,
-            ground_caster: ShapeCaster::new(caster_shape, Vector::ZERO, 0.0, Vector::NEG_Y)
-                .with_max_time_of_impact(10.0),
             gravity: ControllerGravity(gravity),
             movement: MovementBundle::default(),
         }
@@ -131,7 +98,10 @@ impl CharacterControllerBundle {
         jump_impulse: Scalar,
         max_slope_angle: Scalar,
     ) -> Self {
-        self.movement = MovementBundle::new(acceleration, damping, jump_impulse, max_slope_angle);
+        self.movement = MovementBundle::new(acceleration, damping, jump_impulse);
+        self.character_controller = self
+            .character_controller
+            .with_max_slope_angle(max_slope_angle);
         self
     }
 }
@@ -184,33 +154,6 @@ fn gamepad_input(
     }
 }
 
-/// Updates the [`Grounded`] status for character controllers.
-fn update_grounded(
-    mut commands: Commands,
-    mut query: Query<
-        (Entity, &ShapeHits, &Rotation, Option<&MaxSlopeAngle>),
-        With<CharacterController>,
-    >,
-) {
-    for (entity, hits, rotation, max_slope_angle) in &mut query {
-        // The character is grounded if the shape caster has a hit with a normal
-        // that isn't too steep.
-        let is_grounded = hits.iter().any(|hit| {
-            if let Some(angle) = max_slope_angle {
-                rotation.rotate(-hit.normal2).angle_between(Vector::Y).abs() <= angle.0
-            } else {
-                true
-            }
-        });
-
-        if is_grounded {
-            commands.entity(entity).insert(Grounded);
-        } else {
-            commands.entity(entity).remove::<Grounded>();
-        }
-    }
-}
-
 /// Responds to [`MovementAction`] events and moves character controllers accordingly.
 fn movement(
     time: Res<Time>,
@@ -219,7 +162,7 @@ fn movement(
         &MovementAcceleration,
         &JumpImpulse,
         &mut LinearVelocity,
-        Has<Grounded>,
+        Option<&CharacterControllerState>,
     )>,
 ) {
     // Precision is adjusted so that the example works with
@@ -227,15 +170,13 @@ fn movement(
     let delta_time = time.delta_seconds_f64().adjust_precision();
 
     for event in movement_event_reader.read() {
-        for (movement_acceleration, jump_impulse, mut linear_velocity, is_grounded) in
-            &mut controllers
-        {
+        for (movement_acceleration, jump_impulse, mut linear_velocity, state) in &mut controllers {
             match event {
                 MovementAction::Move(direction) => {
                     linear_velocity.x += *direction * movement_acceleration.0 * delta_time;
                 }
                 MovementAction::Jump => {
-                    if is_grounded {
+                    if state.is_some_and(|state| state.grounded) {
                         linear_velocity.y = jump_impulse.0;
                     }
                 }
@@ -265,82 +206,3 @@ fn apply_movement_damping(mut query: Query<(&MovementDampingFactor, &mut LinearV
         linear_velocity.x *= damping_factor.0;
     }
 }
-
-/// Kinematic bodies do not get pushed by collisions by default,
-/// so it needs to be done manually.
-///
-/// This system performs very basic collision response for kinematic
-/// character controllers by pushing them along their contact normals
-/// by the current penetration depths.
-#[allow(clippy::type_complexity)]
-fn kinematic_controller_collisions(
-    collisions: Res<Collisions>,
-    collider_parents: Query<&ColliderParent, Without<Sensor>>,
-    mut character_controllers: Query<
-        (
-            &RigidBody,
-            &mut Position,
-            &Rotation,
-            &mut LinearVelocity,
-            Option<&MaxSlopeAngle>,
-        ),
-        With<CharacterController>,
-    >,
-) {
-    // Iterate through collisions and move the kinematic body to resolve penetration
-    for contacts in collisions.iter() {
-        // If the collision didn't happen during this substep, skip the collision
-        if !contacts.during_current_substep {
-            continue;
-        }
-
-        // Get the rigid body entities of the colliders (colliders could be children)
-        let Ok([collider_parent1, collider_parent2]) =
-            collider_parents.get_many([contacts.entity1, contacts.entity2])
-        else {
-            continue;
-        };
-
-        // Get the body of the character controller and whether it is the first
-        // or second entity in the collision.
-        let is_first: bool;
-        let (rb, mut position, rotation, mut linear_velocity, max_slope_angle) =
-            if let Ok(character) = character_controllers.get_mut(collider_parent1.get()) {
-                is_first = true;
-                character
-            } else if let Ok(character) = character_controllers.get_mut(collider_parent2.get()) {
-                is_first = false;
-                character
-            } else {
-                continue;
-            };
-
-        // This system only handles collision response for kinematic character controllers
-        if !rb.is_kinematic() {
-            continue;
-        }
-
-        // Iterate through contact manifolds and their contacts.
-        // Each contact in a single manifold shares the same contact normal.
-        for manifold in contacts.manifolds.iter() {
-            let normal = if is_first {
-                -manifold.global_normal1(rotation)
-            } else {
-                -manifold.global_normal2(rotation)
-            };
-
-            // Solve each penetrating contact in the manifold
-            for contact in manifold.contacts.iter().filter(|c| c.penetration > 0.0) {
-                position.0 += normal * contact.penetration;
-            }
-
-            // If the slope isn't too steep to walk on but the character
-            // is falling, reset vertical velocity.
-            if max_slope_angle.is_some_and(|angle| normal.angle_between(Vector::Y).abs() <= angle.0)
-                && linear_velocity.y < 0.0
-            {
-                linear_velocity.y = linear_velocity.y.max(0.0);
-            }
-        }
-    }
-}
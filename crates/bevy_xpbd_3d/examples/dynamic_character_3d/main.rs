@@ -15,7 +15,7 @@ mod plugin;
 
 use bevy::prelude::*;
 use bevy_xpbd_3d::{math::*, prelude::*};
-use plugin::*;
+use plugin::{CharacterControllerBundle, CharacterControllerPlugin};
 
 fn main() {
     App::new()
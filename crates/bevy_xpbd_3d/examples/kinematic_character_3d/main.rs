@@ -5,10 +5,10 @@
 //! - Basic directional movement and jumping
 //! - Support for both keyboard and gamepad input
 //! - A configurable maximum slope angle
-//! - Collision response for kinematic bodies
+//! - Move-and-slide collision response using the built-in `CharacterController`
 //! - Loading a platformer environment from a glTF
 //!
-//! The character controller logic is contained within the `plugin` module.
+//! The input and movement logic is contained within the `plugin` module.
 //!
 //! For a dynamic character controller, see the `dynamic_character_3d` example.
 
@@ -23,7 +23,7 @@ fn main() {
         .add_plugins((
             DefaultPlugins,
             PhysicsPlugins::default(),
-            CharacterControllerPlugin,
+            CharacterMovementPlugin,
         ))
         .add_systems(Startup, setup)
         .run();
@@ -17,41 +17,49 @@ pub fn derive_physics_layer(input: TokenStream) -> TokenStream {
         _ => panic!("Only enums can automatically derive PhysicsLayer"),
     };
 
-    assert!(variants.len() <= 32, "Reached the maximum of 32 layers");
+    // The actual limit depends on the width of the `LayerMask`, which is checked at compile time below
+    assert!(variants.len() <= 128, "Reached the maximum of 128 layers");
 
     let to_bits = variants.iter().enumerate().map(|(index, variant)| {
-        let bits: u32 = 1 << index;
         assert!(
             variant.fields.is_empty(),
             "Can only derive PhysicsLayer for enums without fields"
         );
         let ident = &variant.ident;
-        quote! { #enum_ident::#ident => #bits, }
+        quote! { #enum_ident::#ident => 1 << #index, }
     });
 
-    let all_bits: u32 = if variants.len() == 32 {
-        0xffffffff
+    let layer_count = variants.len() as u32;
+    let all_bits = if layer_count == 0 {
+        quote! { 0 }
     } else {
-        (1 << variants.len()) - 1
+        quote! { LayerMask::MAX >> (LayerMask::BITS - #layer_count) }
     };
 
     let expanded = quote! {
-        #[cfg(feature = "2d")]
-        use bevy_xpbd_2d::prelude::PhysicsLayer;
-        #[cfg(feature = "3d")]
-        use bevy_xpbd_3d::prelude::PhysicsLayer;
-
-        impl PhysicsLayer for #enum_ident {
-            fn all_bits() -> u32 {
-                #all_bits
-            }
+        const _: () = {
+            #[cfg(feature = "2d")]
+            use bevy_xpbd_2d::prelude::{LayerMask, PhysicsLayer};
+            #[cfg(feature = "3d")]
+            use bevy_xpbd_3d::prelude::{LayerMask, PhysicsLayer};
+
+            assert!(
+                #layer_count <= LayerMask::BITS,
+                "Reached the maximum number of layers. Enable the `layers-64` or `layers-128` feature to use more layers"
+            );
+
+            impl PhysicsLayer for #enum_ident {
+                fn all_bits() -> LayerMask {
+                    #all_bits
+                }
 
-            fn to_bits(&self) -> u32 {
-                match self {
-                    #(#to_bits)*
+                fn to_bits(&self) -> LayerMask {
+                    match self {
+                        #(#to_bits)*
+                    }
                 }
             }
-        }
+        };
     };
 
     TokenStream::from(expanded)
@@ -1,9 +1,17 @@
-use std::fmt;
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use crate::{prelude::*, utils::make_isometry};
-#[cfg(all(feature = "3d", feature = "collider-from-mesh"))]
+#[cfg(feature = "collider-from-mesh")]
 use bevy::render::mesh::{Indices, VertexAttributeValues};
-#[cfg(all(feature = "3d", feature = "async-collider"))]
+#[cfg(all(feature = "2d", feature = "collider-from-image"))]
+use bevy::render::render_resource::TextureFormat;
+#[cfg(any(
+    all(feature = "2d", feature = "collider-from-mesh"),
+    all(feature = "3d", feature = "async-collider")
+))]
 use bevy::utils::HashMap;
 use bevy::{
     ecs::entity::{EntityMapper, MapEntities},
@@ -84,6 +92,10 @@ pub type TriMeshFlags = parry::shape::TriMeshFlags;
 /// - [`ColliderDensity`]
 /// - [`ColliderMassProperties`]
 ///
+#[cfg_attr(
+    feature = "2d",
+    doc = "Colliders can also be generated from the alpha channel of images with [`Collider::from_image_alpha`] and automatically from meshes with [`AsyncCollider`]."
+)]
 #[cfg_attr(
     feature = "3d",
     doc = "Colliders can also be generated automatically from meshes and scenes. See [`AsyncCollider`] and [`AsyncSceneCollider`]."
@@ -127,6 +139,10 @@ pub type TriMeshFlags = parry::shape::TriMeshFlags;
 /// - [Friction] and [restitution](Restitution) (bounciness)
 /// - [Collision layers](CollisionLayers)
 /// - [Sensors](Sensor)
+#[cfg_attr(
+    feature = "2d",
+    doc = "- Creating colliders from meshes with [`AsyncCollider`] and from images with [`Collider::from_image_alpha`]"
+)]
 #[cfg_attr(
     feature = "3d",
     doc = "- Creating colliders from meshes with [`AsyncCollider`] and [`AsyncSceneCollider`]"
@@ -195,7 +211,16 @@ impl fmt::Debug for Collider {
             TypedShape::HalfSpace(shape) => write!(f, "{:?}", shape),
             TypedShape::HeightField(shape) => write!(f, "{:?}", shape),
             TypedShape::Compound(_) => write!(f, "Compound (not representable)"),
-            TypedShape::Custom(shape) => write!(f, "{:?}", shape),
+            TypedShape::Custom(shape) => {
+                let shape_scaled = self.shape_scaled();
+                if let Some(voxels) = shape_scaled.as_shape::<Voxels>() {
+                    write!(f, "{:?}", voxels)
+                } else if let Some(sdf) = shape_scaled.as_shape::<SignedDistanceField>() {
+                    write!(f, "{:?}", sdf)
+                } else {
+                    write!(f, "{:?}", shape)
+                }
+            }
             #[cfg(feature = "3d")]
             TypedShape::ConvexPolyhedron(shape) => write!(f, "{:?}", shape),
             #[cfg(feature = "3d")]
@@ -251,6 +276,9 @@ impl Collider {
     /// a convex polygon or polyhedron using `num_subdivisions`.
     ///
     /// For example, if a ball was scaled to an ellipse, the new shape would be approximated.
+    ///
+    /// Custom shapes other than [voxels](Collider::voxels) and [signed distance fields](Collider::sdf)
+    /// can't be scaled, and they are used unscaled instead.
     pub fn set_scale(&mut self, scale: Vector, num_subdivisions: u32) {
         if scale == self.scale {
             return;
@@ -496,6 +524,113 @@ impl Collider {
         SharedShape::heightfield(heights, scale.into()).into()
     }
 
+    /// Creates a collider with a [voxel grid](Voxels) shape defined by the size of each voxel
+    /// and the indices of the filled voxels.
+    ///
+    /// The voxel at index `i` spans from `i * voxel_size` to `(i + 1) * voxel_size` in the local space
+    /// of the collider. Voxels can be filled and cleared later using [`Collider::set_voxel`].
+    #[cfg(feature = "2d")]
+    pub fn voxels(voxel_size: Vector, voxels: &[IVec2]) -> Self {
+        SharedShape::new(Voxels::new(voxel_size, voxels)).into()
+    }
+
+    /// Creates a collider with a [voxel grid](Voxels) shape defined by the size of each voxel
+    /// and the indices of the filled voxels.
+    ///
+    /// The voxel at index `i` spans from `i * voxel_size` to `(i + 1) * voxel_size` in the local space
+    /// of the collider. Voxels can be filled and cleared later using [`Collider::set_voxel`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_xpbd_3d::prelude::*;
+    ///
+    /// fn setup(mut commands: Commands) {
+    ///     // A 16x16 floor of voxels with a pillar in the middle
+    ///     let mut voxels: Vec<IVec3> = (0..16)
+    ///         .flat_map(|x| (0..16).map(move |z| IVec3::new(x, 0, z)))
+    ///         .collect();
+    ///     voxels.extend((1..4).map(|y| IVec3::new(8, y, 8)));
+    ///     commands.spawn((RigidBody::Static, Collider::voxels(Vec3::ONE, &voxels)));
+    /// }
+    /// ```
+    #[cfg(feature = "3d")]
+    pub fn voxels(voxel_size: Vector, voxels: &[IVec3]) -> Self {
+        SharedShape::new(Voxels::new(voxel_size, voxels)).into()
+    }
+
+    /// Fills or clears the voxel at the given index if the collider has a [voxel grid](Voxels) shape.
+    /// Returns `true` if the voxel was changed.
+    ///
+    /// Only the voxel is updated instead of rebuilding the whole shape. If the shape is shared
+    /// with other colliders or the [`SpatialQueryPipeline`], its compact bit grid is copied first.
+    #[cfg(feature = "2d")]
+    pub fn set_voxel(&mut self, voxel: IVec2, filled: bool) -> bool {
+        self.update_voxels(|voxels| voxels.set_voxel(voxel, filled))
+    }
+
+    /// Fills or clears the voxel at the given index if the collider has a [voxel grid](Voxels) shape.
+    /// Returns `true` if the voxel was changed.
+    ///
+    /// Only the voxel is updated instead of rebuilding the whole shape. If the shape is shared
+    /// with other colliders or the [`SpatialQueryPipeline`], its compact bit grid is copied first.
+    #[cfg(feature = "3d")]
+    pub fn set_voxel(&mut self, voxel: IVec3, filled: bool) -> bool {
+        self.update_voxels(|voxels| voxels.set_voxel(voxel, filled))
+    }
+
+    /// Calls `update` for the unscaled and scaled [`Voxels`] shapes of the collider.
+    fn update_voxels(&mut self, mut update: impl FnMut(&mut Voxels) -> bool) -> bool {
+        if self.shape.as_shape::<Voxels>().is_none() {
+            return false;
+        }
+
+        // Release the scaled shape if it's the same as the unscaled shape so that it isn't copied
+        let is_scaled = self.scale != Vector::ONE;
+        if !is_scaled {
+            self.scaled_shape = SharedShape::ball(0.0);
+        }
+        let changed = self
+            .shape
+            .make_mut()
+            .as_shape_mut::<Voxels>()
+            .is_some_and(&mut update);
+        if !is_scaled {
+            self.scaled_shape = self.shape.clone();
+        } else if let Some(voxels) = self.scaled_shape.make_mut().as_shape_mut::<Voxels>() {
+            update(voxels);
+        }
+        changed
+    }
+
+    /// Creates a collider with a [signed distance field](SignedDistanceField) shape.
+    ///
+    /// Distance fields can represent sculpted terrain and shapes combined with boolean operations,
+    /// which would be impractical as triangle meshes. Their contacts are supported against convex shapes
+    /// and compounds of convex shapes.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    #[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::{math::Vector, prelude::*};")]
+    #[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::{math::Vector, prelude::*};")]
+    ///
+    /// fn setup(mut commands: Commands) {
+    ///     // Flat ground with a crater of radius 3 subtracted from it
+    ///     let terrain = SignedDistanceField::from_fn(
+    ///         Vector::splat(-20.0),
+    ///         Vector::splat(20.0),
+    ///         |point| point.y.max(3.0 - point.length()),
+    ///     );
+    ///     commands.spawn((RigidBody::Static, Collider::sdf(terrain)));
+    /// }
+    /// ```
+    pub fn sdf(sdf: SignedDistanceField) -> Self {
+        SharedShape::new(sdf).into()
+    }
+
     /// Creates a collider with a triangle mesh shape from a `Mesh`.
     ///
     /// ## Example
@@ -638,27 +773,189 @@ impl Collider {
             SharedShape::convex_decomposition_with_params(&vertices, &indices, parameters).into()
         })
     }
+
+    /// Creates a collider from a 2D `Mesh`, like the meshes used with `Mesh2dHandle`.
+    /// The type of the collider is specified using [`ComputedCollider`].
+    ///
+    /// The Z coordinates of the vertices are ignored. Convex decompositions are computed
+    /// from the outlines of the mesh. Returns `None` if the mesh doesn't have positions and indices.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+    /// use bevy_xpbd_2d::prelude::*;
+    ///
+    /// fn setup(
+    ///     mut commands: Commands,
+    ///     mut meshes: ResMut<Assets<Mesh>>,
+    ///     mut materials: ResMut<Assets<ColorMaterial>>,
+    /// ) {
+    ///     let mesh = Mesh::from(shape::RegularPolygon::new(1.0, 6));
+    ///     commands.spawn((
+    ///         RigidBody::Dynamic,
+    ///         Collider::from_mesh_2d(&mesh, &ComputedCollider::ConvexHull).unwrap(),
+    ///         MaterialMesh2dBundle {
+    ///             mesh: meshes.add(mesh).into(),
+    ///             material: materials.add(ColorMaterial::default()),
+    ///             ..default()
+    ///         },
+    ///     ));
+    /// }
+    /// ```
+    #[cfg(all(feature = "2d", feature = "collider-from-mesh"))]
+    pub fn from_mesh_2d(mesh: &Mesh, shape: &ComputedCollider) -> Option<Self> {
+        let (vertices, indices) = extract_mesh_vertices_indices(mesh)?;
+        match shape {
+            ComputedCollider::TriMesh => Some(SharedShape::trimesh_with_flags(
+                vertices,
+                indices,
+                TriMeshFlags::MERGE_DUPLICATE_VERTICES,
+            )),
+            ComputedCollider::TriMeshWithFlags(flags) => {
+                Some(SharedShape::trimesh_with_flags(vertices, indices, *flags))
+            }
+            ComputedCollider::ConvexHull => SharedShape::convex_hull(&vertices),
+            ComputedCollider::ConvexDecomposition(parameters) => {
+                Some(SharedShape::convex_decomposition_with_params(
+                    &vertices,
+                    &mesh_boundary_edges(&indices),
+                    parameters,
+                ))
+            }
+        }
+        .map(|shape| shape.into())
+    }
+
+    /// Creates a collider that follows the outlines of the opaque pixels of an `Image`,
+    /// using the default [`ImageColliderConfig`].
+    ///
+    /// The outlines are traced with the marching squares algorithm and simplified.
+    /// One pixel is one unit, and the image is centered at the origin like a `Sprite`.
+    ///
+    /// Returns `None` if the image has no opaque pixels or if its format isn't supported.
+    /// The supported formats are 8-bit RGBA and BGRA, 8-bit single channel and 32-bit float RGBA.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_xpbd_2d::prelude::*;
+    ///
+    /// #[derive(Resource)]
+    /// struct Terrain(Handle<Image>);
+    ///
+    /// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    ///     let image = asset_server.load("terrain.png");
+    ///     commands.insert_resource(Terrain(image.clone()));
+    ///     commands.spawn(SpriteBundle {
+    ///         texture: image,
+    ///         ..default()
+    ///     });
+    /// }
+    ///
+    /// // Add the collider once the image has been loaded
+    /// fn add_terrain_collider(
+    ///     mut commands: Commands,
+    ///     terrain: Res<Terrain>,
+    ///     images: Res<Assets<Image>>,
+    ///     sprites: Query<(Entity, &Handle<Image>), Without<Collider>>,
+    /// ) {
+    ///     let Some(image) = images.get(&terrain.0) else {
+    ///         return;
+    ///     };
+    ///     for (entity, handle) in &sprites {
+    ///         if *handle == terrain.0 {
+    ///             if let Some(collider) = Collider::from_image_alpha(image) {
+    ///                 commands.entity(entity).insert((RigidBody::Static, collider));
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[cfg(all(feature = "2d", feature = "collider-from-image"))]
+    pub fn from_image_alpha(image: &Image) -> Option<Self> {
+        Self::from_image_alpha_with_config(image, &ImageColliderConfig::default())
+    }
+
+    /// Creates a collider that follows the outlines of the opaque pixels of an `Image`,
+    /// using the given [`ImageColliderConfig`].
+    ///
+    /// See [`Collider::from_image_alpha`] for more information.
+    #[cfg(all(feature = "2d", feature = "collider-from-image"))]
+    pub fn from_image_alpha_with_config(
+        image: &Image,
+        config: &ImageColliderConfig,
+    ) -> Option<Self> {
+        let alpha = image_alpha(image)?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+
+        // Opaque pixels are inside of the outlines. The image is padded with transparent pixels
+        // so that the outlines are closed at its edges.
+        let threshold = config.alpha_threshold;
+        let segments = crate::utils::marching_squares([width + 2, height + 2], |x, y| {
+            if x == 0 || y == 0 || x > width || y > height {
+                threshold as Scalar
+            } else {
+                (threshold - alpha[(y - 1) * width + x - 1]) as Scalar
+            }
+        });
+
+        let mut vertices = vec![];
+        let mut indices = vec![];
+        for outline in crate::utils::connect_segments(&segments) {
+            // Convert the outline from the padded grid of pixel centers to the local space of the sprite
+            let outline: Vec<Vector> = outline
+                .into_iter()
+                .map(|[x, y]| {
+                    Vector::new(
+                        x - 0.5 - width as Scalar * 0.5,
+                        height as Scalar * 0.5 - (y - 0.5),
+                    )
+                })
+                .collect();
+            let outline =
+                crate::utils::simplify_closed_polyline(&outline, config.simplification_tolerance);
+            if outline.len() < 3 {
+                continue;
+            }
+
+            let start = vertices.len() as u32;
+            let count = outline.len() as u32;
+            vertices.extend(outline.into_iter().map(parry::math::Point::from));
+            indices.extend((0..count).map(|i| [start + i, start + (i + 1) % count]));
+        }
+
+        if vertices.is_empty() {
+            return None;
+        }
+        let shape = match &config.shape {
+            ImageColliderShape::Polyline => SharedShape::polyline(vertices, Some(indices)),
+            ImageColliderShape::ConvexDecomposition(parameters) => {
+                SharedShape::convex_decomposition_with_params(&vertices, &indices, parameters)
+            }
+        };
+        Some(shape.into())
+    }
 }
 
-#[cfg(all(feature = "3d", feature = "collider-from-mesh"))]
-type VerticesIndices = (Vec<nalgebra::Point3<Scalar>>, Vec<[u32; 3]>);
+#[cfg(feature = "collider-from-mesh")]
+type VerticesIndices = (Vec<parry::math::Point<Scalar>>, Vec<[u32; 3]>);
 
-#[cfg(all(feature = "3d", feature = "collider-from-mesh"))]
+#[cfg(feature = "collider-from-mesh")]
 fn extract_mesh_vertices_indices(mesh: &Mesh) -> Option<VerticesIndices> {
     let vertices = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?;
     let indices = mesh.indices()?;
 
     let vtx: Vec<_> = match vertices {
-        VertexAttributeValues::Float32(vtx) => Some(
-            vtx.chunks(3)
-                .map(|v| [v[0] as Scalar, v[1] as Scalar, v[2] as Scalar].into())
-                .collect(),
-        ),
-        VertexAttributeValues::Float32x3(vtx) => Some(
-            vtx.iter()
-                .map(|v| [v[0] as Scalar, v[1] as Scalar, v[2] as Scalar].into())
-                .collect(),
-        ),
+        VertexAttributeValues::Float32(vtx) => Some(vtx.chunks(3).map(vertex_to_point).collect()),
+        VertexAttributeValues::Float32x3(vtx) => {
+            Some(vtx.iter().map(|v| vertex_to_point(v)).collect())
+        }
+        #[cfg(feature = "2d")]
+        VertexAttributeValues::Float32x2(vtx) => {
+            Some(vtx.iter().map(|v| vertex_to_point(v)).collect())
+        }
         _ => None,
     }?;
 
@@ -673,6 +970,54 @@ fn extract_mesh_vertices_indices(mesh: &Mesh) -> Option<VerticesIndices> {
     Some((vtx, idx))
 }
 
+/// Converts the position of a mesh vertex to a point. In 2D, the Z coordinate is ignored.
+#[cfg(feature = "collider-from-mesh")]
+fn vertex_to_point(v: &[f32]) -> parry::math::Point<Scalar> {
+    #[cfg(feature = "2d")]
+    let point = [v[0] as Scalar, v[1] as Scalar];
+    #[cfg(feature = "3d")]
+    let point = [v[0] as Scalar, v[1] as Scalar, v[2] as Scalar];
+    point.into()
+}
+
+/// Returns the edges of a triangle mesh that belong to only one triangle, which form its outlines.
+#[cfg(all(feature = "2d", feature = "collider-from-mesh"))]
+fn mesh_boundary_edges(indices: &[[u32; 3]]) -> Vec<[u32; 2]> {
+    let mut edge_counts = HashMap::<[u32; 2], usize>::new();
+    let edges = indices
+        .iter()
+        .flat_map(|[a, b, c]| [[*a, *b], [*b, *c], [*c, *a]]);
+    for [a, b] in edges.clone() {
+        *edge_counts.entry([a.min(b), a.max(b)]).or_default() += 1;
+    }
+    edges
+        .filter(|[a, b]| edge_counts[&[*a.min(b), *a.max(b)]] == 1)
+        .collect()
+}
+
+/// Returns the alpha value of each pixel of an image, or `None` if the format isn't supported.
+#[cfg(all(feature = "2d", feature = "collider-from-image"))]
+fn image_alpha(image: &Image) -> Option<Vec<f32>> {
+    let alpha: Vec<f32> = match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb => image
+            .data
+            .chunks_exact(4)
+            .map(|pixel| pixel[3] as f32 / 255.0)
+            .collect(),
+        TextureFormat::R8Unorm => image.data.iter().map(|a| *a as f32 / 255.0).collect(),
+        TextureFormat::Rgba32Float => image
+            .data
+            .chunks_exact(16)
+            .map(|pixel| f32::from_ne_bytes([pixel[12], pixel[13], pixel[14], pixel[15]]))
+            .collect(),
+        _ => return None,
+    };
+    (alpha.len() == (image.width() * image.height()) as usize).then_some(alpha)
+}
+
 fn scale_shape(
     shape: &SharedShape,
     scale: Vector,
@@ -832,31 +1177,82 @@ fn scale_shape(
             }
             Ok(SharedShape::compound(scaled))
         }
-        _ => Err(parry::query::Unsupported),
+        TypedShape::Custom(_) => {
+            if let Some(voxels) = shape.as_shape::<Voxels>() {
+                Ok(SharedShape::new(voxels.scaled(scale)))
+            } else if let Some(sdf) = shape.as_shape::<SignedDistanceField>() {
+                Ok(SharedShape::new(sdf.scaled(scale)))
+            } else {
+                // Other custom shapes can't be scaled generically, so they are used unscaled.
+                static WARNED: AtomicBool = AtomicBool::new(false);
+                if !WARNED.swap(true, Ordering::Relaxed) {
+                    log::warn!(
+                        "Scaling custom collider shapes is not supported. The shape will be used unscaled."
+                    );
+                }
+                Ok(shape.clone())
+            }
+        }
     }
 }
 
 /// A component that will automatically generate a [`Collider`] based on the entity's `Mesh`.
 /// The type of the generated collider can be specified using [`ComputedCollider`].
 ///
+#[cfg_attr(
+    feature = "2d",
+    doc = "In 2D, the mesh is read from the `Mesh2dHandle` of the entity, and the collider is created using [`Collider::from_mesh_2d`]."
+)]
+#[cfg_attr(feature = "2d", doc = "")]
 /// ## Example
 ///
 /// ```
-/// use bevy::prelude::*;
-/// use bevy_xpbd_3d::prelude::*;
+#[cfg_attr(
+    feature = "2d",
+    doc = "use bevy::{prelude::*, sprite::MaterialMesh2dBundle};"
+)]
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
 ///
-/// fn setup(mut commands: Commands, mut assets: ResMut<AssetServer>, mut meshes: Assets<Mesh>) {
-///     // Spawn a cube with a convex hull collider generated from the mesh
+#[cfg_attr(
+    feature = "2d",
+    doc = "fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<ColorMaterial>>) {"
+)]
+#[cfg_attr(
+    feature = "2d",
+    doc = "    // Spawn a hexagon with a convex hull collider generated from the mesh"
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "fn setup(mut commands: Commands, mut assets: ResMut<AssetServer>, mut meshes: Assets<Mesh>) {"
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "    // Spawn a cube with a convex hull collider generated from the mesh"
+)]
 ///     commands.spawn((
 ///         AsyncCollider(ComputedCollider::ConvexHull),
-///         PbrBundle {
-///             mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+#[cfg_attr(feature = "2d", doc = "        MaterialMesh2dBundle {")]
+#[cfg_attr(
+    feature = "2d",
+    doc = "            mesh: meshes.add(Mesh::from(shape::RegularPolygon::new(1.0, 6))).into(),"
+)]
+#[cfg_attr(
+    feature = "2d",
+    doc = "            material: materials.add(ColorMaterial::default()),"
+)]
+#[cfg_attr(feature = "3d", doc = "        PbrBundle {")]
+#[cfg_attr(
+    feature = "3d",
+    doc = "            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),"
+)]
 ///             ..default()
 ///         },
 ///     ));
 /// }
 /// ```
-#[cfg(all(feature = "3d", feature = "async-collider"))]
+#[cfg(feature = "async-collider")]
 #[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
 pub struct AsyncCollider(pub ComputedCollider);
 
@@ -1014,11 +1410,15 @@ impl Default for AsyncSceneColliderData {
 /// Colliders can be created from meshes with the following components and methods:
 ///
 /// - [`AsyncCollider`] (requires `async-collider` features)
-/// - [`AsyncSceneCollider`] (requires `async-collider` features)
-/// - [`Collider::trimesh_from_mesh`]
-/// - [`Collider::convex_hull_from_mesh`]
-/// - [`Collider::convex_decomposition_from_mesh`]
-#[cfg(all(feature = "3d", feature = "collider-from-mesh"))]
+#[cfg_attr(feature = "2d", doc = "- [`Collider::from_mesh_2d`]")]
+#[cfg_attr(
+    feature = "3d",
+    doc = "- [`AsyncSceneCollider`] (requires `async-collider` features)"
+)]
+#[cfg_attr(feature = "3d", doc = "- [`Collider::trimesh_from_mesh`]")]
+#[cfg_attr(feature = "3d", doc = "- [`Collider::convex_hull_from_mesh`]")]
+#[cfg_attr(feature = "3d", doc = "- [`Collider::convex_decomposition_from_mesh`]")]
+#[cfg(feature = "collider-from-mesh")]
 #[derive(Component, Clone, Debug, Default, PartialEq)]
 pub enum ComputedCollider {
     /// A triangle mesh.
@@ -1033,6 +1433,49 @@ pub enum ComputedCollider {
     ConvexDecomposition(VHACDParameters),
 }
 
+/// Configures how a [`Collider`] is generated from the alpha channel of an `Image`
+/// by [`Collider::from_image_alpha_with_config`].
+#[cfg(all(feature = "2d", feature = "collider-from-image"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageColliderConfig {
+    /// Pixels with an alpha value above the threshold are inside of the collider.
+    ///
+    /// Default: `0.5`
+    pub alpha_threshold: f32,
+    /// The maximum distance in pixels between the traced outlines and the simplified outlines.
+    /// Larger values result in fewer vertices.
+    ///
+    /// Default: `1.0`
+    pub simplification_tolerance: Scalar,
+    /// The type of the generated collider.
+    ///
+    /// Default: [`ImageColliderShape::Polyline`]
+    pub shape: ImageColliderShape,
+}
+
+#[cfg(all(feature = "2d", feature = "collider-from-image"))]
+impl Default for ImageColliderConfig {
+    fn default() -> Self {
+        Self {
+            alpha_threshold: 0.5,
+            simplification_tolerance: 1.0,
+            shape: ImageColliderShape::default(),
+        }
+    }
+}
+
+/// Determines the type of the [`Collider`] generated from the outlines of an `Image`.
+#[cfg(all(feature = "2d", feature = "collider-from-image"))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ImageColliderShape {
+    /// A polyline that follows the outlines. The collider is hollow, so it's best suited for static terrain.
+    #[default]
+    Polyline,
+    /// A compound shape obtained from a decomposition of the outlines into convex parts
+    /// using the specified [`VHACDParameters`].
+    ConvexDecomposition(VHACDParameters),
+}
+
 /// A component that stores the `Entity` ID of the [`RigidBody`] that a [`Collider`] is attached to.
 ///
 /// If the collider is a child of a rigid body, this points to the body's `Entity` ID.
@@ -1137,6 +1580,19 @@ impl From<Transform> for ColliderTransform {
 /// but allow other bodies to pass through them. This is often used to detect when something enters
 /// or leaves an area or is intersecting some shape.
 ///
+/// Sensors don't generate [`Contacts`]. Their intersections are checked once per frame with a cheap
+/// boolean intersection test and stored in [`SensorOverlaps`]. When an intersection starts or ends,
+/// [`SensorEntered`] or [`SensorExited`] is sent along with [`CollisionStarted`] or [`CollisionEnded`].
+///
+/// ## Migrating from contact-based sensors
+///
+/// Previously, sensors generated contacts like other colliders. Now, the intersections of sensors
+/// are no longer stored in [`Collisions`], and no [`Collision`] events are sent for them.
+/// Code that looks for sensor intersections in [`Collisions`] or reads [`Collision`] events
+/// should use [`SensorOverlaps`] or [`CollidingEntities`] instead. [`CollisionStarted`] and
+/// [`CollisionEnded`] work like before, but the sensor is always the first entity, and like for other
+/// collisions, they are only sent if one of the entities has [`CollisionEventsEnabled`].
+///
 /// ## Example
 ///
 /// ```
@@ -1146,7 +1602,7 @@ impl From<Transform> for ColliderTransform {
 ///
 /// fn setup(mut commands: Commands) {
 ///     // Spawn a static body with a sensor collider.
-///     // Other bodies will pass through, but it will still send collision events.
+///     // Other bodies will pass through, but it will still send sensor events.
 ///     commands.spawn((RigidBody::Static, Collider::ball(0.5), Sensor));
 /// }
 /// ```
@@ -1178,6 +1634,7 @@ impl Default for ColliderAabb {
 ///
 /// This component is automatically added for all entities with a [`Collider`],
 /// but it will only be filled if the [`ContactReportingPlugin`] is enabled (by default, it is).
+/// It contains both the entities in contact and the entities intersecting [sensors](Sensor).
 ///
 /// ## Example
 ///
@@ -1211,3 +1668,166 @@ impl MapEntities for CollidingEntities {
             .collect()
     }
 }
+
+/// Enables [collision events](ContactReportingPlugin#collision-events) for a [collider](Collider)
+/// or a [rigid body](RigidBody) and all of its colliders.
+///
+/// Entities with this component receive their own collisions in the [`OnCollisionStart`]
+/// and [`OnCollisionEnd`] components, which report both the collider and the rigid body of the other entity.
+///
+/// [`Collision`], [`CollisionStarted`] and [`CollisionEnded`] events are also only sent for collisions where
+/// at least one of the colliders or their rigid bodies has this component, unless
+/// [`ContactReportingConfig::require_opt_in`] is disabled. This keeps the number of events low
+/// in scenes with lots of contacts that nothing is interested in.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// #[derive(Component)]
+/// struct Player;
+///
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         Player,
+///         RigidBody::Dynamic,
+///         Collider::ball(0.5),
+///         CollisionEventsEnabled,
+///         OnCollisionStart::default(),
+///     ));
+/// }
+///
+/// fn print_player_collisions(players: Query<&OnCollisionStart, With<Player>>) {
+///     for collisions in &players {
+///         for collision in collisions.iter() {
+///             println!(
+///                 "The player hit collider {:?} of body {:?}",
+///                 collision.other_collider,
+///                 collision.other_body,
+///             );
+///         }
+///     }
+/// }
+/// ```
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct CollisionEventsEnabled;
+
+/// A collision reported in the [`OnCollisionStart`] or [`OnCollisionEnd`] component of an entity.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CollisionEventData {
+    /// The collider of the entity that is in the collision. For rigid bodies, this can be one of their child colliders.
+    pub collider: Entity,
+    /// The other collider in the collision.
+    pub other_collider: Entity,
+    /// The rigid body that the other collider is attached to, if any.
+    pub other_body: Option<Entity>,
+}
+
+/// The collisions of a [collider](Collider) or a [rigid body](RigidBody) that started during the current physics frame.
+///
+/// The collisions are only reported for entities that have [`CollisionEventsEnabled`].
+/// The component is cleared and filled again each frame in [`PhysicsStepSet::ReportContacts`].
+#[derive(Reflect, Clone, Component, Debug, Default, Deref, DerefMut, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct OnCollisionStart(pub Vec<CollisionEventData>);
+
+/// The collisions of a [collider](Collider) or a [rigid body](RigidBody) that ended during the current physics frame.
+///
+/// The collisions are only reported for entities that have [`CollisionEventsEnabled`].
+/// The component is cleared and filled again each frame in [`PhysicsStepSet::ReportContacts`].
+#[derive(Reflect, Clone, Component, Debug, Default, Deref, DerefMut, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct OnCollisionEnd(pub Vec<CollisionEventData>);
+
+/// Enables [`ContactForceEvent`]s for a [collider](Collider) or a [rigid body](RigidBody) and all of its colliders.
+///
+/// A [`ContactForceEvent`] is sent for a pair of colliders when the magnitude of the total impulse applied
+/// by their contacts during a physics frame exceeds the threshold. If both colliders have a threshold,
+/// the smaller one is used. A collider without a threshold uses the threshold of its rigid body.
+///
+/// The threshold is an impulse, so it has the unit Newton-seconds. The impulse caused by a resting contact
+/// is the force multiplied by the length of the physics frame.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(mut commands: Commands) {
+///     // Only report hard impacts
+///     commands.spawn((
+///         RigidBody::Dynamic,
+///         Collider::ball(0.5),
+///         ContactForceThreshold(5.0),
+///     ));
+/// }
+/// ```
+#[derive(
+    Reflect, Clone, Copy, Component, Debug, Default, Deref, DerefMut, PartialEq, PartialOrd,
+)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct ContactForceThreshold(pub Scalar);
+
+/// The total impulses applied to a [rigid body](RigidBody) by its contacts during the current physics frame, in world space.
+///
+/// This component is not added automatically. It is updated in [`PhysicsStepSet::ReportContacts`]
+/// for rigid bodies that have it, which can be used for things like pressure plates measuring weight.
+///
+/// The impulses are kept while the body is sleeping on a static body or another sleeping body.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// #[derive(Component)]
+/// struct PressurePlate;
+///
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         RigidBody::Static,
+#[cfg_attr(feature = "2d", doc = "        Collider::cuboid(1.0, 0.1),")]
+#[cfg_attr(feature = "3d", doc = "        Collider::cuboid(1.0, 0.1, 1.0),")]
+///         ContactImpulses::default(),
+///         PressurePlate,
+///     ));
+/// }
+///
+/// fn check_pressure_plates(plates: Query<&ContactImpulses, With<PressurePlate>>) {
+///     for impulses in &plates {
+///         // The weight of the bodies on the plate is the normal impulse divided by the length of the frame
+///         if impulses.normal_impulse.length() > 0.5 {
+///             println!("The plate is pressed");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct ContactImpulses {
+    /// The sum of the normal impulses applied by the contacts of the body.
+    pub normal_impulse: Vector,
+    /// The sum of the friction impulses applied by the contacts of the body.
+    pub friction_impulse: Vector,
+}
+
+impl ContactImpulses {
+    /// Returns the sum of the normal and friction impulses.
+    pub fn total_impulse(&self) -> Vector {
+        self.normal_impulse + self.friction_impulse
+    }
+}
@@ -1,22 +1,32 @@
 use bevy::prelude::*;
 
+/// The bitmask type used for the groups and masks of [`CollisionLayers`], with one bit for each of the 32 layers.
+#[cfg(not(any(feature = "layers-64", feature = "layers-128")))]
+pub type LayerMask = u32;
+/// The bitmask type used for the groups and masks of [`CollisionLayers`], with one bit for each of the 64 layers.
+#[cfg(all(feature = "layers-64", not(feature = "layers-128")))]
+pub type LayerMask = u64;
+/// The bitmask type used for the groups and masks of [`CollisionLayers`], with one bit for each of the 128 layers.
+#[cfg(feature = "layers-128")]
+pub type LayerMask = u128;
+
 /// A layer used for determining which entities should interact with each other.
 /// Physics layers are used heavily by [`CollisionLayers`].
 ///
 /// This trait can be derived for enums with `#[derive(PhysicsLayer)]`.
 pub trait PhysicsLayer: Sized {
     /// Converts the layer to a bitmask.
-    fn to_bits(&self) -> u32;
+    fn to_bits(&self) -> LayerMask;
     /// Creates a layer bitmask with all bits set to 1.
-    fn all_bits() -> u32;
+    fn all_bits() -> LayerMask;
 }
 
 impl<L: PhysicsLayer> PhysicsLayer for &L {
-    fn to_bits(&self) -> u32 {
+    fn to_bits(&self) -> LayerMask {
         L::to_bits(self)
     }
 
-    fn all_bits() -> u32 {
+    fn all_bits() -> LayerMask {
         L::all_bits()
     }
 }
@@ -47,6 +57,9 @@ impl<L: PhysicsLayer> PhysicsLayer for &L {
 /// Internally, the groups and masks are represented as bitmasks, so you can also use [`CollisionLayers::from_bits()`]
 /// to create collision layers.
 ///
+/// By default, the bitmasks are 32 bits wide, so there can be at most 32 layers. To use more layers, enable the
+/// `layers-64` or `layers-128` feature, which changes the [`LayerMask`] type to `u64` or `u128`.
+///
 /// ## Example
 ///
 /// ```
@@ -73,8 +86,8 @@ impl<L: PhysicsLayer> PhysicsLayer for &L {
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 #[reflect(Component)]
 pub struct CollisionLayers {
-    groups: u32,
-    masks: u32,
+    groups: LayerMask,
+    masks: LayerMask,
 }
 
 impl CollisionLayers {
@@ -108,10 +121,10 @@ impl CollisionLayers {
 
     /// Creates a new [`CollisionLayers`] using bits.
     ///
-    /// There is one bit per group and mask, so there are a total of 32 layers.
+    /// There is one bit per group and mask, so there are a total of 32 layers by default.
     /// For example, if an entity is a part of the layers `[0, 1, 3]` and can interact with the layers `[1, 2]`,
     /// the groups in bits would be `0b01011` while the masks would be `0b00110`.
-    pub const fn from_bits(groups: u32, masks: u32) -> Self {
+    pub const fn from_bits(groups: LayerMask, masks: LayerMask) -> Self {
         Self { groups, masks }
     }
 
@@ -192,12 +205,12 @@ impl CollisionLayers {
     }
 
     /// Returns the `groups` bitmask.
-    pub fn groups_bits(self) -> u32 {
+    pub fn groups_bits(self) -> LayerMask {
         self.groups
     }
 
     /// Returns the `masks` bitmask.
-    pub fn masks_bits(self) -> u32 {
+    pub fn masks_bits(self) -> LayerMask {
         self.masks
     }
 }
@@ -205,8 +218,57 @@ impl CollisionLayers {
 impl Default for CollisionLayers {
     fn default() -> Self {
         Self {
-            groups: 0xffff_ffff,
-            masks: 0xffff_ffff,
+            groups: LayerMask::MAX,
+            masks: LayerMask::MAX,
+        }
+    }
+}
+
+/// A collision group index that overrides [`CollisionLayers`] for colliders in the same group.
+///
+/// Colliders that share the same non-zero group index either always or never collide with each other:
+///
+/// - A shared **positive** group means that the colliders always collide, even if their layers don't interact
+/// - A shared **negative** group means that the colliders never collide, even if their layers interact
+///
+/// If the colliders are in different groups or either of the groups is zero, the [`CollisionLayers`]
+/// determine whether the colliders interact. Colliders without this component are in the group zero.
+///
+/// This is useful for assemblies like ragdolls and vehicles, where the parts of one instance shouldn't
+/// collide with each other, but should still collide with other instances. Each instance can use its own
+/// negative group instead of needing a separate collision layer.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn spawn_ragdolls(mut commands: Commands) {
+///     for i in 0..10 {
+///         // The limbs of a ragdoll don't collide with each other,
+///         // but they collide with the limbs of other ragdolls.
+///         let group = CollisionGroup(-(i + 1));
+///
+///         commands.spawn((RigidBody::Dynamic, Collider::ball(0.5), group));
+///         commands.spawn((RigidBody::Dynamic, Collider::ball(0.5), group));
+///     }
+/// }
+/// ```
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct CollisionGroup(pub i32);
+
+impl CollisionGroup {
+    /// Returns `Some(true)` if colliders in this group and the `other` group always collide,
+    /// `Some(false)` if they never collide, and `None` if the [`CollisionLayers`] should decide.
+    pub fn test(self, other: Self) -> Option<bool> {
+        if self.0 != 0 && self.0 == other.0 {
+            Some(self.0 > 0)
+        } else {
+            None
         }
     }
 }
@@ -479,7 +479,10 @@ pub struct GravityScale(pub Scalar);
 /// When combine rules clash with each other, the following priority order is used:
 /// `Max > Multiply > Min > Average`.
 #[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
-#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    any(feature = "serialize", feature = "physics-material"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum CoefficientCombine {
     // The discriminants allow priority ordering to work automatically via comparison methods
     /// Coefficients are combined by computing their average.
@@ -534,7 +537,10 @@ pub enum CoefficientCombine {
 #[doc(alias = "Bounciness")]
 #[doc(alias = "Elasticity")]
 #[derive(Reflect, Clone, Copy, Component, Debug, PartialEq, PartialOrd)]
-#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    any(feature = "serialize", feature = "physics-material"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 #[reflect(Component)]
 pub struct Restitution {
     /// The [coefficient of restitution](https://en.wikipedia.org/wiki/Coefficient_of_restitution).
@@ -669,7 +675,10 @@ impl From<Scalar> for Restitution {
 /// );
 /// ```
 #[derive(Reflect, Clone, Copy, Component, Debug, PartialEq, PartialOrd)]
-#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    any(feature = "serialize", feature = "physics-material"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 #[reflect(Component)]
 pub struct Friction {
     /// Coefficient of dynamic friction.
@@ -766,6 +775,34 @@ impl From<Scalar> for Friction {
     }
 }
 
+/// The velocity of the surface of a [collider](Collider) in the local space of the collider.
+/// Friction treats contacts with the collider as if its surface was moving at this velocity,
+/// even though the collider itself doesn't move. This can be used for things like conveyor belts and treadmills.
+///
+/// Only the part of the velocity that is along the contact surface is taken into account.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::{math::*, prelude::*};")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::{math::*, prelude::*};")]
+///
+/// fn setup(mut commands: Commands) {
+///     // A conveyor belt that moves bodies on top of it along the x axis
+///     commands.spawn((
+///         RigidBody::Static,
+#[cfg_attr(feature = "2d", doc = "        Collider::cuboid(10.0, 0.5),")]
+#[cfg_attr(feature = "3d", doc = "        Collider::cuboid(10.0, 0.5, 2.0),")]
+///         SurfaceVelocity(Vector::X * 2.0),
+///     ));
+/// }
+/// ```
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Default, Deref, DerefMut, From)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct SurfaceVelocity(pub Vector);
+
 /// Automatically slows down a dynamic [rigid body](RigidBody), decreasing its
 /// [linear velocity](LinearVelocity) each frame. This can be used to simulate air resistance.
 ///
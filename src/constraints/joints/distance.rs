@@ -118,6 +118,14 @@ impl Joint for DistanceJoint {
     fn damping_angular(&self) -> Scalar {
         self.damping_angular
     }
+
+    fn force(&self) -> Vector {
+        self.force
+    }
+
+    fn torque(&self) -> Torque {
+        Torque::ZERO
+    }
 }
 
 impl DistanceJoint {
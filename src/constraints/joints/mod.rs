@@ -58,6 +58,18 @@
 //! `with_angular_velocity_damping` methods. Increasing the damping values will cause the velocities
 //! of the connected entities to decrease faster.
 //!
+//! ### Motors
+//!
+//! [Revolute joints](RevoluteJoint) and [prismatic joints](PrismaticJoint) can be driven by a [`JointMotor`]
+//! using the `with_motor` method. A motor can either drive the joint towards a target velocity or a target position,
+//! and the force or torque that it can exert can be limited.
+//!
+//! ### Breaking
+//!
+//! Joints can be made breakable by adding a [`BreakForce`] or [`BreakTorque`] component to the joint entity.
+//! When the force or torque exerted by the joint exceeds the threshold, the joint component is removed
+//! and a [`JointBroken`] event is sent.
+//!
 //! ### Other configuration
 //!
 //! Different joints may have different configuration options. Many joints allow you to change the axis of allowed
@@ -126,6 +138,24 @@ pub trait Joint: Component + PositionConstraint + AngularConstraint {
     /// Returns the angular velocity damping of the joint.
     fn damping_angular(&self) -> Scalar;
 
+    /// Returns the force exerted by the joint during the last substep.
+    ///
+    /// Returns zero by default. Joints that should be breakable with [`BreakForce`]
+    /// need to implement this.
+    fn force(&self) -> Vector {
+        Vector::ZERO
+    }
+
+    /// Returns the torque exerted by the joint during the last substep.
+    ///
+    /// This doesn't include the torque exerted by [motors](JointMotor).
+    ///
+    /// Returns zero by default. Joints that should be breakable with [`BreakTorque`]
+    /// need to implement this.
+    fn torque(&self) -> Torque {
+        Torque::ZERO
+    }
+
     /// Applies a positional correction that aligns the positions of the local attachment points `r1` and `r2`.
     ///
     /// Returns the force exerted by the alignment.
@@ -351,3 +381,182 @@ impl AngleLimit {
         None
     }
 }
+
+/// A motor that drives the relative motion of the bodies attached to a joint along the joint's free axis.
+///
+/// Motors are supported by [`RevoluteJoint`] and [`PrismaticJoint`]. For revolute joints, the targets are
+/// angles and angular velocities, and for prismatic joints, they are distances and linear velocities
+/// along the free axis.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(mut commands: Commands) {
+///     let chassis = commands.spawn(RigidBody::Dynamic).id();
+///     let wheel = commands.spawn(RigidBody::Dynamic).id();
+///
+///     // Spin the wheel at 10 radians per second, using a maximum torque of 50 Newton meters
+///     commands.spawn(
+///         RevoluteJoint::new(chassis, wheel)
+///             .with_motor(JointMotor::velocity(10.0).with_max_force(50.0)),
+///     );
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct JointMotor {
+    /// The target that the motor drives the joint towards.
+    pub target: MotorTarget,
+    /// The maximum force or torque that the motor can exert. Infinite by default.
+    ///
+    /// Note that a position motor with a limited force can overshoot its target and oscillate around it,
+    /// especially if the joint has little damping.
+    pub max_force: Scalar,
+    /// The motor's compliance, the inverse of stiffness. Zero by default.
+    ///
+    /// A higher compliance makes the motor reach its target more softly, like a spring.
+    pub compliance: Scalar,
+}
+
+/// The target of a [`JointMotor`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum MotorTarget {
+    /// The motor drives the relative velocity of the bodies towards the given target velocity.
+    Velocity(Scalar),
+    /// The motor drives the relative position of the bodies towards the given target position.
+    Position(Scalar),
+}
+
+impl JointMotor {
+    /// Creates a motor that drives the joint towards the given target velocity.
+    pub fn velocity(target_velocity: Scalar) -> Self {
+        Self {
+            target: MotorTarget::Velocity(target_velocity),
+            max_force: Scalar::MAX,
+            compliance: 0.0,
+        }
+    }
+
+    /// Creates a motor that drives the joint towards the given target position.
+    pub fn position(target_position: Scalar) -> Self {
+        Self {
+            target: MotorTarget::Position(target_position),
+            max_force: Scalar::MAX,
+            compliance: 0.0,
+        }
+    }
+
+    /// Sets the maximum force or torque that the motor can exert.
+    pub fn with_max_force(self, max_force: Scalar) -> Self {
+        Self { max_force, ..self }
+    }
+
+    /// Sets the motor's compliance (inverse of stiffness).
+    pub fn with_compliance(self, compliance: Scalar) -> Self {
+        Self { compliance, ..self }
+    }
+
+    /// Computes the constraint error of the motor based on the current and previous
+    /// relative positions of the bodies.
+    ///
+    /// If `wrap_angle` is `true`, the positions are treated as angles, and the error is wrapped to `[-PI, PI]`.
+    pub(crate) fn compute_error(
+        &self,
+        position: Scalar,
+        previous_position: Scalar,
+        wrap_angle: bool,
+        dt: Scalar,
+    ) -> Scalar {
+        let wrap = |angle: Scalar| {
+            if wrap_angle {
+                (angle + PI).rem_euclid(2.0 * PI) - PI
+            } else {
+                angle
+            }
+        };
+        match self.target {
+            MotorTarget::Velocity(velocity) => wrap(position - previous_position) - velocity * dt,
+            MotorTarget::Position(target) => wrap(position - target),
+        }
+    }
+
+    /// Computes the Lagrange multiplier update of the motor, limited so that the motor
+    /// doesn't exceed its maximum force or torque.
+    pub(crate) fn limit_lagrange_update(
+        &self,
+        lagrange: Scalar,
+        delta_lagrange: Scalar,
+        dt: Scalar,
+    ) -> Scalar {
+        let max_lagrange = self.max_force * dt.powi(2);
+        (lagrange + delta_lagrange).clamp(-max_lagrange, max_lagrange) - lagrange
+    }
+}
+
+/// The maximum force that a [joint](joints) can exert before it breaks.
+///
+/// When the magnitude of the [force](Joint::force) exerted by the joint exceeds this value,
+/// the joint component is removed and a [`JointBroken`] event is sent.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(mut commands: Commands) {
+///     let entity1 = commands.spawn(RigidBody::Dynamic).id();
+///     let entity2 = commands.spawn(RigidBody::Dynamic).id();
+///
+///     // Connect the bodies with a fixed joint that breaks when the force exceeds 100 Newtons
+///     commands.spawn((FixedJoint::new(entity1, entity2), BreakForce(100.0)));
+/// }
+/// ```
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct BreakForce(pub Scalar);
+
+impl Default for BreakForce {
+    /// Returns an infinite break force, which means that the joint never breaks.
+    fn default() -> Self {
+        Self(Scalar::MAX)
+    }
+}
+
+/// The maximum torque that a [joint](joints) can exert before it breaks.
+///
+/// When the magnitude of the [torque](Joint::torque) exerted by the joint exceeds this value,
+/// the joint component is removed and a [`JointBroken`] event is sent.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct BreakTorque(pub Scalar);
+
+impl Default for BreakTorque {
+    /// Returns an infinite break torque, which means that the joint never breaks.
+    fn default() -> Self {
+        Self(Scalar::MAX)
+    }
+}
+
+/// An event that is sent when a joint breaks because its [`BreakForce`] or [`BreakTorque`] was exceeded.
+///
+/// The joint component is removed from the joint entity, but the entity itself is not despawned.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct JointBroken {
+    /// The entity that the broken joint was attached to.
+    pub joint_entity: Entity,
+    /// The entities that were connected by the joint.
+    pub entities: [Entity; 2],
+    /// The force exerted by the joint when it broke.
+    pub force: Vector,
+    /// The torque exerted by the joint when it broke.
+    pub torque: Torque,
+}
@@ -24,6 +24,8 @@ pub struct PrismaticJoint {
     pub free_axis: Vector,
     /// The extents of the allowed relative translation along the free axis.
     pub free_axis_limits: Option<DistanceLimit>,
+    /// A motor that drives the relative translation of the bodies along the free axis.
+    pub motor: Option<JointMotor>,
     /// Linear damping applied by the joint.
     pub damping_linear: Scalar,
     /// Angular damping applied by the joint.
@@ -32,12 +34,16 @@ pub struct PrismaticJoint {
     pub position_lagrange: Scalar,
     /// Lagrange multiplier for the angular correction caused by the alignment of the bodies.
     pub align_lagrange: Scalar,
+    /// Lagrange multiplier for the positional correction caused by the motor.
+    pub motor_lagrange: Scalar,
     /// The joint's compliance, the inverse of stiffness, has the unit meters / Newton.
     pub compliance: Scalar,
     /// The force exerted by the joint.
     pub force: Vector,
     /// The torque exerted by the joint when aligning the bodies.
     pub align_torque: Torque,
+    /// The force exerted by the motor.
+    pub motor_force: Vector,
 }
 
 impl XpbdConstraint<2> for PrismaticJoint {
@@ -48,6 +54,7 @@ impl XpbdConstraint<2> for PrismaticJoint {
     fn clear_lagrange_multipliers(&mut self) {
         self.position_lagrange = 0.0;
         self.align_lagrange = 0.0;
+        self.motor_lagrange = 0.0;
     }
 
     fn solve(&mut self, bodies: [&mut RigidBodyQueryItem; 2], dt: Scalar) {
@@ -62,6 +69,9 @@ impl XpbdConstraint<2> for PrismaticJoint {
 
         // Constrain the relative positions of the bodies, only allowing translation along one free axis
         self.force = self.constrain_positions(body1, body2, dt);
+
+        // Drive the relative translation along the free axis with the motor
+        self.motor_force = self.apply_motor(body1, body2, dt);
     }
 }
 
@@ -74,16 +84,19 @@ impl Joint for PrismaticJoint {
             local_anchor2: Vector::ZERO,
             free_axis: Vector::X,
             free_axis_limits: None,
+            motor: None,
             damping_linear: 1.0,
             damping_angular: 1.0,
             position_lagrange: 0.0,
             align_lagrange: 0.0,
+            motor_lagrange: 0.0,
             compliance: 0.0,
             force: Vector::ZERO,
             #[cfg(feature = "2d")]
             align_torque: 0.0,
             #[cfg(feature = "3d")]
             align_torque: Vector::ZERO,
+            motor_force: Vector::ZERO,
         }
     }
 
@@ -134,6 +147,14 @@ impl Joint for PrismaticJoint {
     fn damping_angular(&self) -> Scalar {
         self.damping_angular
     }
+
+    fn force(&self) -> Vector {
+        self.force
+    }
+
+    fn torque(&self) -> Torque {
+        self.align_torque
+    }
 }
 
 impl PrismaticJoint {
@@ -238,6 +259,79 @@ impl PrismaticJoint {
         }
     }
 
+    /// Sets the motor that drives the relative translation of the bodies along the free axis.
+    ///
+    /// The targets of the motor are distances and linear velocities along the free axis.
+    pub fn with_motor(self, motor: JointMotor) -> Self {
+        Self {
+            motor: Some(motor),
+            ..self
+        }
+    }
+
+    /// Applies the motor to drive the relative translation of the bodies along the free axis.
+    ///
+    /// Returns the force exerted by the motor.
+    fn apply_motor(
+        &mut self,
+        body1: &mut RigidBodyQueryItem,
+        body2: &mut RigidBodyQueryItem,
+        dt: Scalar,
+    ) -> Vector {
+        let Some(motor) = self.motor else {
+            return Vector::ZERO;
+        };
+
+        let world_r1 = body1.rotation.rotate(self.local_anchor1);
+        let world_r2 = body2.rotation.rotate(self.local_anchor2);
+        let axis1 = body1.rotation.rotate(self.free_axis);
+
+        // The current and previous translations along the free axis
+        let translation =
+            (body2.current_position() + world_r2 - body1.current_position() - world_r1).dot(axis1);
+        let previous_translation = (body2.previous_position.0
+            + body2.previous_rotation.rotate(self.local_anchor2)
+            - body1.previous_position.0
+            - body1.previous_rotation.rotate(self.local_anchor1))
+        .dot(body1.previous_rotation.rotate(self.free_axis));
+
+        let c = motor.compute_error(translation, previous_translation, false, dt);
+
+        if c.abs() <= Scalar::EPSILON {
+            return Vector::ZERO;
+        }
+
+        // The correction is applied in the opposite direction of the free axis,
+        // moving the bodies towards each other when the error is positive
+        let dir = -axis1;
+
+        // Compute generalized inverse masses
+        let w1 = PositionConstraint::compute_generalized_inverse_mass(self, body1, world_r1, dir);
+        let w2 = PositionConstraint::compute_generalized_inverse_mass(self, body2, world_r2, dir);
+
+        // Constraint gradients and inverse masses
+        let gradients = [dir, -dir];
+        let w = [w1, w2];
+
+        // Compute Lagrange multiplier update, limited by the maximum force of the motor
+        let delta_lagrange = self.compute_lagrange_update(
+            self.motor_lagrange,
+            c,
+            &gradients,
+            &w,
+            motor.compliance,
+            dt,
+        );
+        let delta_lagrange = motor.limit_lagrange_update(self.motor_lagrange, delta_lagrange, dt);
+        self.motor_lagrange += delta_lagrange;
+
+        // Apply positional correction to drive the bodies
+        self.apply_positional_correction(body1, body2, delta_lagrange, dir, world_r1, world_r2);
+
+        // Return motor force
+        self.compute_force(self.motor_lagrange, dir, dt)
+    }
+
     #[cfg(feature = "2d")]
     fn get_delta_q(&self, rot1: &Rotation, rot2: &Rotation) -> Vector3 {
         (*rot2 - *rot1).as_radians() * Vector3::Z
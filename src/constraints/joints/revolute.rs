@@ -30,6 +30,8 @@ pub struct RevoluteJoint {
     pub aligned_axis: Vector,
     /// The extents of the allowed relative rotation of the bodies around the `aligned_axis`.
     pub angle_limit: Option<AngleLimit>,
+    /// A motor that drives the relative rotation of the bodies around the `aligned_axis`.
+    pub motor: Option<JointMotor>,
     /// Linear damping applied by the joint.
     pub damping_linear: Scalar,
     /// Angular damping applied by the joint.
@@ -40,6 +42,8 @@ pub struct RevoluteJoint {
     pub align_lagrange: Scalar,
     /// Lagrange multiplier for the angular correction caused by the angle limits.
     pub angle_limit_lagrange: Scalar,
+    /// Lagrange multiplier for the angular correction caused by the motor.
+    pub motor_lagrange: Scalar,
     /// The joint's compliance, the inverse of stiffness, has the unit meters / Newton.
     pub compliance: Scalar,
     /// The force exerted by the joint.
@@ -48,6 +52,8 @@ pub struct RevoluteJoint {
     pub align_torque: Torque,
     /// The torque exerted by the joint when limiting the relative rotation of the bodies around the `aligned_axis`.
     pub angle_limit_torque: Torque,
+    /// The torque exerted by the motor.
+    pub motor_torque: Torque,
 }
 
 impl XpbdConstraint<2> for RevoluteJoint {
@@ -59,6 +65,7 @@ impl XpbdConstraint<2> for RevoluteJoint {
         self.position_lagrange = 0.0;
         self.align_lagrange = 0.0;
         self.angle_limit_lagrange = 0.0;
+        self.motor_lagrange = 0.0;
     }
 
     fn solve(&mut self, bodies: [&mut RigidBodyQueryItem; 2], dt: Scalar) {
@@ -86,6 +93,9 @@ impl XpbdConstraint<2> for RevoluteJoint {
 
         // Apply angle limits when rotating around the free axis
         self.angle_limit_torque = self.apply_angle_limits(body1, body2, dt);
+
+        // Drive the relative rotation around the free axis with the motor
+        self.motor_torque = self.apply_motor(body1, body2, dt);
     }
 }
 
@@ -98,11 +108,13 @@ impl Joint for RevoluteJoint {
             local_anchor2: Vector::ZERO,
             aligned_axis: Vector3::Z,
             angle_limit: None,
+            motor: None,
             damping_linear: 1.0,
             damping_angular: 1.0,
             position_lagrange: 0.0,
             align_lagrange: 0.0,
             angle_limit_lagrange: 0.0,
+            motor_lagrange: 0.0,
             compliance: 0.0,
             force: Vector::ZERO,
             #[cfg(feature = "2d")]
@@ -113,6 +125,10 @@ impl Joint for RevoluteJoint {
             angle_limit_torque: 0.0,
             #[cfg(feature = "3d")]
             angle_limit_torque: Vector::ZERO,
+            #[cfg(feature = "2d")]
+            motor_torque: 0.0,
+            #[cfg(feature = "3d")]
+            motor_torque: Vector::ZERO,
         }
     }
 
@@ -163,6 +179,14 @@ impl Joint for RevoluteJoint {
     fn damping_angular(&self) -> Scalar {
         self.damping_angular
     }
+
+    fn force(&self) -> Vector {
+        self.force
+    }
+
+    fn torque(&self) -> Torque {
+        self.align_torque + self.angle_limit_torque
+    }
 }
 
 impl RevoluteJoint {
@@ -183,6 +207,16 @@ impl RevoluteJoint {
         }
     }
 
+    /// Sets the motor that drives the relative rotation of the bodies around the `aligned_axis`.
+    ///
+    /// The targets of the motor are angles in radians and angular velocities in radians per second.
+    pub fn with_motor(self, motor: JointMotor) -> Self {
+        Self {
+            motor: Some(motor),
+            ..self
+        }
+    }
+
     fn get_delta_q(&self, rot1: &Rotation, rot2: &Rotation) -> Vector3 {
         let a1 = rot1.rotate_vec3(self.aligned_axis);
         let a2 = rot2.rotate_vec3(self.aligned_axis);
@@ -217,6 +251,77 @@ impl RevoluteJoint {
         }
         Torque::ZERO
     }
+
+    /// Returns the signed angle of the second body relative to the first body around the `aligned_axis`.
+    fn relative_angle(&self, rot1: &Rotation, rot2: &Rotation) -> Scalar {
+        // Measure the angle between two rotated copies of a vector perpendicular to the axis
+        let axis = self.aligned_axis.normalize();
+        let reference_axis = axis.any_orthonormal_vector();
+        let a1 = rot1.rotate_vec3(reference_axis);
+        let a2 = rot2.rotate_vec3(reference_axis);
+        let n = rot1.rotate_vec3(axis);
+        a1.cross(a2).dot(n).atan2(a1.dot(a2))
+    }
+
+    /// Applies the motor to drive the relative rotation of the bodies around the `aligned_axis`.
+    ///
+    /// Returns the torque exerted by the motor.
+    fn apply_motor(
+        &mut self,
+        body1: &mut RigidBodyQueryItem,
+        body2: &mut RigidBodyQueryItem,
+        dt: Scalar,
+    ) -> Torque {
+        let Some(motor) = self.motor else {
+            return Torque::ZERO;
+        };
+
+        let angle = self.relative_angle(&body1.rotation, &body2.rotation);
+        let previous_angle =
+            self.relative_angle(&body1.previous_rotation.0, &body2.previous_rotation.0);
+        let c = motor.compute_error(angle, previous_angle, true, dt);
+
+        if c.abs() <= Scalar::EPSILON {
+            return Torque::ZERO;
+        }
+
+        let axis = body1.rotation.rotate_vec3(self.aligned_axis);
+
+        // Compute generalized inverse masses
+        let w1 = AngularConstraint::compute_generalized_inverse_mass(self, body1, axis);
+        let w2 = AngularConstraint::compute_generalized_inverse_mass(self, body2, axis);
+
+        // Constraint gradients and inverse masses
+        let gradients = {
+            #[cfg(feature = "2d")]
+            {
+                [Vector::Y * axis.z, Vector::NEG_Y * axis.z]
+            }
+            #[cfg(feature = "3d")]
+            {
+                [axis, -axis]
+            }
+        };
+        let w = [w1, w2];
+
+        // Compute Lagrange multiplier update, limited by the maximum torque of the motor
+        let delta_lagrange = self.compute_lagrange_update(
+            self.motor_lagrange,
+            c,
+            &gradients,
+            &w,
+            motor.compliance,
+            dt,
+        );
+        let delta_lagrange = motor.limit_lagrange_update(self.motor_lagrange, delta_lagrange, dt);
+        self.motor_lagrange += delta_lagrange;
+
+        // Apply angular correction to drive the bodies
+        self.apply_angular_correction(body1, body2, delta_lagrange, axis);
+
+        // Return motor torque
+        self.compute_torque(self.motor_lagrange, axis, dt)
+    }
 }
 
 impl PositionConstraint for RevoluteJoint {}
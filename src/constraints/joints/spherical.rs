@@ -160,6 +160,14 @@ impl Joint for SphericalJoint {
     fn damping_angular(&self) -> Scalar {
         self.damping_angular
     }
+
+    fn force(&self) -> Vector {
+        self.force
+    }
+
+    fn torque(&self) -> Torque {
+        self.swing_torque + self.twist_torque
+    }
 }
 
 impl SphericalJoint {
@@ -16,6 +16,10 @@ pub struct PenetrationConstraint {
     pub entity1: Entity,
     /// Second entity in the constraint.
     pub entity2: Entity,
+    /// The entity of the first collider in the contact. For child colliders, this is different from [`entity1`](Self::entity1).
+    pub collider_entity1: Entity,
+    /// The entity of the second collider in the contact. For child colliders, this is different from [`entity2`](Self::entity2).
+    pub collider_entity2: Entity,
     /// Data associated with the contact.
     pub contact: ContactData,
     /// Vector from the first entity's center of mass to the contact point in local coordinates.
@@ -54,12 +58,21 @@ impl XpbdConstraint<2> for PenetrationConstraint {
     fn solve(&mut self, bodies: [&mut RigidBodyQueryItem; 2], dt: Scalar) {
         let [body1, body2] = bodies;
 
+        // Warm start the contact by applying the correction of the previous substep
+        if self.normal_lagrange != 0.0 {
+            let normal = self.contact.global_normal1(&body1.rotation);
+            let r1 = body1.rotation.rotate(self.r1);
+            let r2 = body2.rotation.rotate(self.r2);
+            self.apply_positional_correction(body1, body2, self.normal_lagrange, normal, r1, r2);
+        }
+
         let p1 = body1.current_position() + body1.rotation.rotate(self.contact.point1);
         let p2 = body2.current_position() + body2.rotation.rotate(self.contact.point2);
         self.contact.penetration = (p1 - p2).dot(self.contact.global_normal1(&body1.rotation));
 
-        // If penetration depth is under 0, skip the collision
-        if self.contact.penetration <= Scalar::EPSILON {
+        // If penetration depth is under 0 and there is no warm starting correction to undo, skip the collision
+        if self.contact.penetration <= Scalar::EPSILON && self.normal_lagrange == 0.0 {
+            self.contact.friction_anchors = None;
             return;
         }
 
@@ -70,10 +83,30 @@ impl XpbdConstraint<2> for PenetrationConstraint {
 
 impl PenetrationConstraint {
     /// Creates a new [`PenetrationConstraint`] with the given bodies and contact data.
+    /// The bodies are also used as the colliders in the contact.
+    ///
+    /// The normal Lagrange multiplier is initialized with the [`ContactData::normal_lagrange`]
+    /// of the contact, which is used for warm starting the constraint.
     pub fn new(
         body1: &RigidBodyQueryItem,
         body2: &RigidBodyQueryItem,
         contact: ContactData,
+    ) -> Self {
+        Self::new_with_colliders(body1, body2, body1.entity, body2.entity, contact)
+    }
+
+    /// Creates a new [`PenetrationConstraint`] with the given bodies, the entities of the colliders
+    /// in the contact and contact data. For colliders that are attached directly to the bodies,
+    /// the collider entities are the same as the body entities.
+    ///
+    /// The normal Lagrange multiplier is initialized with the [`ContactData::normal_lagrange`]
+    /// of the contact, which is used for warm starting the constraint.
+    pub fn new_with_colliders(
+        body1: &RigidBodyQueryItem,
+        body2: &RigidBodyQueryItem,
+        collider_entity1: Entity,
+        collider_entity2: Entity,
+        contact: ContactData,
     ) -> Self {
         let r1 = contact.point1 - body1.center_of_mass.0;
         let r2 = contact.point2 - body2.center_of_mass.0;
@@ -81,10 +114,12 @@ impl PenetrationConstraint {
         Self {
             entity1: body1.entity,
             entity2: body2.entity,
+            collider_entity1,
+            collider_entity2,
             contact,
             r1,
             r2,
-            normal_lagrange: 0.0,
+            normal_lagrange: contact.normal_lagrange,
             tangent_lagrange: 0.0,
             compliance: 0.0,
             dynamic_friction_coefficient: 0.0,
@@ -118,9 +153,11 @@ impl PenetrationConstraint {
         let gradients = [normal, -normal];
         let w = [w1, w2];
 
-        // Compute Lagrange multiplier update
+        // Compute Lagrange multiplier update.
+        // The total correction is clamped so that the contact can only push the bodies apart.
         let delta_lagrange =
             self.compute_lagrange_update(lagrange, penetration, &gradients, &w, compliance, dt);
+        let delta_lagrange = (lagrange + delta_lagrange).min(0.0) - lagrange;
         self.normal_lagrange += delta_lagrange;
 
         // Apply positional correction to solve overlap
@@ -139,24 +176,44 @@ impl PenetrationConstraint {
         // Shorter aliases
         let compliance = self.compliance;
         let lagrange = self.tangent_lagrange;
-        let penetration = self.contact.penetration;
         let normal = self.contact.global_normal1(&body1.rotation);
         let r1 = body1.rotation.rotate(self.r1);
         let r2 = body2.rotation.rotate(self.r2);
 
-        // Compute relative motion of the contact points and get the tangential component
-        let delta_p1 = body1.current_position() - body1.previous_position.0
-            + body1.rotation.rotate(self.contact.point1)
-            - body1.previous_rotation.rotate(self.contact.point1);
-        let delta_p2 = body2.current_position() - body2.previous_position.0
-            + body2.rotation.rotate(self.contact.point2)
-            - body2.previous_rotation.rotate(self.contact.point2);
-        let delta_p = delta_p1 - delta_p2;
+        // The bodies are separated, so there is no friction
+        if self.normal_lagrange == 0.0 {
+            self.contact.friction_anchors = None;
+            return;
+        }
+
+        // Compute the relative motion of the contact points and get the tangential component.
+        // If static friction held the contact in place during the previous substep, the motion is
+        // measured from the anchor points, so that the bodies don't creep over multiple substeps.
+        // Contacts with a target tangent velocity are moving and can't be anchored.
+        let tangent_velocity = self.contact.tangent_velocity;
+        let anchors = self
+            .contact
+            .friction_anchors
+            .filter(|_| tangent_velocity == Vector::ZERO);
+        let delta_p = if let Some((anchor1, anchor2)) = anchors {
+            body1.current_position() + body1.rotation.rotate(anchor1)
+                - body2.current_position()
+                - body2.rotation.rotate(anchor2)
+        } else {
+            let delta_p1 = body1.current_position() - body1.previous_position.0
+                + body1.rotation.rotate(self.contact.point1)
+                - body1.previous_rotation.rotate(self.contact.point1);
+            let delta_p2 = body2.current_position() - body2.previous_position.0
+                + body2.rotation.rotate(self.contact.point2)
+                - body2.previous_rotation.rotate(self.contact.point2);
+            delta_p1 - delta_p2 - tangent_velocity * dt
+        };
         let delta_p_tangent = delta_p - delta_p.dot(normal) * normal;
 
         // Compute magnitude of relative tangential movement and get normalized tangent vector
         let sliding_len = delta_p_tangent.length();
         if sliding_len <= Scalar::EPSILON {
+            self.hold_friction_anchors();
             return;
         }
         let tangent = delta_p_tangent / sliding_len;
@@ -169,11 +226,14 @@ impl PenetrationConstraint {
         let gradients = [tangent, -tangent];
         let w = [w1, w2];
 
-        // Apply static friction if |delta_x_perp| < mu_s * d
-        if sliding_len < self.static_friction_coefficient * penetration {
-            // Compute Lagrange multiplier update for static friction
-            let delta_lagrange =
-                self.compute_lagrange_update(lagrange, sliding_len, &gradients, &w, compliance, dt);
+        // Compute Lagrange multiplier update for static friction
+        let delta_lagrange =
+            self.compute_lagrange_update(lagrange, sliding_len, &gradients, &w, compliance, dt);
+
+        // Apply static friction if |lambda_t| < mu_s * |lambda_n|
+        if (lagrange + delta_lagrange).abs()
+            < self.static_friction_coefficient * self.normal_lagrange.abs()
+        {
             self.tangent_lagrange += delta_lagrange;
 
             // Apply positional correction to handle static friction
@@ -181,8 +241,24 @@ impl PenetrationConstraint {
 
             // Update static friction force using the equation f = lambda * n / h^2
             self.static_friction_force = self.tangent_lagrange * tangent / dt.powi(2);
+
+            self.hold_friction_anchors();
+        } else {
+            // The contact is sliding, so dynamic friction is applied in the velocity solve instead
+            self.contact.friction_anchors = None;
         }
     }
+
+    /// Keeps the current friction anchors, or anchors the contact at the current contact points
+    /// if it wasn't anchored yet. Contacts with a target tangent velocity are never anchored.
+    fn hold_friction_anchors(&mut self) {
+        self.contact.friction_anchors =
+            (self.contact.tangent_velocity == Vector::ZERO).then(|| {
+                self.contact
+                    .friction_anchors
+                    .unwrap_or((self.contact.point1, self.contact.point2))
+            });
+    }
 }
 
 impl PositionConstraint for PenetrationConstraint {}
@@ -191,5 +267,7 @@ impl MapEntities for PenetrationConstraint {
     fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
         self.entity1 = entity_mapper.get_or_reserve(self.entity1);
         self.entity2 = entity_mapper.get_or_reserve(self.entity2);
+        self.collider_entity1 = entity_mapper.get_or_reserve(self.collider_entity1);
+        self.collider_entity2 = entity_mapper.get_or_reserve(self.collider_entity2);
     }
 }
@@ -51,15 +51,25 @@
 //! | `3d`                   | Enables 3D physics. Incompatible with `2d`.                                                                                      | Yes (`bevy_xpbd_3d`)    |
 //! | `f32`                  | Enables `f32` precision for physics. Incompatible with `f64`.                                                                    | Yes                     |
 //! | `f64`                  | Enables `f64` precision for physics. Incompatible with `f32`.                                                                    | No                      |
+//! | `collider-from-mesh`   | Allows you to create [`Collider`]s from `Mesh`es.                                                                                | Yes                     |
 #![cfg_attr(
-    feature = "3d",
-    doc = "| `collider-from-mesh`   | Allows you to create [`Collider`]s from `Mesh`es.                                                                                | Yes                     |"
+    feature = "2d",
+    doc = "| `collider-from-image`  | Allows you to create [`Collider`]s from the alpha channel of `Image`s.                                                           | No                      |"
+)]
+#![cfg_attr(
+    feature = "2d",
+    doc = "| `async-collider`       | Allows you to generate [`Collider`]s from 2D mesh handles.                                                                       | No                      |"
 )]
 #![cfg_attr(
     feature = "3d",
     doc = "| `async-collider`       | Allows you to generate [`Collider`]s from mesh handles and scenes.                                                               | Yes                     |"
 )]
 //! | `debug-plugin`         | Enables physics debug rendering using the [`PhysicsDebugPlugin`]. The plugin must be added separately.                           | Yes                     |
+//! | `soft-body-mesh`       | Allows you to create [`SoftBody`]s from `Mesh`es and updates the meshes of soft bodies.                                          | No                      |
+//! | `physics-material`     | Enables `PhysicsMaterial` assets loaded from `.physmat.ron` files. The `PhysicsMaterialPlugin` must be added separately.         | No                      |
+//! | `collision-matrix-asset` | Enables loading the [`CollisionMatrix`] from `.collmat.ron` files.                                                             | No                      |
+//! | `layers-64`            | Uses 64-bit [`LayerMask`]s, which allows up to 64 [collision layers](CollisionLayers).                                           | No                      |
+//! | `layers-128`           | Uses 128-bit [`LayerMask`]s, which allows up to 128 [collision layers](CollisionLayers).                                         | No                      |
 //! | `enhanced-determinism` | Enables increased determinism.                                                                                                   | No                      |
 //! | `parallel`             | Enables some extra multithreading, which improves performance for larger simulations but can add some overhead for smaller ones. | Yes                     |
 //! | `simd`                 | Enables [SIMD] optimizations.                                                                                                    | No                      |
@@ -122,6 +132,14 @@
 //! - [Lock translational and rotational axes](LockedAxes)
 //! - [Dominance]
 //! - [Automatic deactivation with sleeping](Sleeping)
+//! - [Character controllers](CharacterController)
+//! - [Vehicles](Vehicle)
+//!
+//! ### Soft bodies and fluids
+//!
+//! - [Soft bodies and cloth](SoftBody)
+//! - [Distance](ParticleDistanceConstraint) and [volume](ParticleVolumeConstraint) constraints
+//! - [Position-based fluids](Fluid)
 //!
 //! ### Collision detection
 //!
@@ -129,8 +147,17 @@
 //!     - [Creation](Collider#creation)
 //!     - [Density](ColliderDensity)
 //!     - [Friction] and [restitution](Restitution) (bounciness)
+//!     - Physics materials loaded from `.physmat.ron` files (requires `physics-material` feature)
+//!     - [Surface velocity](SurfaceVelocity) for conveyor belts
 //!     - [Collision layers](CollisionLayers)
+//!     - [Collision matrix](CollisionMatrix) with named layers
+//!     - [Collision groups](CollisionGroup)
 //!     - [Sensors](Sensor)
+//!     - [Continuous collision detection](SweptCcd)
+#![cfg_attr(
+    feature = "2d",
+    doc = "    - Creating colliders from meshes with [`AsyncCollider`] and from images with [`Collider::from_image_alpha`]"
+)]
 #![cfg_attr(
     feature = "3d",
     doc = "    - Creating colliders from meshes with [`AsyncCollider`] and [`AsyncSceneCollider`]"
@@ -149,8 +176,10 @@
 //!     - [Prismatic joint](PrismaticJoint)
 //!     - [Revolute joint](RevoluteJoint)
 //!     - [Spherical joint](SphericalJoint)
+//!     - [Joint motors](JointMotor)
+//!     - [Breakable joints](BreakForce)
 //!
-//! Joint motors and articulations are not supported yet, but they will be implemented in a future release.
+//! Articulations are not supported yet, but they will be implemented in a future release.
 //!
 //! ### Spatial queries
 //!
@@ -180,6 +209,7 @@
 //! - [Configure the schedule used for running physics](PhysicsPlugins#custom-schedule)
 //! - [Pausing, resuming and stepping physics](Physics#pausing-resuming-and-stepping-physics)
 //! - [Usage on servers](#can-the-engine-be-used-on-servers)
+//! - [Snapshotting and restoring the physics world](PhysicsSnapshot)
 //!
 //! ### Architecture
 //!
@@ -278,16 +308,15 @@
 //!
 //! ### Is there a character controller?
 //!
-//! Bevy XPBD does not have a built-in character controller, so if you need one,
-//! you will need to implement it yourself. However, third party character controllers
-//! like [`bevy_tnua`](https://github.com/idanarye/bevy-tnua) support Bevy XPBD, and [`bevy_mod_wanderlust`](https://github.com/PROMETHIA-27/bevy_mod_wanderlust)
-//! and others are also likely to get Bevy XPBD support soon.
+//! Yes, the [`CharacterControllerPlugin`] moves [kinematic](RigidBody::Kinematic) bodies with a
+//! [`CharacterController`] using move-and-slide. It supports slopes, stairs, snapping to the ground
+//! and moving platforms, and reports whether the character is touching the ground, a ceiling or a wall
+//! in [`CharacterControllerState`]. See the `kinematic_character_2d` and `kinematic_character_3d` examples
+//! for how to use it.
 //!
-//! For custom character controllers, you can take a look at the [`basic_dynamic_character`]
-//! and [`basic_kinematic_character`] examples to get started.
-//!
-//! [`basic_dynamic_character`]: https://github.com/Jondolf/bevy_xpbd/blob/42fb8b21c756a7f4dd91071597dc251245ddaa8f/crates/bevy_xpbd_3d/examples/basic_dynamic_character.rs
-//! [`basic_kinematic_character`]: https://github.com/Jondolf/bevy_xpbd/blob/42fb8b21c756a7f4dd91071597dc251245ddaa8f/crates/bevy_xpbd_3d/examples/basic_kinematic_character.rs
+//! For more advanced features, third party character controllers like [`bevy_tnua`](https://github.com/idanarye/bevy-tnua)
+//! also support Bevy XPBD. For a dynamic character controller that is moved by forces,
+//! you can take a look at the `dynamic_character_2d` and `dynamic_character_3d` examples to get started.
 //!
 //! ### Why are there separate `Position` and `Rotation` components?
 //!
@@ -472,28 +501,45 @@ pub mod constraints;
 pub mod math;
 pub mod plugins;
 pub mod resources;
+pub mod snapshot;
 
 /// Re-exports common components, bundles, resources, plugins and types.
 pub mod prelude {
     #[cfg(feature = "debug-plugin")]
     pub use crate::plugins::debug::*;
+    #[cfg(feature = "physics-material")]
+    pub use crate::plugins::physics_material::{PhysicsMaterial, PhysicsMaterialPlugin};
     pub use crate::{
         components::*,
         constraints::{joints::*, *},
         plugins::{
+            character_controller::{CharacterController, CharacterControllerState},
             collision::{
-                broad_phase::BroadCollisionPairs,
-                contact_reporting::{Collision, CollisionEnded, CollisionStarted},
+                broad_phase::{BroadCollisionPairs, BroadPhaseAlgorithm},
+                ccd::SweptCcd,
+                collision_filter::CollisionFilter,
+                collision_matrix::*,
+                contact_reporting::{
+                    Collision, CollisionEnded, CollisionStarted, ContactForceEvent,
+                    ContactReportingConfig, SensorEntered, SensorExited,
+                },
                 narrow_phase::NarrowPhaseConfig,
+                sdf::SignedDistanceField,
+                voxels::Voxels,
                 *,
             },
+            fluid::Fluid,
             prepare::*,
             setup::*,
-            solver::solve_constraint,
+            sleeping::{link_constraint_islands, SimulationIslands},
+            soft_body::{Particle, ParticleDistanceConstraint, ParticleVolumeConstraint, SoftBody},
+            solver::{break_joints, solve_constraint},
             spatial_query::*,
+            vehicle::{Vehicle, Wheel, WheelState},
             *,
         },
         resources::*,
+        snapshot::PhysicsSnapshot,
         PhysicsSet, PostProcessCollisions,
     };
     pub(crate) use crate::{math::*, *};
@@ -559,6 +605,66 @@ pub struct SubstepSchedule;
 ///     });
 /// }
 /// ```
+///
+/// Pairs that should never interact are better rejected with a [`CollisionFilter`], which runs before
+/// the contacts are computed.
+///
+/// ## Modifying contacts
+///
+/// The [friction](ContactData::friction), [restitution](ContactData::restitution),
+/// [compliance](ContactData::compliance), [target tangent velocity](ContactData::tangent_velocity)
+/// and normals of individual contacts can also be changed before the contacts are solved,
+/// and contacts can be [disabled](ContactData::enabled). The contacts are computed again in every substep,
+/// so the modifications need to be applied in every substep as well.
+///
+/// Below is an example of one-way platforms that bodies can pass through from below, and icy surfaces.
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// #[derive(Component)]
+/// struct OneWayPlatform;
+///
+/// #[derive(Component)]
+/// struct Ice;
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+///         .add_systems(PostProcessCollisions, modify_contacts)
+///         .run();
+/// }
+///
+/// fn modify_contacts(
+///     mut collisions: ResMut<Collisions>,
+///     platforms: Query<(), With<OneWayPlatform>>,
+///     ice: Query<(), With<Ice>>,
+/// ) {
+///     for contacts in collisions.iter_mut() {
+///         let is_icy = ice.contains(contacts.entity1) || ice.contains(contacts.entity2);
+///         let is_platform1 = platforms.contains(contacts.entity1);
+///         let is_platform2 = platforms.contains(contacts.entity2);
+///
+///         for manifold in contacts.manifolds.iter_mut() {
+///             for contact in manifold.contacts.iter_mut() {
+///                 if is_icy {
+///                     contact.friction = Some(Friction::ZERO);
+///                 }
+///
+///                 // Only collide with the top of the platforms. The normals are in the local space
+///                 // of the entities, and the platforms aren't rotated in this example.
+///                 if (is_platform1 && contact.normal1.y < 0.5)
+///                     || (is_platform2 && contact.normal2.y < 0.5)
+///                 {
+///                     contact.enabled = false;
+///                 }
+///             }
+///         }
+///     }
+/// }
+/// ```
 #[derive(Debug, Hash, PartialEq, Eq, Clone, ScheduleLabel)]
 pub struct PostProcessCollisions;
 
@@ -608,9 +714,10 @@ pub enum PhysicsSet {
 ///     3. Solve positional and angular constraints
 ///     4. Update velocities
 ///     5. Solve velocity constraints (dynamic friction and restitution)
-/// 3. Report contacts (send collision events)
-/// 4. Sleeping
-/// 5. Spatial queries
+/// 3. Swept continuous collision detection
+/// 4. Report contacts (send collision events)
+/// 5. Sleeping
+/// 6. Spatial queries
 #[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PhysicsStepSet {
     /// Responsible for collecting pairs of potentially colliding entities into [`BroadCollisionPairs`] using
@@ -622,6 +729,10 @@ pub enum PhysicsStepSet {
     ///
     /// See [`SubstepSet`] and [`SubstepSchedule`].
     Substeps,
+    /// Responsible for preventing fast moving bodies with [`SweptCcd`] from tunneling through colliders.
+    ///
+    /// See [`CcdPlugin`].
+    SweptCcd,
     /// Responsible for sending collision events and updating [`CollidingEntities`].
     ///
     /// See [`ContactReportingPlugin`].
@@ -0,0 +1,613 @@
+//! A kinematic character controller that moves characters using *move-and-slide*.
+//!
+//! See [`CharacterControllerPlugin`] and [`CharacterController`].
+
+use crate::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+
+/// The maximum number of iterations used for pushing characters out of colliders they are overlapping.
+const DEPENETRATION_ITERATIONS: usize = 4;
+
+/// Moves [kinematic](RigidBody::Kinematic) rigid bodies with a [`CharacterController`] using *move-and-slide*.
+///
+/// At the start of each physics step, the [`LinearVelocity`] of each character is treated as the velocity
+/// the character *wants* to move with. The character's [`Collider`] is cast along the movement using the
+/// [`SpatialQueryPipeline`], and whenever it hits something, the character is stopped at the surface
+/// and the remaining movement is slid along it. This handles:
+///
+/// - Walking up and down slopes that are not steeper than the [maximum slope angle](CharacterController::max_slope_angle)
+/// - Stepping up small ledges like stairs, up to the [step height](CharacterController::step_height)
+/// - Snapping down to the ground when walking down slopes and stairs, up to the [snap distance](CharacterController::snap_to_ground)
+/// - Moving along with the platform the character is standing on, and keeping its velocity when jumping off
+///
+/// The velocity required to reach the resolved position is then given to the body for the duration of the step,
+/// so the character interacts with dynamic bodies like any other kinematic body.
+/// After the step, the [`LinearVelocity`] is replaced by the character's velocity with the components
+/// into the hit surfaces removed. For example, falling velocity is removed when the character lands on the ground.
+///
+/// The results of the movement, like whether the character is on the ground, are stored in the
+/// [`CharacterControllerState`] component, which is added automatically.
+///
+/// The controller doesn't apply gravity or handle input, so that it can be used for any kind of movement.
+///
+/// The plugin requires the [`SpatialQueryPlugin`]. Without it, characters are moved like normal kinematic bodies,
+/// and a warning is logged. The movement is computed in a system that runs before
+/// [`PhysicsStepSet::BroadPhase`], and the velocity is restored after [`PhysicsStepSet::Substeps`].
+pub struct CharacterControllerPlugin;
+
+impl Plugin for CharacterControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CharacterController>()
+            .register_type::<CharacterControllerState>();
+
+        let physics_schedule = app
+            .get_schedule_mut(PhysicsSchedule)
+            .expect("add PhysicsSchedule first");
+
+        physics_schedule
+            .add_systems(move_and_slide.before(PhysicsStepSet::BroadPhase))
+            .add_systems(
+                restore_character_velocities
+                    .after(PhysicsStepSet::Substeps)
+                    .before(PhysicsStepSet::SweptCcd),
+            );
+    }
+}
+
+/// Makes a [kinematic](RigidBody::Kinematic) rigid body with a [`Collider`] move like a character,
+/// sliding along the surfaces it hits instead of passing through them.
+///
+/// The character is moved by setting its [`LinearVelocity`]. Gravity and other forces are not applied
+/// to kinematic bodies, so they should be added to the velocity manually.
+///
+/// Whether the character is on the ground or touching a ceiling or wall can be read from
+/// the [`CharacterControllerState`] component. See [`CharacterControllerPlugin`] for how the movement works.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         RigidBody::Kinematic,
+#[cfg_attr(feature = "2d", doc = "        Collider::capsule(20.0, 12.5),")]
+#[cfg_attr(feature = "3d", doc = "        Collider::capsule(1.0, 0.4),")]
+///         CharacterController::default()
+///             .with_max_slope_angle(0.8)
+#[cfg_attr(feature = "2d", doc = "            .with_step_height(10.0),")]
+#[cfg_attr(feature = "3d", doc = "            .with_step_height(0.3),")]
+///     ));
+/// }
+///
+/// fn jump(mut characters: Query<(&CharacterControllerState, &mut LinearVelocity)>) {
+///     for (state, mut linear_velocity) in &mut characters {
+///         if state.grounded {
+#[cfg_attr(feature = "2d", doc = "            linear_velocity.y = 400.0;")]
+#[cfg_attr(feature = "3d", doc = "            linear_velocity.y = 7.0;")]
+///         }
+///     }
+/// }
+/// ```
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct CharacterController {
+    /// The up direction of the character. Surfaces are classified as ground, walls and ceilings
+    /// based on the angle between their normals and this direction. Should be normalized.
+    pub up: Vector,
+    /// The maximum angle in radians between a surface's normal and the [up direction](Self::up)
+    /// for the surface to be considered ground. Steeper slopes are treated as walls that the character
+    /// can't walk up.
+    pub max_slope_angle: Scalar,
+    /// The maximum height of ledges that the character can step up onto, like stairs.
+    /// Stepping is disabled if the height is zero.
+    pub step_height: Scalar,
+    /// The maximum distance that the character is pulled down to stay on the ground when
+    /// walking down slopes and stairs. Snapping is disabled if the distance is zero.
+    pub snap_to_ground: Scalar,
+    /// The distance that the character keeps from the surfaces it hits. This prevents the character's
+    /// collider from getting stuck in other colliders due to numerical errors.
+    pub skin_width: Scalar,
+    /// The maximum number of times the movement can be slid along hit surfaces during a single physics step.
+    pub max_slide_iterations: u32,
+    /// If true, the character moves along with the body it is standing on, and keeps the body's velocity
+    /// when it leaves the ground, for example by jumping.
+    pub inherit_platform_velocity: bool,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            up: Vector::Y,
+            max_slope_angle: PI * 0.25,
+            #[cfg(feature = "2d")]
+            step_height: 8.0,
+            #[cfg(feature = "3d")]
+            step_height: 0.25,
+            #[cfg(feature = "2d")]
+            snap_to_ground: 4.0,
+            #[cfg(feature = "3d")]
+            snap_to_ground: 0.2,
+            #[cfg(feature = "2d")]
+            skin_width: 0.1,
+            #[cfg(feature = "3d")]
+            skin_width: 0.01,
+            max_slide_iterations: 4,
+            inherit_platform_velocity: true,
+        }
+    }
+}
+
+impl CharacterController {
+    /// Sets the [up direction](Self::up) of the character.
+    pub fn with_up(mut self, up: Vector) -> Self {
+        self.up = up;
+        self
+    }
+
+    /// Sets the [maximum slope angle](Self::max_slope_angle) in radians.
+    pub fn with_max_slope_angle(mut self, max_slope_angle: Scalar) -> Self {
+        self.max_slope_angle = max_slope_angle;
+        self
+    }
+
+    /// Sets the [step height](Self::step_height). Use zero to disable stepping.
+    pub fn with_step_height(mut self, step_height: Scalar) -> Self {
+        self.step_height = step_height;
+        self
+    }
+
+    /// Sets the [snap-to-ground distance](Self::snap_to_ground). Use zero to disable snapping.
+    pub fn with_snap_to_ground(mut self, distance: Scalar) -> Self {
+        self.snap_to_ground = distance;
+        self
+    }
+
+    /// Sets the [skin width](Self::skin_width).
+    pub fn with_skin_width(mut self, skin_width: Scalar) -> Self {
+        self.skin_width = skin_width;
+        self
+    }
+
+    /// Sets the [maximum number of slide iterations](Self::max_slide_iterations).
+    pub fn with_max_slide_iterations(mut self, iterations: u32) -> Self {
+        self.max_slide_iterations = iterations;
+        self
+    }
+
+    /// Sets whether the character [inherits the velocity](Self::inherit_platform_velocity)
+    /// of the body it is standing on.
+    pub fn with_inherit_platform_velocity(mut self, inherit: bool) -> Self {
+        self.inherit_platform_velocity = inherit;
+        self
+    }
+
+    /// Returns true if a surface with the given normal is flat enough to be walked on.
+    pub fn is_walkable(&self, normal: Vector) -> bool {
+        normal.angle_between(self.up).abs() <= self.max_slope_angle
+    }
+
+    /// Returns true if a surface with the given normal is considered a ceiling.
+    pub fn is_ceiling(&self, normal: Vector) -> bool {
+        normal.angle_between(self.up).abs() >= PI - self.max_slope_angle
+    }
+}
+
+/// The state of a [`CharacterController`] after the latest physics step.
+///
+/// This component is added and updated automatically by the [`CharacterControllerPlugin`].
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct CharacterControllerState {
+    /// True if the character is standing on a surface that is flat enough to walk on.
+    pub grounded: bool,
+    /// The normal of the ground the character is standing on, or zero if the character is not grounded.
+    pub ground_normal: Vector,
+    /// The collider entity of the ground the character is standing on.
+    pub ground_entity: Option<Entity>,
+    /// True if the character hit a ceiling.
+    pub touching_ceiling: bool,
+    /// True if the character hit a wall or a slope that is too steep to walk on.
+    pub touching_wall: bool,
+    /// The normal of the wall the character hit, or zero if the character didn't hit a wall.
+    pub wall_normal: Vector,
+    /// The velocity of the body the character is standing on at the character's position,
+    /// if [`CharacterController::inherit_platform_velocity`] is enabled.
+    pub platform_velocity: Vector,
+    /// The velocity of the character after sliding along the surfaces it hit.
+    velocity: Vector,
+}
+
+impl CharacterControllerState {
+    fn set_ground(&mut self, hit: &SurfaceHit) {
+        self.grounded = true;
+        self.ground_normal = hit.normal;
+        self.ground_entity = Some(hit.entity);
+    }
+}
+
+/// A surface hit by a character's collider.
+struct SurfaceHit {
+    /// The collider entity that was hit.
+    entity: Entity,
+    /// The distance the character can move before it is within the skin width of the surface.
+    distance: Scalar,
+    /// The outward normal of the hit surface.
+    normal: Vector,
+    /// The world-space point where the surface was hit.
+    point: Vector,
+}
+
+/// Casts the collider of a character against the rest of the world.
+struct CharacterCaster<'a> {
+    pipeline: &'a SpatialQueryPipeline,
+    collider: &'a Collider,
+    rotation: Rotation,
+    filter: SpatialQueryFilter,
+    skin_width: Scalar,
+}
+
+impl CharacterCaster<'_> {
+    /// Casts the character from `origin` in the given direction and returns the closest surface
+    /// within the given distance.
+    fn cast(&self, origin: Vector, direction: Vector, distance: Scalar) -> Option<SurfaceHit> {
+        let hit = self.pipeline.cast_shape(
+            self.collider,
+            origin,
+            rotation_value(&self.rotation),
+            direction,
+            distance + self.skin_width,
+            true,
+            self.filter.clone(),
+        )?;
+
+        Some(SurfaceHit {
+            entity: hit.entity,
+            distance: (hit.time_of_impact - self.skin_width).max(0.0),
+            normal: self.rotation.rotate(-hit.normal2),
+            point: origin + direction * hit.time_of_impact + self.rotation.rotate(hit.point2),
+        })
+    }
+
+    /// Pushes the character out of the colliders it is overlapping.
+    fn depenetrate(&self, position: &mut Vector) {
+        for _ in 0..DEPENETRATION_ITERATIONS {
+            let intersections = self.pipeline.shape_intersections(
+                self.collider,
+                *position,
+                rotation_value(&self.rotation),
+                self.filter.clone(),
+            );
+
+            if intersections.is_empty() {
+                return;
+            }
+
+            for entity in intersections {
+                let Some((isometry, collider, _)) = self.pipeline.colliders.get(&entity) else {
+                    continue;
+                };
+
+                let isometry1 = utils::make_isometry(*position, self.rotation);
+                if let Ok(Some(contact)) = self.pipeline.dispatcher.contact(
+                    &isometry1.inv_mul(isometry),
+                    self.collider.shape_scaled().0.as_ref(),
+                    collider.shape_scaled().0.as_ref(),
+                    0.0,
+                ) {
+                    // The normal is in the local space of the character
+                    let normal: Vector = (isometry1.rotation * contact.normal1).into();
+                    *position += normal * (contact.dist - self.skin_width);
+                }
+            }
+        }
+    }
+
+    /// Tries to step up onto a ledge in the direction of the horizontal part of the movement.
+    ///
+    /// Returns the position on top of the ledge, the remaining movement and the ground hit on the ledge.
+    fn step_up(
+        &self,
+        controller: &CharacterController,
+        position: Vector,
+        movement: Vector,
+    ) -> Option<(Vector, Vector, SurfaceHit)> {
+        let up = controller.up;
+        let horizontal = movement - up * movement.dot(up);
+        let length = horizontal.length();
+        if length <= Scalar::EPSILON {
+            return None;
+        }
+        let direction = horizontal / length;
+
+        // Move up, then forward, and finally back down onto the ledge
+        let rise = self
+            .cast(position, up, controller.step_height)
+            .map_or(controller.step_height, |hit| hit.distance);
+        let raised = position + up * rise;
+
+        let forward = self
+            .cast(raised, direction, length)
+            .map_or(length, |hit| hit.distance);
+        if forward <= Scalar::EPSILON {
+            return None;
+        }
+        let moved = raised + direction * forward;
+
+        let mut ground = self.cast(moved, -up, rise)?;
+        if !controller.is_walkable(ground.normal) {
+            // Rounded shapes land on the edge of the ledge, so check the surface on top of the edge instead
+            let top = self.pipeline.cast_ray(
+                ground.point + (direction + up) * self.skin_width,
+                -up,
+                2.0 * self.skin_width,
+                true,
+                self.filter.clone(),
+            )?;
+            if !controller.is_walkable(top.normal) {
+                return None;
+            }
+            ground.normal = top.normal;
+        }
+
+        Some((
+            moved - up * ground.distance,
+            direction * (length - forward),
+            ground,
+        ))
+    }
+}
+
+#[cfg(feature = "2d")]
+fn rotation_value(rotation: &Rotation) -> RotationValue {
+    rotation.as_radians()
+}
+
+#[cfg(feature = "3d")]
+fn rotation_value(rotation: &Rotation) -> RotationValue {
+    rotation.0
+}
+
+/// Removes the part of the vector that points into a surface with the given normal.
+fn remove_into_surface(vector: Vector, normal: Vector) -> Vector {
+    vector - normal * vector.dot(normal).min(0.0)
+}
+
+/// Moves character controllers using move-and-slide and sets their velocities so that
+/// they reach the resolved positions during the physics step.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn move_and_slide(
+    mut commands: Commands,
+    mut characters: Query<(
+        Entity,
+        &RigidBody,
+        &CharacterController,
+        Option<&mut CharacterControllerState>,
+        &Collider,
+        &Position,
+        &Rotation,
+        &mut LinearVelocity,
+        Option<&CollisionLayers>,
+    )>,
+    child_colliders: Query<(Entity, &ColliderParent)>,
+    sensors: Query<Entity, With<Sensor>>,
+    platforms: Query<
+        (
+            &Position,
+            &Rotation,
+            &CenterOfMass,
+            &LinearVelocity,
+            &AngularVelocity,
+        ),
+        Without<CharacterController>,
+    >,
+    collider_parents: Query<&ColliderParent>,
+    pipeline: Option<Res<SpatialQueryPipeline>>,
+    time: Res<Time<Physics>>,
+    mut warned_no_pipeline: Local<bool>,
+) {
+    let delta_secs = time.delta_seconds_f64().adjust_precision();
+    if delta_secs <= 0.0 {
+        return;
+    }
+    let Some(pipeline) =
+        pipeline_or_warn_once(&pipeline, &mut warned_no_pipeline, "`CharacterController`")
+    else {
+        return;
+    };
+
+    // Colliders attached to the characters should be ignored by their own casts
+    let mut ignored_colliders = HashMap::<Entity, Vec<Entity>>::default();
+    for (entity, parent) in &child_colliders {
+        if characters.contains(parent.get()) {
+            ignored_colliders
+                .entry(parent.get())
+                .or_default()
+                .push(entity);
+        }
+    }
+
+    for (
+        entity,
+        rb,
+        controller,
+        current_state,
+        collider,
+        position,
+        rotation,
+        mut lin_vel,
+        layers,
+    ) in &mut characters
+    {
+        if !rb.is_kinematic() {
+            continue;
+        }
+
+        let previous_state = current_state.as_deref().copied().unwrap_or_default();
+        let up = controller.up;
+
+        let mut filter = SpatialQueryFilter::new()
+            .without_entities([entity])
+            .without_entities(&sensors);
+        if let Some(colliders) = ignored_colliders.get(&entity) {
+            filter = filter.without_entities(colliders.iter().copied());
+        }
+        if let Some(layers) = layers {
+            filter = filter.with_masks_from_bits(layers.masks_bits());
+        }
+
+        let caster = CharacterCaster {
+            pipeline,
+            collider,
+            rotation: *rotation,
+            filter,
+            skin_width: controller.skin_width,
+        };
+
+        // Get the velocity of the platform the character was standing on at the character's position
+        let platform_velocity = previous_state
+            .ground_entity
+            .filter(|_| controller.inherit_platform_velocity && previous_state.grounded)
+            .and_then(|ground| collider_parents.get(ground).ok())
+            .and_then(|parent| platforms.get(parent.get()).ok())
+            .map_or(
+                Vector::ZERO,
+                |(
+                    platform_pos,
+                    platform_rot,
+                    center_of_mass,
+                    platform_lin_vel,
+                    platform_ang_vel,
+                )| {
+                    let world_com = platform_pos.0 + platform_rot.rotate(center_of_mass.0);
+                    utils::velocity_at_point(
+                        platform_lin_vel.0,
+                        platform_ang_vel.0,
+                        position.0 - world_com,
+                    )
+                },
+            );
+
+        let mut state = CharacterControllerState::default();
+        let mut velocity = lin_vel.0;
+        let mut new_position = position.0;
+
+        caster.depenetrate(&mut new_position);
+
+        let mut movement = (velocity + platform_velocity) * delta_secs;
+
+        for _ in 0..controller.max_slide_iterations {
+            let length = movement.length();
+            if length <= Scalar::EPSILON {
+                break;
+            }
+            let direction = movement / length;
+
+            let Some(hit) = caster.cast(new_position, direction, length) else {
+                new_position += movement;
+                movement = Vector::ZERO;
+                break;
+            };
+
+            new_position += direction * hit.distance;
+            movement -= direction * hit.distance;
+
+            if controller.is_walkable(hit.normal) {
+                state.set_ground(&hit);
+
+                // Walkable ground shouldn't make the character slide down
+                velocity -= up * velocity.dot(up).min(0.0);
+                movement -= up * movement.dot(up).min(0.0);
+                movement = remove_into_surface(movement, hit.normal);
+            } else if controller.is_ceiling(hit.normal) {
+                state.touching_ceiling = true;
+
+                velocity = remove_into_surface(velocity, hit.normal);
+                movement = remove_into_surface(movement, hit.normal);
+            } else {
+                let on_ground = previous_state.grounded || state.grounded;
+
+                if on_ground && controller.step_height > 0.0 {
+                    if let Some((stepped_position, remaining, ground)) =
+                        caster.step_up(controller, new_position, movement)
+                    {
+                        new_position = stepped_position;
+                        movement = remaining;
+                        state.set_ground(&ground);
+                        continue;
+                    }
+                }
+
+                state.touching_wall = true;
+                state.wall_normal = hit.normal;
+
+                // Treat steep slopes like vertical walls when on the ground so that they can't be walked up
+                let normal = if on_ground {
+                    (hit.normal - up * hit.normal.dot(up))
+                        .try_normalize()
+                        .unwrap_or(hit.normal)
+                } else {
+                    hit.normal
+                };
+
+                velocity = remove_into_surface(velocity, normal);
+                movement = remove_into_surface(movement, normal);
+            }
+        }
+
+        // Check if the character is standing on the ground, and snap it down to the ground
+        // if it was grounded before the movement
+        if !state.grounded && velocity.dot(up) <= 0.0 {
+            let distance = if previous_state.grounded {
+                controller.snap_to_ground.max(controller.skin_width)
+            } else {
+                controller.skin_width
+            };
+
+            if let Some(hit) = caster
+                .cast(new_position, -up, distance)
+                .filter(|hit| controller.is_walkable(hit.normal))
+            {
+                new_position -= up * hit.distance;
+                velocity -= up * velocity.dot(up).min(0.0);
+                state.set_ground(&hit);
+            }
+        }
+
+        if controller.inherit_platform_velocity {
+            if state.grounded {
+                state.platform_velocity = platform_velocity;
+            } else if previous_state.grounded {
+                // Keep the platform's velocity when leaving it
+                velocity += platform_velocity;
+            }
+        }
+
+        state.velocity = velocity;
+
+        // Move the body to the resolved position during the physics step
+        lin_vel.0 = (new_position - position.0) / delta_secs;
+
+        if let Some(mut current_state) = current_state {
+            *current_state = state;
+        } else {
+            commands.entity(entity).insert(state);
+        }
+    }
+}
+
+/// Replaces the velocities used for moving character controllers during the physics step
+/// with the velocities of the characters after sliding along the surfaces they hit.
+fn restore_character_velocities(
+    mut characters: Query<(&RigidBody, &CharacterControllerState, &mut LinearVelocity)>,
+) {
+    for (rb, state, mut lin_vel) in &mut characters {
+        if rb.is_kinematic() {
+            lin_vel.0 = state.velocity;
+        }
+    }
+}
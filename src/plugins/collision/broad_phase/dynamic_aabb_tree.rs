@@ -0,0 +1,351 @@
+//! A dynamic bounding volume hierarchy of [AABBs](Aabb) used by the [broad phase](super::BroadPhasePlugin).
+
+use crate::prelude::*;
+use parry::bounding_volume::{Aabb, BoundingVolume};
+
+/// The index of a node that doesn't exist.
+const NULL_NODE: usize = usize::MAX;
+
+/// A node in a [`DynamicAabbTree`]. Leaves store user data, internal nodes always have two children.
+#[derive(Clone, Debug)]
+struct TreeNode<T> {
+    /// The AABB enclosing the node and all of its descendants.
+    aabb: Aabb,
+    parent: usize,
+    child1: usize,
+    child2: usize,
+    /// The height of the subtree, where leaves have a height of `0`. Free nodes have a height of `-1`.
+    height: i32,
+    /// The user data of a leaf node.
+    data: Option<T>,
+}
+
+impl<T> TreeNode<T> {
+    fn is_leaf(&self) -> bool {
+        self.child1 == NULL_NODE
+    }
+}
+
+/// A dynamic AABB tree, a binary bounding volume hierarchy that supports incremental
+/// insertion, removal and updates of leaves.
+///
+/// The tree is kept balanced using tree rotations, and new leaves are inserted
+/// using a surface area heuristic, similar to the dynamic tree in [Box2D](https://box2d.org).
+///
+/// The leaves are identified by *proxy* indices that stay valid until the leaf is removed.
+#[derive(Clone, Debug)]
+pub(crate) struct DynamicAabbTree<T> {
+    nodes: Vec<TreeNode<T>>,
+    root: usize,
+    free_nodes: Vec<usize>,
+}
+
+impl<T> Default for DynamicAabbTree<T> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![],
+            root: NULL_NODE,
+            free_nodes: vec![],
+        }
+    }
+}
+
+impl<T> DynamicAabbTree<T> {
+    /// Inserts a new leaf with the given AABB and data and returns its proxy index.
+    pub(crate) fn insert(&mut self, aabb: Aabb, data: T) -> usize {
+        let proxy = self.allocate_node(aabb, Some(data));
+        self.insert_leaf(proxy);
+        proxy
+    }
+
+    /// Removes the leaf with the given proxy index and returns its data.
+    pub(crate) fn remove(&mut self, proxy: usize) -> Option<T> {
+        // Only leaves can be removed
+        if self.nodes.get(proxy)?.height != 0 {
+            return None;
+        }
+        self.remove_leaf(proxy);
+        self.free_node(proxy)
+    }
+
+    /// Moves the leaf with the given proxy index to a new AABB, reinserting it into the tree.
+    pub(crate) fn update(&mut self, proxy: usize, aabb: Aabb) {
+        self.remove_leaf(proxy);
+        self.nodes[proxy].aabb = aabb;
+        self.insert_leaf(proxy);
+    }
+
+    /// Returns the AABB stored for the leaf with the given proxy index.
+    pub(crate) fn aabb(&self, proxy: usize) -> &Aabb {
+        &self.nodes[proxy].aabb
+    }
+
+    /// Returns a reference to the data of the leaf with the given proxy index.
+    pub(crate) fn get(&self, proxy: usize) -> Option<&T> {
+        self.nodes.get(proxy).and_then(|node| node.data.as_ref())
+    }
+
+    /// Returns a mutable reference to the data of the leaf with the given proxy index.
+    pub(crate) fn get_mut(&mut self, proxy: usize) -> Option<&mut T> {
+        self.nodes
+            .get_mut(proxy)
+            .and_then(|node| node.data.as_mut())
+    }
+
+    /// Returns an iterator over the proxy indices and data of all leaves, ordered by proxy index.
+    pub(crate) fn leaves(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(proxy, node)| node.data.as_ref().map(|data| (proxy, data)))
+    }
+
+    /// Calls the given callback for each leaf whose AABB intersects the given AABB.
+    pub(crate) fn query(&self, aabb: &Aabb, mut callback: impl FnMut(usize, &T)) {
+        if self.root == NULL_NODE {
+            return;
+        }
+
+        let mut stack = vec![self.root];
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+
+            if !node.aabb.intersects(aabb) {
+                continue;
+            }
+
+            if let Some(data) = &node.data {
+                callback(index, data);
+            } else {
+                stack.push(node.child1);
+                stack.push(node.child2);
+            }
+        }
+    }
+
+    fn allocate_node(&mut self, aabb: Aabb, data: Option<T>) -> usize {
+        let node = TreeNode {
+            aabb,
+            parent: NULL_NODE,
+            child1: NULL_NODE,
+            child2: NULL_NODE,
+            height: 0,
+            data,
+        };
+
+        if let Some(index) = self.free_nodes.pop() {
+            self.nodes[index] = node;
+            index
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        }
+    }
+
+    fn free_node(&mut self, index: usize) -> Option<T> {
+        let node = &mut self.nodes[index];
+        node.height = -1;
+        node.parent = NULL_NODE;
+        node.child1 = NULL_NODE;
+        node.child2 = NULL_NODE;
+        self.free_nodes.push(index);
+        node.data.take()
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        if self.root == NULL_NODE {
+            self.root = leaf;
+            self.nodes[leaf].parent = NULL_NODE;
+            return;
+        }
+
+        // Find the best sibling for the new leaf using the surface area heuristic
+        let leaf_aabb = self.nodes[leaf].aabb;
+        let mut index = self.root;
+
+        while !self.nodes[index].is_leaf() {
+            let node = &self.nodes[index];
+            let (child1, child2) = (node.child1, node.child2);
+
+            let area = surface_area(&node.aabb);
+            let combined_area = surface_area(&node.aabb.merged(&leaf_aabb));
+
+            // Cost of creating a new parent for this node and the new leaf
+            let cost = 2.0 * combined_area;
+
+            // Minimum cost of pushing the leaf further down the tree
+            let inheritance_cost = 2.0 * (combined_area - area);
+
+            let cost1 = self.descend_cost(child1, &leaf_aabb) + inheritance_cost;
+            let cost2 = self.descend_cost(child2, &leaf_aabb) + inheritance_cost;
+
+            if cost < cost1 && cost < cost2 {
+                break;
+            }
+
+            index = if cost1 < cost2 { child1 } else { child2 };
+        }
+
+        let sibling = index;
+
+        // Create a new parent for the sibling and the new leaf
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate_node(leaf_aabb.merged(&self.nodes[sibling].aabb), None);
+        self.nodes[new_parent].parent = old_parent;
+        self.nodes[new_parent].height = self.nodes[sibling].height + 1;
+        self.nodes[new_parent].child1 = sibling;
+        self.nodes[new_parent].child2 = leaf;
+        self.nodes[sibling].parent = new_parent;
+        self.nodes[leaf].parent = new_parent;
+
+        if old_parent == NULL_NODE {
+            self.root = new_parent;
+        } else if self.nodes[old_parent].child1 == sibling {
+            self.nodes[old_parent].child1 = new_parent;
+        } else {
+            self.nodes[old_parent].child2 = new_parent;
+        }
+
+        // Walk back up the tree, fixing heights and AABBs
+        self.refit_ancestors(self.nodes[leaf].parent);
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if leaf == self.root {
+            self.root = NULL_NODE;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent;
+        let grandparent = self.nodes[parent].parent;
+        let sibling = if self.nodes[parent].child1 == leaf {
+            self.nodes[parent].child2
+        } else {
+            self.nodes[parent].child1
+        };
+
+        // Replace the parent with the sibling
+        if grandparent == NULL_NODE {
+            self.root = sibling;
+            self.nodes[sibling].parent = NULL_NODE;
+            self.free_node(parent);
+        } else {
+            if self.nodes[grandparent].child1 == parent {
+                self.nodes[grandparent].child1 = sibling;
+            } else {
+                self.nodes[grandparent].child2 = sibling;
+            }
+            self.nodes[sibling].parent = grandparent;
+            self.free_node(parent);
+
+            self.refit_ancestors(grandparent);
+        }
+
+        self.nodes[leaf].parent = NULL_NODE;
+    }
+
+    /// Balances and refits the given node and all of its ancestors.
+    fn refit_ancestors(&mut self, mut index: usize) {
+        while index != NULL_NODE {
+            index = self.balance(index);
+
+            let (child1, child2) = (self.nodes[index].child1, self.nodes[index].child2);
+            self.nodes[index].height = 1 + self.nodes[child1].height.max(self.nodes[child2].height);
+            self.nodes[index].aabb = self.nodes[child1].aabb.merged(&self.nodes[child2].aabb);
+
+            index = self.nodes[index].parent;
+        }
+    }
+
+    /// Returns the cost of descending into the given child when inserting a leaf with the given AABB.
+    fn descend_cost(&self, child: usize, leaf_aabb: &Aabb) -> Scalar {
+        let node = &self.nodes[child];
+        let merged_area = surface_area(&leaf_aabb.merged(&node.aabb));
+        if node.is_leaf() {
+            merged_area
+        } else {
+            merged_area - surface_area(&node.aabb)
+        }
+    }
+
+    /// Performs a left or right tree rotation if the subtree at `a` is imbalanced.
+    /// Returns the index of the new root of the subtree.
+    fn balance(&mut self, a: usize) -> usize {
+        if self.nodes[a].is_leaf() || self.nodes[a].height < 2 {
+            return a;
+        }
+
+        let b = self.nodes[a].child1;
+        let c = self.nodes[a].child2;
+        let balance = self.nodes[c].height - self.nodes[b].height;
+
+        if balance > 1 {
+            // Rotate C up
+            self.rotate_up(a, c, b, false);
+            c
+        } else if balance < -1 {
+            // Rotate B up
+            self.rotate_up(a, b, c, true);
+            b
+        } else {
+            a
+        }
+    }
+
+    /// Rotates `up` above its parent `a`. `other` is the other child of `a`.
+    ///
+    /// If `up_is_child1` is `true`, `up` is the first child of `a`, otherwise it is the second child.
+    fn rotate_up(&mut self, a: usize, up: usize, other: usize, up_is_child1: bool) {
+        let f = self.nodes[up].child1;
+        let g = self.nodes[up].child2;
+
+        // Swap A and the rotated node
+        self.nodes[up].child1 = a;
+        self.nodes[up].parent = self.nodes[a].parent;
+        self.nodes[a].parent = up;
+
+        // A's old parent should point to the rotated node
+        let up_parent = self.nodes[up].parent;
+        if up_parent == NULL_NODE {
+            self.root = up;
+        } else if self.nodes[up_parent].child1 == a {
+            self.nodes[up_parent].child1 = up;
+        } else {
+            self.nodes[up_parent].child2 = up;
+        }
+
+        // Keep the taller grandchild below the rotated node and give the shorter one to A
+        let (kept, moved) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+
+        self.nodes[up].child2 = kept;
+        if up_is_child1 {
+            self.nodes[a].child1 = moved;
+        } else {
+            self.nodes[a].child2 = moved;
+        }
+        self.nodes[moved].parent = a;
+
+        self.nodes[a].aabb = self.nodes[other].aabb.merged(&self.nodes[moved].aabb);
+        self.nodes[up].aabb = self.nodes[a].aabb.merged(&self.nodes[kept].aabb);
+        self.nodes[a].height = 1 + self.nodes[other].height.max(self.nodes[moved].height);
+        self.nodes[up].height = 1 + self.nodes[a].height.max(self.nodes[kept].height);
+    }
+}
+
+/// Returns the perimeter of an AABB in 2D or its surface area in 3D.
+fn surface_area(aabb: &Aabb) -> Scalar {
+    let extents = aabb.extents();
+    #[cfg(feature = "2d")]
+    {
+        2.0 * (extents.x + extents.y)
+    }
+    #[cfg(feature = "3d")]
+    {
+        2.0 * (extents.x * extents.y + extents.y * extents.z + extents.z * extents.x)
+    }
+}
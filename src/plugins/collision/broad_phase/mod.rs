@@ -0,0 +1,690 @@
+//! Collects pairs of potentially colliding entities into [`BroadCollisionPairs`] using
+//! [AABB](ColliderAabb) intersection checks.
+//!
+//! See [`BroadPhasePlugin`].
+
+mod dynamic_aabb_tree;
+
+use super::collision_filter::CustomCollisionFilter;
+use crate::prelude::*;
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        system::{ReadOnlySystemParam, StaticSystemParam, SystemParam, SystemParamItem},
+    },
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use dynamic_aabb_tree::DynamicAabbTree;
+use parry::bounding_volume::{Aabb, BoundingVolume};
+
+/// Collects pairs of potentially colliding entities into [`BroadCollisionPairs`] using
+/// [AABB](ColliderAabb) intersection checks. This speeds up narrow phase collision detection,
+/// as the number of precise collision checks required is greatly reduced.
+///
+/// Pairs are only collected for colliders whose [`CollisionLayers`] interact, unless a shared
+/// [`CollisionGroup`] overrides the layers. If both colliders have a [`CollisionLayerName`] that exists
+/// in the [`CollisionMatrix`], the matrix is used instead of their [`CollisionLayers`].
+///
+/// The algorithm used for finding the pairs can be configured with the [`BroadPhaseAlgorithm`] resource.
+/// By default, the [sweep and prune](https://en.wikipedia.org/wiki/Sweep_and_prune) algorithm is used.
+/// Only the data structures of the selected algorithm are kept up to date.
+///
+/// The broad phase systems run in [`PhysicsStepSet::BroadPhase`].
+pub struct BroadPhasePlugin;
+
+impl Plugin for BroadPhasePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AabbIntervals>()
+            .init_resource::<AabbTree>()
+            .init_resource::<BroadPhaseAlgorithm>()
+            .register_type::<BroadPhaseAlgorithm>();
+
+        let physics_schedule = app
+            .get_schedule_mut(PhysicsSchedule)
+            .expect("add PhysicsSchedule first");
+
+        physics_schedule.add_systems(
+            (
+                update_aabb,
+                (update_aabb_intervals, add_new_aabb_intervals)
+                    .chain()
+                    .run_if(resource_equals(BroadPhaseAlgorithm::SweepAndPrune)),
+                update_aabb_tree.run_if(resource_equals(BroadPhaseAlgorithm::DynamicAabbTree)),
+                collect_collision_pairs::<()>
+                    .run_if(not(resource_exists::<CustomCollisionFilter>())),
+            )
+                .chain()
+                .in_set(PhysicsStepSet::BroadPhase),
+        );
+    }
+}
+
+/// A resource for selecting the algorithm used by the [broad phase](BroadPhasePlugin)
+/// to collect pairs of potentially colliding entities into [`BroadCollisionPairs`].
+///
+/// Both algorithms produce the same pairs, but they have different performance characteristics.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+///         .insert_resource(BroadPhaseAlgorithm::DynamicAabbTree)
+///         .run();
+/// }
+/// ```
+#[derive(Reflect, Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Resource)]
+pub enum BroadPhaseAlgorithm {
+    /// Sorts the [AABBs](ColliderAabb) along the x-axis and sweeps over them to find overlapping pairs.
+    ///
+    /// Sweep and prune is very fast when the colliders are spread out along the x-axis,
+    /// but it degrades when many colliders overlap on the x-axis, for example in large, flat levels.
+    #[default]
+    SweepAndPrune,
+    /// Maintains a dynamic bounding volume hierarchy of enlarged [AABBs](ColliderAabb) and queries it
+    /// for the colliders that have moved.
+    ///
+    /// Colliders are only reinserted into the tree when they move outside of their enlarged AABBs,
+    /// and the performance doesn't depend on how the colliders are distributed in the world.
+    ///
+    /// The enlarged AABBs of the tree are also used to update the `Qbvh` of the [`SpatialQueryPipeline`]
+    /// incrementally, so that it doesn't have to be rebuilt every physics frame.
+    DynamicAabbTree,
+}
+
+/// A list of entity pairs for potential collisions collected during the broad phase.
+#[derive(Reflect, Resource, Default, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Resource)]
+pub struct BroadCollisionPairs(pub Vec<(Entity, Entity)>);
+
+type AABBChanged = Or<(
+    Changed<Position>,
+    Changed<Rotation>,
+    Changed<LinearVelocity>,
+    Changed<AngularVelocity>,
+    Changed<Collider>,
+)>;
+
+/// Updates the Axis-Aligned Bounding Boxes of all colliders. A safety margin will be added to account for sudden accelerations.
+#[allow(clippy::type_complexity)]
+fn update_aabb(
+    mut colliders: Query<
+        (
+            &Collider,
+            &mut ColliderAabb,
+            &Position,
+            &Rotation,
+            Option<&ColliderParent>,
+            Option<&LinearVelocity>,
+            Option<&AngularVelocity>,
+        ),
+        AABBChanged,
+    >,
+    parent_velocity: Query<
+        (&Position, Option<&LinearVelocity>, Option<&AngularVelocity>),
+        With<Children>,
+    >,
+    dt: Res<Time>,
+    narrow_phase_config: Option<Res<NarrowPhaseConfig>>,
+) {
+    // Safety margin multiplier bigger than DELTA_TIME to account for sudden accelerations
+    let safety_margin_factor = 2.0 * dt.delta_seconds_adjusted();
+
+    for (collider, mut aabb, pos, rot, collider_parent, lin_vel, ang_vel) in &mut colliders {
+        let (lin_vel, ang_vel) = if let (Some(lin_vel), Some(ang_vel)) = (lin_vel, ang_vel) {
+            (*lin_vel, *ang_vel)
+        } else if let Some(Ok((parent_pos, Some(lin_vel), Some(ang_vel)))) =
+            collider_parent.map(|p| parent_velocity.get(p.get()))
+        {
+            // If the rigid body is rotating, off-center colliders will orbit around it,
+            // which affects their linear velocities. We need to compute the linear velocity
+            // at the offset position.
+            // TODO: This assumes that the colliders would continue moving in the same direction,
+            //       but because they are orbiting, the direction will change. We should take
+            //       into account the uniform circular motion.
+            let offset = pos.0 - parent_pos.0;
+            #[cfg(feature = "2d")]
+            let vel_at_offset =
+                lin_vel.0 + Vector::new(-ang_vel.0 * offset.y, ang_vel.0 * offset.x) * 1.0;
+            #[cfg(feature = "3d")]
+            let vel_at_offset = lin_vel.0 + ang_vel.cross(offset);
+            (LinearVelocity(vel_at_offset), *ang_vel)
+        } else {
+            (LinearVelocity::ZERO, AngularVelocity::ZERO)
+        };
+
+        // Compute current isometry and predicted isometry for next feame
+        let start_iso = utils::make_isometry(*pos, *rot);
+        let end_iso = {
+            #[cfg(feature = "2d")]
+            {
+                utils::make_isometry(
+                    pos.0 + lin_vel.0 * safety_margin_factor,
+                    *rot + Rotation::from_radians(safety_margin_factor * ang_vel.0),
+                )
+            }
+            #[cfg(feature = "3d")]
+            {
+                let q = Quaternion::from_vec4(ang_vel.0.extend(0.0)) * rot.0;
+                let (x, y, z, w) = (
+                    rot.x + safety_margin_factor * 0.5 * q.x,
+                    rot.y + safety_margin_factor * 0.5 * q.y,
+                    rot.z + safety_margin_factor * 0.5 * q.z,
+                    rot.w + safety_margin_factor * 0.5 * q.w,
+                );
+                utils::make_isometry(
+                    pos.0 + lin_vel.0 * safety_margin_factor,
+                    Quaternion::from_xyzw(x, y, z, w).normalize(),
+                )
+            }
+        };
+
+        // Compute swept AABB, the space that the body would occupy if it was integrated for one frame
+        aabb.0 = collider
+            .shape_scaled()
+            .compute_swept_aabb(&start_iso, &end_iso);
+
+        // Add narrow phase prediction distance to AABBs to avoid missed collisions
+        let prediction_distance = if let Some(ref config) = narrow_phase_config {
+            config.prediction_distance
+        } else {
+            #[cfg(feature = "2d")]
+            {
+                1.0
+            }
+            #[cfg(feature = "3d")]
+            {
+                0.005
+            }
+        };
+        aabb.maxs.x += prediction_distance;
+        aabb.mins.x -= prediction_distance;
+        aabb.maxs.y += prediction_distance;
+        aabb.mins.y -= prediction_distance;
+        #[cfg(feature = "3d")]
+        {
+            aabb.maxs.z += prediction_distance;
+            aabb.mins.z -= prediction_distance;
+        }
+    }
+}
+
+/// Tracks whether a collider has moved since the broad phase last updated it.
+///
+/// The position and rotation are compared by value instead of using change detection, so that
+/// restoring the broad phase from a [`PhysicsSnapshot`] also restores which colliders have moved.
+/// Colliders of dynamic bodies that are awake are always treated as active.
+#[derive(Clone, Copy, Debug)]
+struct ColliderActivity {
+    position: Position,
+    rotation: Rotation,
+    /// True if the collider hasn't moved.
+    is_inactive: bool,
+}
+
+impl ColliderActivity {
+    fn new(position: Position, rotation: Rotation, is_inactive: bool) -> Self {
+        Self {
+            position,
+            rotation,
+            is_inactive,
+        }
+    }
+
+    /// Stores the new position and rotation and marks the collider as inactive if they are unchanged
+    /// and its rigid body isn't an awake dynamic body.
+    fn update(&mut self, position: Position, rotation: Rotation, is_awake_dynamic: bool) {
+        self.is_inactive =
+            !is_awake_dynamic && self.position == position && self.rotation == rotation;
+        self.position = position;
+        self.rotation = rotation;
+    }
+}
+
+/// Returns true if the rigid body of the collider is dynamic and not sleeping.
+fn is_awake_dynamic(bodies: &Query<(&RigidBody, Has<Sleeping>)>, parent: &ColliderParent) -> bool {
+    bodies
+        .get(parent.get())
+        .is_ok_and(|(rb, is_sleeping)| rb.is_dynamic() && !is_sleeping)
+}
+
+/// The layers of a collider that determine which other colliders it interacts with.
+#[derive(Clone, Copy, Debug, Default)]
+struct ColliderLayers {
+    layers: CollisionLayers,
+    /// The index of the collider's [`CollisionLayerName`] in the [`CollisionMatrix`].
+    matrix_layer: Option<usize>,
+}
+
+impl ColliderLayers {
+    fn new(layers: Option<&CollisionLayers>, matrix_layer: Option<&CollisionMatrixLayer>) -> Self {
+        Self {
+            layers: layers.copied().unwrap_or_default(),
+            matrix_layer: matrix_layer.and_then(CollisionMatrixLayer::index),
+        }
+    }
+
+    /// Uses the [`CollisionMatrix`] if both colliders are on a layer of the matrix,
+    /// and the [`CollisionLayers`] otherwise.
+    fn interacts_with(&self, other: &Self, matrix: Option<&CollisionMatrix>) -> bool {
+        match (self.matrix_layer, other.matrix_layer, matrix) {
+            (Some(index1), Some(index2), Some(matrix)) => matrix.interacts_by_index(index1, index2),
+            _ => self.layers.interacts_with(other.layers),
+        }
+    }
+}
+
+/// Entities with [`ColliderAabb`]s sorted along an axis by their extents.
+#[derive(Resource, Clone, Debug, Default)]
+pub(crate) struct AabbIntervals(
+    Vec<(
+        Entity,
+        ColliderParent,
+        ColliderAabb,
+        ColliderLayers,
+        CollisionGroup,
+        ColliderActivity,
+    )>,
+);
+
+impl MapEntities for AabbIntervals {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        for interval in self.0.iter_mut() {
+            interval.0 = entity_mapper.get_or_reserve(interval.0);
+        }
+    }
+}
+
+/// Updates [`AabbIntervals`] to keep them in sync with the [`ColliderAabb`]s.
+#[allow(clippy::type_complexity)]
+fn update_aabb_intervals(
+    aabbs: Query<(
+        &ColliderAabb,
+        &ColliderParent,
+        Option<&CollisionLayers>,
+        Option<&CollisionMatrixLayer>,
+        Option<&CollisionGroup>,
+        &Position,
+        &Rotation,
+    )>,
+    bodies: Query<(&RigidBody, Has<Sleeping>)>,
+    mut intervals: ResMut<AabbIntervals>,
+) {
+    intervals.0.retain_mut(
+        |(collider_entity, collider_parent, aabb, layers, group, activity)| {
+            if let Ok((
+                new_aabb,
+                new_parent,
+                new_layers,
+                matrix_layer,
+                new_group,
+                position,
+                rotation,
+            )) = aabbs.get(*collider_entity)
+            {
+                *aabb = *new_aabb;
+                *collider_parent = *new_parent;
+                *layers = ColliderLayers::new(new_layers, matrix_layer);
+                *group = new_group.copied().unwrap_or_default();
+                activity.update(
+                    *position,
+                    *rotation,
+                    is_awake_dynamic(&bodies, collider_parent),
+                );
+                true
+            } else {
+                false
+            }
+        },
+    );
+}
+
+/// Adds new [`ColliderAabb`]s to [`AabbIntervals`].
+#[allow(clippy::type_complexity)]
+fn add_new_aabb_intervals(
+    aabbs: Query<
+        (
+            Entity,
+            &ColliderParent,
+            &ColliderAabb,
+            Option<&RigidBody>,
+            Option<&CollisionLayers>,
+            Option<&CollisionMatrixLayer>,
+            Option<&CollisionGroup>,
+            &Position,
+            &Rotation,
+        ),
+        Added<ColliderAabb>,
+    >,
+    mut intervals: ResMut<AabbIntervals>,
+) {
+    let aabbs = aabbs.iter().map(
+        |(ent, parent, aabb, rb, layers, matrix_layer, group, position, rotation)| {
+            (
+                ent,
+                *parent,
+                *aabb,
+                ColliderLayers::new(layers, matrix_layer),
+                group.copied().unwrap_or_default(),
+                ColliderActivity::new(
+                    *position,
+                    *rotation,
+                    // Default to treating collider as immovable/static for filtering unnecessary collision checks
+                    rb.map_or(false, |rb| rb.is_static()),
+                ),
+            )
+        },
+    );
+    intervals.0.extend(aabbs);
+}
+
+/// The data stored for each leaf of the [`AabbTree`].
+#[derive(Clone, Debug)]
+struct AabbTreeProxy {
+    entity: Entity,
+    parent: ColliderParent,
+    /// The actual AABB of the collider. The AABB stored in the tree is enlarged.
+    aabb: ColliderAabb,
+    layers: ColliderLayers,
+    group: CollisionGroup,
+    activity: ColliderActivity,
+}
+
+/// A dynamic AABB tree of colliders used by [`BroadPhaseAlgorithm::DynamicAabbTree`].
+#[derive(Resource, Clone, Debug, Default)]
+pub(crate) struct AabbTree {
+    tree: DynamicAabbTree<AabbTreeProxy>,
+    proxies: HashMap<Entity, usize>,
+}
+
+impl AabbTree {
+    /// Returns the enlarged AABB stored in the tree for the collider of the given entity.
+    pub(crate) fn leaf_aabb(&self, entity: Entity) -> Option<Aabb> {
+        self.proxies
+            .get(&entity)
+            .map(|&proxy| *self.tree.aabb(proxy))
+    }
+}
+
+impl MapEntities for AabbTree {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        let proxies = std::mem::take(&mut self.proxies);
+        for (entity, proxy) in proxies {
+            let entity = entity_mapper.get_or_reserve(entity);
+            if let Some(data) = self.tree.get_mut(proxy) {
+                data.entity = entity;
+            }
+            self.proxies.insert(entity, proxy);
+        }
+    }
+}
+
+/// The fraction of its size that an AABB is enlarged by when it is inserted into the [`AabbTree`].
+/// The enlarged AABB allows colliders to move a bit without having to be reinserted.
+const AABB_TREE_MARGIN_FACTOR: Scalar = 0.1;
+
+/// Inserts, updates and removes colliders in the [`AabbTree`] to keep it in sync with the [`ColliderAabb`]s.
+#[allow(clippy::type_complexity)]
+fn update_aabb_tree(
+    aabbs: Query<(
+        Entity,
+        &ColliderAabb,
+        &ColliderParent,
+        Option<&CollisionLayers>,
+        Option<&CollisionMatrixLayer>,
+        Option<&CollisionGroup>,
+        Option<&RigidBody>,
+        &Position,
+        &Rotation,
+    )>,
+    bodies: Query<(&RigidBody, Has<Sleeping>)>,
+    mut aabb_tree: ResMut<AabbTree>,
+) {
+    let AabbTree { tree, proxies } = &mut *aabb_tree;
+
+    // Remove colliders that no longer exist
+    proxies.retain(|entity, proxy| {
+        let exists = aabbs.contains(*entity);
+        if !exists {
+            tree.remove(*proxy);
+        }
+        exists
+    });
+
+    for (entity, aabb, parent, layers, matrix_layer, group, rb, position, rotation) in &aabbs {
+        let layers = ColliderLayers::new(layers, matrix_layer);
+        let group = group.copied().unwrap_or_default();
+
+        if let Some(&proxy) = proxies.get(&entity) {
+            if let Some(data) = tree.get_mut(proxy) {
+                data.parent = *parent;
+                data.aabb = *aabb;
+                data.layers = layers;
+                data.group = group;
+                data.activity
+                    .update(*position, *rotation, is_awake_dynamic(&bodies, parent));
+            }
+
+            // Reinsert the collider if it has moved outside of its enlarged AABB
+            if !tree.aabb(proxy).contains(&aabb.0) {
+                tree.update(proxy, enlarged_aabb(aabb));
+            }
+        } else {
+            let proxy = tree.insert(
+                enlarged_aabb(aabb),
+                AabbTreeProxy {
+                    entity,
+                    parent: *parent,
+                    aabb: *aabb,
+                    layers,
+                    group,
+                    activity: ColliderActivity::new(
+                        *position,
+                        *rotation,
+                        // Default to treating collider as immovable/static for filtering unnecessary collision checks
+                        rb.is_some_and(|rb| rb.is_static()),
+                    ),
+                },
+            );
+            proxies.insert(entity, proxy);
+        }
+    }
+}
+
+/// Read-only access to the colliders that the [broad phase](BroadPhasePlugin) treats as inactive.
+///
+/// The broad phase doesn't collect pairs where both colliders are inactive, so systems that keep state
+/// for pairs across frames can use this to tell skipped pairs apart from pairs that stopped intersecting.
+#[derive(SystemParam)]
+pub(crate) struct BroadPhaseInactivity<'w> {
+    algorithm: Res<'w, BroadPhaseAlgorithm>,
+    intervals: Res<'w, AabbIntervals>,
+    aabb_tree: Res<'w, AabbTree>,
+}
+
+impl BroadPhaseInactivity<'_> {
+    /// Returns the given entities that the broad phase treated as inactive in the current physics step.
+    pub(crate) fn inactive_entities(
+        &self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> HashSet<Entity> {
+        let mut entities: HashSet<Entity> = entities.into_iter().collect();
+        if entities.is_empty() {
+            return entities;
+        }
+
+        match *self.algorithm {
+            BroadPhaseAlgorithm::SweepAndPrune => {
+                let mut inactive = HashSet::default();
+                for (entity, .., activity) in self.intervals.0.iter() {
+                    if activity.is_inactive && entities.contains(entity) {
+                        inactive.insert(*entity);
+                    }
+                }
+                inactive
+            }
+            BroadPhaseAlgorithm::DynamicAabbTree => {
+                entities.retain(|entity| {
+                    self.aabb_tree
+                        .proxies
+                        .get(entity)
+                        .and_then(|proxy| self.aabb_tree.tree.get(*proxy))
+                        .is_some_and(|data| data.activity.is_inactive)
+                });
+                entities
+            }
+        }
+    }
+}
+
+/// Enlarges the given AABB by a margin proportional to its size.
+fn enlarged_aabb(aabb: &ColliderAabb) -> Aabb {
+    let margin = aabb.half_extents().max() * AABB_TREE_MARGIN_FACTOR;
+    aabb.loosened(margin.max(0.0))
+}
+
+/// Collects bodies that are potentially colliding using the [`BroadPhaseAlgorithm`].
+///
+/// Pairs rejected by the [`CollisionFilter`] `F` are not collected. The [`CollisionFilterPlugin`]
+/// replaces the default instance of this system, which uses a filter that accepts all pairs.
+pub(crate) fn collect_collision_pairs<F>(
+    filter: StaticSystemParam<F>,
+    intervals: ResMut<AabbIntervals>,
+    aabb_tree: Res<AabbTree>,
+    algorithm: Res<BroadPhaseAlgorithm>,
+    matrix: Option<Res<CollisionMatrix>>,
+    mut broad_collision_pairs: ResMut<BroadCollisionPairs>,
+) where
+    F: CollisionFilter + ReadOnlySystemParam + 'static,
+    for<'w, 's> SystemParamItem<'w, 's, F>: CollisionFilter,
+{
+    match *algorithm {
+        BroadPhaseAlgorithm::SweepAndPrune => sweep_and_prune(
+            intervals,
+            &*filter,
+            matrix.as_deref(),
+            &mut broad_collision_pairs.0,
+        ),
+        BroadPhaseAlgorithm::DynamicAabbTree => query_aabb_tree(
+            &aabb_tree,
+            &*filter,
+            matrix.as_deref(),
+            &mut broad_collision_pairs.0,
+        ),
+    }
+}
+
+/// Queries the [`AabbTree`] with the AABB of each collider that has moved and collects
+/// the entity pairs that have intersecting AABBs.
+fn query_aabb_tree(
+    aabb_tree: &AabbTree,
+    filter: &impl CollisionFilter,
+    matrix: Option<&CollisionMatrix>,
+    broad_collision_pairs: &mut Vec<(Entity, Entity)>,
+) {
+    // Clear broad phase collisions from previous iteration.
+    broad_collision_pairs.clear();
+
+    for (proxy1, data1) in aabb_tree.tree.leaves() {
+        // No collisions between bodies that haven't moved, so only moving colliders need to be queried
+        if data1.activity.is_inactive {
+            continue;
+        }
+
+        aabb_tree.tree.query(&data1.aabb, |proxy2, data2| {
+            // Pairs of moving colliders are only collected once
+            if proxy1 == proxy2 || (!data2.activity.is_inactive && proxy2 < proxy1) {
+                return;
+            }
+
+            // No collisions between colliders with incompatible layers or groups or colliders with the same parent
+            let interacts = data1
+                .group
+                .test(data2.group)
+                .unwrap_or_else(|| data1.layers.interacts_with(&data2.layers, matrix));
+            if !interacts || data1.parent == data2.parent {
+                return;
+            }
+
+            // The tree stores enlarged AABBs, so check the actual AABBs
+            if data1.aabb.intersects(&data2.aabb) && filter.filter_pair(data1.entity, data2.entity)
+            {
+                broad_collision_pairs.push((data1.entity, data2.entity));
+            }
+        });
+    }
+}
+
+/// Sorts the entities by their minimum extents along an axis and collects the entity pairs that have intersecting AABBs.
+///
+/// Sweep and prune exploits temporal coherence, as bodies are unlikely to move significantly between two simulation steps. Insertion sort is used, as it is good at sorting nearly sorted lists efficiently.
+fn sweep_and_prune(
+    mut intervals: ResMut<AabbIntervals>,
+    filter: &impl CollisionFilter,
+    matrix: Option<&CollisionMatrix>,
+    broad_collision_pairs: &mut Vec<(Entity, Entity)>,
+) {
+    // Sort bodies along the x-axis using insertion sort, a sorting algorithm great for sorting nearly sorted lists.
+    insertion_sort(&mut intervals.0, |a, b| a.2.mins.x > b.2.mins.x);
+
+    // Clear broad phase collisions from previous iteration.
+    broad_collision_pairs.clear();
+
+    // Find potential collisions by checking for AABB intersections along all axes.
+    for (i, (ent1, parent1, aabb1, layers1, group1, activity1)) in intervals.0.iter().enumerate() {
+        for (ent2, parent2, aabb2, layers2, group2, activity2) in intervals.0.iter().skip(i + 1) {
+            // x doesn't intersect; check this first so we can discard as soon as possible
+            if aabb2.mins.x > aabb1.maxs.x {
+                break;
+            }
+
+            // No collisions between bodies that haven't moved, colliders with incompatible layers or groups
+            // or colliders with the same parent
+            if (activity1.is_inactive && activity2.is_inactive)
+                || !group1
+                    .test(*group2)
+                    .unwrap_or_else(|| layers1.interacts_with(layers2, matrix))
+                || parent1 == parent2
+            {
+                continue;
+            }
+
+            // y doesn't intersect
+            if aabb1.mins.y > aabb2.maxs.y || aabb1.maxs.y < aabb2.mins.y {
+                continue;
+            }
+
+            #[cfg(feature = "3d")]
+            // z doesn't intersect
+            if aabb1.mins.z > aabb2.maxs.z || aabb1.maxs.z < aabb2.mins.z {
+                continue;
+            }
+
+            if filter.filter_pair(*ent1, *ent2) {
+                broad_collision_pairs.push((*ent1, *ent2));
+            }
+        }
+    }
+}
+
+/// Sorts a list iteratively using comparisons. In an ascending sort order, when a smaller value is encountered, it is moved lower in the list until it is larger than the item before it.
+///
+/// This is relatively slow for large lists, but very efficient in cases where the list is already mostly sorted.
+fn insertion_sort<T>(items: &mut Vec<T>, comparison: fn(&T, &T) -> bool) {
+    for i in 1..items.len() {
+        let mut j = i;
+        while j > 0 && comparison(&items[j - 1], &items[j]) {
+            items.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
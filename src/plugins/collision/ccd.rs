@@ -0,0 +1,388 @@
+//! Prevents fast moving bodies from tunneling through other colliders using
+//! *Continuous Collision Detection* (CCD).
+//!
+//! See [`CcdPlugin`] and [`SweptCcd`].
+
+use crate::prelude::*;
+use bevy::{ecs::query::Has, prelude::*, utils::HashMap};
+
+/// Prevents fast moving bodies with the [`SweptCcd`] component from tunneling through other colliders.
+///
+/// By default, collision detection is *discrete*: contacts are only computed at the positions
+/// bodies have after each substep. If a body moves far enough during a single step, it can pass
+/// straight through thin geometry without any contacts being detected. This is known as *tunneling*.
+///
+/// This plugin handles tunneling for bodies that opt in to *swept CCD* by adding the [`SweptCcd`] component.
+/// After the substepping loop, the motion of each such body during the step is swept against the colliders
+/// it could have hit according to the [broad phase](BroadPhasePlugin) using [`contact_query::time_of_impact_with_dispatcher`].
+/// If the body hits a collider that it was not already touching at the start of the step,
+/// it is moved back to the time of impact and its velocity towards the hit surface is removed,
+/// taking [restitution](Restitution) into account.
+///
+/// The CCD systems run in [`PhysicsStepSet::SweptCcd`].
+pub struct CcdPlugin;
+
+impl Plugin for CcdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CcdStartPositions>()
+            .init_resource::<PhysicsQueryDispatcher>()
+            .register_type::<SweptCcd>();
+
+        let physics_schedule = app
+            .get_schedule_mut(PhysicsSchedule)
+            .expect("add PhysicsSchedule first");
+
+        physics_schedule
+            .add_systems(
+                store_ccd_start_positions
+                    .after(PhysicsStepSet::BroadPhase)
+                    .before(PhysicsStepSet::Substeps),
+            )
+            .add_systems(solve_swept_ccd.in_set(PhysicsStepSet::SweptCcd));
+    }
+}
+
+/// Enables swept *Continuous Collision Detection* (CCD) for a [dynamic](RigidBody::Dynamic) rigid body.
+///
+/// Swept CCD prevents fast moving bodies like bullets from passing through thin geometry
+/// by sweeping their motion during each physics step against nearby colliders and clamping the motion
+/// to the time of impact. See [`CcdPlugin`] for more information.
+///
+/// Only the linear motion of the body is swept. Rotation is assumed to stay constant during the sweep.
+///
+/// CCD has a performance cost, so it should only be enabled for bodies that actually need it.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(mut commands: Commands) {
+///     // Spawn a fast moving bullet with swept CCD enabled
+///     commands.spawn((
+///         RigidBody::Dynamic,
+///         Collider::ball(0.05),
+#[cfg_attr(feature = "2d", doc = "        LinearVelocity(Vec2::X * 500.0),")]
+#[cfg_attr(feature = "3d", doc = "        LinearVelocity(Vec3::X * 500.0),")]
+///         // Only sweep against static and kinematic bodies
+///         SweptCcd::default().include_dynamic(false),
+///     ));
+/// }
+/// ```
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct SweptCcd {
+    /// If `true`, the body's motion is also swept against other dynamic bodies.
+    /// Otherwise, only static and kinematic bodies and colliders without a rigid body are considered.
+    ///
+    /// The default is `true`.
+    pub include_dynamic: bool,
+    /// The minimum linear speed required for swept CCD to be performed.
+    /// Bodies moving slower than this are handled by discrete collision detection only.
+    ///
+    /// The default is `0.0`.
+    pub linear_threshold: Scalar,
+}
+
+impl Default for SweptCcd {
+    fn default() -> Self {
+        Self {
+            include_dynamic: true,
+            linear_threshold: 0.0,
+        }
+    }
+}
+
+impl SweptCcd {
+    /// Sets whether the body's motion should also be swept against other dynamic bodies.
+    pub fn include_dynamic(mut self, should_include: bool) -> Self {
+        self.include_dynamic = should_include;
+        self
+    }
+
+    /// Sets the minimum linear speed required for swept CCD to be performed.
+    pub fn with_linear_threshold(mut self, threshold: Scalar) -> Self {
+        self.linear_threshold = threshold;
+        self
+    }
+}
+
+/// The positions of awake dynamic bodies with [`SweptCcd`] at the start of the current physics step.
+#[derive(Resource, Default)]
+struct CcdStartPositions(HashMap<Entity, Vector>);
+
+/// Stores the positions of bodies with [`SweptCcd`] before the substepping loop
+/// so that their motion during the step can be swept afterwards.
+#[allow(clippy::type_complexity)]
+fn store_ccd_start_positions(
+    bodies: Query<(Entity, &RigidBody, &Position), (With<SweptCcd>, Without<Sleeping>)>,
+    mut start_positions: ResMut<CcdStartPositions>,
+) {
+    start_positions.0.clear();
+
+    for (entity, rb, position) in &bodies {
+        if rb.is_dynamic() {
+            start_positions.0.insert(entity, position.0);
+        }
+    }
+}
+
+/// The earliest hit found for a body during a sweep.
+#[derive(Clone, Copy, Debug)]
+struct CcdHit {
+    /// The time of impact in seconds from the start of the step.
+    time_of_impact: Scalar,
+    /// The world-space hit normal pointing from the body towards the surface that was hit.
+    normal: Vector,
+    /// The linear velocity of the body that was hit.
+    other_velocity: Vector,
+    /// The combined restitution of the two colliders.
+    restitution: Scalar,
+}
+
+// Colliders without a rigid body also have a `Position` and `Rotation`,
+// so these are queried for all entities to avoid conflicting queries.
+type CcdBodyComponents = (
+    Option<&'static RigidBody>,
+    &'static mut Position,
+    &'static Rotation,
+    Option<&'static mut LinearVelocity>,
+    Option<&'static SweptCcd>,
+    Has<Sleeping>,
+);
+
+type CcdBodyItem<'a> = (
+    Option<&'a RigidBody>,
+    &'a Position,
+    &'a Rotation,
+    Option<&'a LinearVelocity>,
+    Option<&'a SweptCcd>,
+    bool,
+);
+
+type CcdColliderComponents = (
+    &'static Collider,
+    Option<&'static ColliderParent>,
+    Option<&'static ColliderTransform>,
+    Option<&'static Restitution>,
+);
+
+/// Sweeps the motion of bodies with [`SweptCcd`] against the colliders in [`BroadCollisionPairs`]
+/// and clamps their motion to the earliest time of impact.
+fn solve_swept_ccd(
+    mut bodies: Query<CcdBodyComponents>,
+    colliders: Query<CcdColliderComponents>,
+    broad_collision_pairs: Res<BroadCollisionPairs>,
+    start_positions: Res<CcdStartPositions>,
+    dispatcher: Res<PhysicsQueryDispatcher>,
+    time: Res<Time<Physics>>,
+) {
+    // The generic `Time` is reset to `Time<Virtual>` after the substepping loop,
+    // so the physics timestep is read directly from `Time<Physics>`.
+    let delta_secs = time.delta_seconds_f64().adjust_precision();
+
+    if delta_secs == 0.0 || start_positions.0.is_empty() {
+        return;
+    }
+
+    let mut hits: HashMap<Entity, CcdHit> = HashMap::default();
+
+    for (collider_entity1, collider_entity2) in broad_collision_pairs.0.iter() {
+        let Ok([collider1, collider2]) = colliders.get_many([*collider_entity1, *collider_entity2])
+        else {
+            continue;
+        };
+
+        let body_entity1 = collider1.1.map_or(*collider_entity1, |p| p.get());
+        let body_entity2 = collider2.1.map_or(*collider_entity2, |p| p.get());
+
+        if body_entity1 == body_entity2 {
+            continue;
+        }
+
+        let start_position1 = start_positions.0.get(&body_entity1);
+        let start_position2 = start_positions.0.get(&body_entity2);
+
+        if start_position1.is_none() && start_position2.is_none() {
+            continue;
+        }
+
+        let body1 = bodies.get(body_entity1).ok();
+        let body2 = bodies.get(body_entity2).ok();
+
+        let Some((position1, rotation1, velocity1)) = collider_motion(
+            bodies.get(*collider_entity1).ok(),
+            body1,
+            start_position1,
+            collider1.2,
+            delta_secs,
+        ) else {
+            continue;
+        };
+        let Some((position2, rotation2, velocity2)) = collider_motion(
+            bodies.get(*collider_entity2).ok(),
+            body2,
+            start_position2,
+            collider2.2,
+            delta_secs,
+        ) else {
+            continue;
+        };
+
+        let uses_ccd1 = start_position1.is_some() && uses_ccd(body1, body2, velocity1);
+        let uses_ccd2 = start_position2.is_some() && uses_ccd(body2, body1, velocity2);
+
+        if !uses_ccd1 && !uses_ccd2 {
+            continue;
+        }
+
+        // Sweep from the positions at the start of the step to the current positions
+        let Ok(Some(toi)) = contact_query::time_of_impact_with_dispatcher(
+            &**dispatcher,
+            collider1.0,
+            position1 - velocity1 * delta_secs,
+            rotation1,
+            velocity1,
+            collider2.0,
+            position2 - velocity2 * delta_secs,
+            rotation2,
+            velocity2,
+            delta_secs,
+        ) else {
+            continue;
+        };
+
+        // Bodies that were already touching at the start of the step are handled by the discrete solver.
+        // This also skips failed sweeps.
+        if !matches!(
+            toi.status,
+            contact_query::TimeOfImpactStatus::Converged
+                | contact_query::TimeOfImpactStatus::OutOfIterations
+        ) {
+            continue;
+        }
+
+        let restitution = collider1
+            .3
+            .copied()
+            .unwrap_or_default()
+            .combine(collider2.3.copied().unwrap_or_default())
+            .coefficient;
+
+        if uses_ccd1 {
+            insert_earliest_hit(
+                &mut hits,
+                body_entity1,
+                CcdHit {
+                    time_of_impact: toi.time_of_impact,
+                    normal: rotation1.rotate(toi.normal1),
+                    other_velocity: velocity2,
+                    restitution,
+                },
+            );
+        }
+        if uses_ccd2 {
+            insert_earliest_hit(
+                &mut hits,
+                body_entity2,
+                CcdHit {
+                    time_of_impact: toi.time_of_impact,
+                    normal: rotation2.rotate(toi.normal2),
+                    other_velocity: velocity1,
+                    restitution,
+                },
+            );
+        }
+    }
+
+    for (entity, hit) in hits {
+        let Ok((_, mut position, _, Some(mut lin_vel), _, _)) = bodies.get_mut(entity) else {
+            continue;
+        };
+        let Some(start_position) = start_positions.0.get(&entity) else {
+            continue;
+        };
+
+        // Move the body back to where it was at the time of impact
+        position.0 =
+            *start_position + (position.0 - *start_position) * hit.time_of_impact / delta_secs;
+
+        // Remove the velocity towards the surface that was hit, applying restitution
+        let normal_speed = (lin_vel.0 - hit.other_velocity).dot(hit.normal);
+        if normal_speed > 0.0 {
+            lin_vel.0 -= hit.normal * normal_speed * (1.0 + hit.restitution);
+        }
+    }
+}
+
+/// Returns `true` if the body should be swept against the other body.
+fn uses_ccd(
+    body: Option<CcdBodyItem>,
+    other_body: Option<CcdBodyItem>,
+    sweep_velocity: Vector,
+) -> bool {
+    let Some((_, _, _, _, Some(ccd), _)) = body else {
+        return false;
+    };
+
+    if sweep_velocity == Vector::ZERO || sweep_velocity.length() < ccd.linear_threshold {
+        return false;
+    }
+
+    let other_is_dynamic = other_body.is_some_and(|(rb, ..)| rb.is_some_and(|rb| rb.is_dynamic()));
+
+    ccd.include_dynamic || !other_is_dynamic
+}
+
+/// Computes the current world-space position and rotation of a collider and the linear velocity
+/// that it moved with during the step.
+///
+/// For bodies with [`SweptCcd`], the velocity is computed from the actual displacement during the step,
+/// because the constraint solver may have changed both the position and velocity of the body.
+/// For other bodies, the current linear velocity is used.
+///
+/// The positions of child colliders are only updated at the start of each frame,
+/// so they are computed from the current position and rotation of the rigid body.
+fn collider_motion(
+    collider: Option<CcdBodyItem>,
+    body: Option<CcdBodyItem>,
+    start_position: Option<&Vector>,
+    collider_transform: Option<&ColliderTransform>,
+    delta_secs: Scalar,
+) -> Option<(Vector, Rotation, Vector)> {
+    let Some((Some(rb), body_pos, body_rot, lin_vel, _, _)) = body else {
+        // Colliders without a rigid body are treated as static
+        let (_, position, rotation, ..) = collider?;
+        return Some((position.0, *rotation, Vector::ZERO));
+    };
+
+    let transform = collider_transform.copied().unwrap_or_default();
+    let position = body_pos.0 + body_rot.rotate(transform.translation);
+    #[cfg(feature = "2d")]
+    let rotation = *body_rot + transform.rotation;
+    #[cfg(feature = "3d")]
+    let rotation = Rotation((body_rot.0 * transform.rotation.0).normalize());
+    let velocity = if rb.is_static() {
+        Vector::ZERO
+    } else if let Some(start_position) = start_position {
+        (body_pos.0 - *start_position) / delta_secs
+    } else {
+        lin_vel.map_or(Vector::ZERO, |lin_vel| lin_vel.0)
+    };
+
+    Some((position, rotation, velocity))
+}
+
+/// Stores the given hit for the body unless an earlier hit has already been found.
+fn insert_earliest_hit(hits: &mut HashMap<Entity, CcdHit>, entity: Entity, hit: CcdHit) {
+    hits.entry(entity)
+        .and_modify(|earliest| {
+            if hit.time_of_impact < earliest.time_of_impact {
+                *earliest = hit;
+            }
+        })
+        .or_insert(hit);
+}
@@ -0,0 +1,140 @@
+//! Filtering collision pairs with custom game logic before contacts are solved.
+//!
+//! See [`CollisionFilter`] and [`CollisionFilterPlugin`].
+
+use std::marker::PhantomData;
+
+use super::{broad_phase::collect_collision_pairs, narrow_phase::collect_collisions};
+use crate::prelude::*;
+use bevy::ecs::system::{ReadOnlySystemParam, SystemParam, SystemParamItem};
+
+/// A [`SystemParam`] that decides whether two colliders should interact using arbitrary ECS data.
+///
+/// The filter is added with a [`CollisionFilterPlugin`]:
+///
+/// - [`filter_pair`](Self::filter_pair) is called for each pair of colliders with intersecting
+///   [AABBs](ColliderAabb) while the [broad phase](BroadPhasePlugin) collects them into
+///   [`BroadCollisionPairs`], so rejected pairs never reach the [narrow phase](NarrowPhasePlugin).
+/// - [`filter_contacts`](Self::filter_contacts) is called for each pair of colliders in contact as soon as
+///   the narrow phase has computed their [`Contacts`] in each substep, before the contacts are solved.
+///
+/// Rejected pairs are not stored in [`Collisions`], so they don't send collision events, generate
+/// constraints or wake up sleeping bodies. Unlike the [`PostProcessCollisions`] schedule, the filter
+/// doesn't waste time computing contacts for pairs that are rejected by [`filter_pair`](Self::filter_pair).
+///
+/// The entities are the entities of the colliders, which can be different from the entities of
+/// the rigid bodies for child colliders. The order of the entities is arbitrary.
+///
+/// To combine several filters, add them as fields of one [`SystemParam`] and call them in its implementation.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::{ecs::system::SystemParam, prelude::*};
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// #[derive(Component)]
+/// struct Projectile {
+///     shooter: Entity,
+///     /// The time in seconds when the projectile was fired.
+///     fired_at: f32,
+/// }
+///
+/// #[derive(SystemParam)]
+/// struct ProjectileFilter<'w, 's> {
+///     projectiles: Query<'w, 's, &'static Projectile>,
+///     time: Res<'w, Time>,
+/// }
+///
+/// impl CollisionFilter for ProjectileFilter<'_, '_> {
+///     fn filter_pair(&self, entity1: Entity, entity2: Entity) -> bool {
+///         // Projectiles ignore their shooter for 0.2 seconds after being fired.
+///         let ignores = |projectile: Entity, other: Entity| {
+///             self.projectiles.get(projectile).is_ok_and(|projectile| {
+///                 projectile.shooter == other
+///                     && self.time.elapsed_seconds() - projectile.fired_at < 0.2
+///             })
+///         };
+///         !ignores(entity1, entity2) && !ignores(entity2, entity1)
+///     }
+/// }
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((
+///             DefaultPlugins,
+///             PhysicsPlugins::default(),
+///             CollisionFilterPlugin::<ProjectileFilter>::default(),
+///         ))
+///         .run();
+/// }
+/// ```
+pub trait CollisionFilter: SystemParam + Send + Sync {
+    /// Returns `false` if the colliders shouldn't interact. Called for each pair of colliders
+    /// collected by the [broad phase](BroadPhasePlugin).
+    fn filter_pair(&self, entity1: Entity, entity2: Entity) -> bool {
+        let _ = (entity1, entity2);
+        true
+    }
+
+    /// Returns `false` if the given [`Contacts`] should be discarded. Called for each pair of colliders
+    /// in contact after the [narrow phase](NarrowPhasePlugin) has computed the contacts in a substep.
+    fn filter_contacts(&self, contacts: &Contacts) -> bool {
+        let _ = contacts;
+        true
+    }
+}
+
+/// The default filter, which accepts all pairs and contacts.
+impl CollisionFilter for () {}
+
+/// A marker resource that disables the default collision pair and contact collection systems,
+/// which are replaced by the systems of a [`CollisionFilterPlugin`].
+#[derive(Resource)]
+pub(crate) struct CustomCollisionFilter;
+
+/// Rejects collision pairs using the [`CollisionFilter`] `F`.
+///
+/// The plugin replaces the systems that collect the pairs in the [broad phase](BroadPhasePlugin)
+/// and the [`Contacts`] in the [narrow phase](NarrowPhasePlugin) with versions that call the filter
+/// for each pair, so rejected pairs are never stored.
+///
+/// Only one collision filter can be added, but a filter can combine several others.
+pub struct CollisionFilterPlugin<F: CollisionFilter + 'static>(PhantomData<fn() -> F>);
+
+impl<F: CollisionFilter + 'static> Default for CollisionFilterPlugin<F> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F> Plugin for CollisionFilterPlugin<F>
+where
+    F: CollisionFilter + ReadOnlySystemParam + 'static,
+    for<'w, 's> SystemParamItem<'w, 's, F>: CollisionFilter,
+{
+    fn build(&self, app: &mut App) {
+        assert!(
+            !app.world.contains_resource::<CustomCollisionFilter>(),
+            "only one `CollisionFilterPlugin` can be added"
+        );
+        app.insert_resource(CustomCollisionFilter);
+
+        app.get_schedule_mut(PhysicsSchedule)
+            .expect("add PhysicsSchedule first")
+            .add_systems(
+                collect_collision_pairs::<F>
+                    .after(collect_collision_pairs::<()>)
+                    .in_set(PhysicsStepSet::BroadPhase),
+            );
+
+        app.get_schedule_mut(SubstepSchedule)
+            .expect("add SubstepSchedule first")
+            .add_systems(
+                collect_collisions::<F>
+                    .after(collect_collisions::<()>)
+                    .in_set(SubstepSet::NarrowPhase),
+            );
+    }
+}
@@ -0,0 +1,435 @@
+//! Named collision layers and the rules for which layers interact, configured in one place.
+//!
+//! See [`CollisionMatrixPlugin`] and [`CollisionMatrix`].
+
+#[cfg(feature = "collision-matrix-asset")]
+use crate::plugins::ron_asset::{RonAsset, RonAssetLoader};
+use crate::prelude::*;
+use bevy::{prelude::*, utils::intern::Interned};
+
+/// Manages the [`CollisionMatrix`] that the [broad phase](BroadPhasePlugin) and [spatial queries](crate::spatial_query)
+/// use for entities with a [`CollisionLayerName`], and keeps the [`CollisionMatrixLayer`] of the entities up to date.
+///
+/// With the `collision-matrix-asset` feature, the [`CollisionMatrix`] can also be loaded from
+/// a `.collmat.ron` file by inserting a `CollisionMatrixHandle` resource. The asset and its loader are only
+/// registered if the app has an `AssetServer`, so the `AssetPlugin` should be added before the physics plugins.
+///
+/// The systems run in [`PhysicsSet::Prepare`] before the colliders are initialized.
+pub struct CollisionMatrixPlugin {
+    schedule: Interned<dyn ScheduleLabel>,
+}
+
+impl CollisionMatrixPlugin {
+    /// Creates a [`CollisionMatrixPlugin`] with the schedule that is used for running the [`PhysicsSchedule`].
+    ///
+    /// The default schedule is `PostUpdate`.
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for CollisionMatrixPlugin {
+    fn default() -> Self {
+        Self::new(PostUpdate)
+    }
+}
+
+impl Plugin for CollisionMatrixPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollisionMatrix>()
+            .register_type::<CollisionMatrix>()
+            .register_type::<CollisionLayerName>()
+            .register_type::<CollisionMatrixLayer>();
+
+        #[cfg(feature = "collision-matrix-asset")]
+        register_collision_matrix_asset(app);
+
+        app.add_systems(
+            self.schedule,
+            (
+                #[cfg(feature = "collision-matrix-asset")]
+                update_collision_matrix_from_asset,
+                update_collision_matrix_layers,
+            )
+                .chain()
+                .in_set(PhysicsSet::Prepare)
+                .before(crate::plugins::prepare::PrepareSet::Init),
+        );
+    }
+
+    #[cfg(feature = "collision-matrix-asset")]
+    fn finish(&self, app: &mut App) {
+        // The asset server might have been added after the physics plugins.
+        register_collision_matrix_asset(app);
+    }
+}
+
+/// Initializes the [`CollisionMatrix`] asset and registers the [`CollisionMatrixLoader`]
+/// if an `AssetServer` exists and the asset hasn't been registered yet.
+#[cfg(feature = "collision-matrix-asset")]
+fn register_collision_matrix_asset(app: &mut App) {
+    if app.world.contains_resource::<AssetServer>()
+        && !app.world.contains_resource::<Assets<CollisionMatrix>>()
+    {
+        app.init_asset::<CollisionMatrix>()
+            .init_asset_loader::<CollisionMatrixLoader>();
+    }
+}
+
+/// The name of the layer in the [`CollisionMatrix`] that an entity belongs to.
+///
+/// When both colliders of a pair have a name that exists in the matrix, the [broad phase](BroadPhasePlugin)
+/// uses the matrix to decide whether they interact. Otherwise, their [`CollisionLayers`] are used.
+/// The [`CollisionLayers`] of the entity are never modified.
+///
+/// The index of the layer in the matrix is stored in the [`CollisionMatrixLayer`] component,
+/// which is added automatically.
+///
+/// See [`CollisionMatrix`] for an example.
+#[derive(Reflect, Clone, Component, Debug, Default, PartialEq, Eq, Deref, DerefMut)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct CollisionLayerName(pub String);
+
+impl CollisionLayerName {
+    /// Creates a new [`CollisionLayerName`] with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+/// The index of the [`CollisionLayerName`] of an entity in the [`CollisionMatrix`].
+///
+/// This is added automatically to entities with a [`CollisionLayerName`], and updated when the name
+/// or the matrix changes, so that the layer doesn't have to be looked up by name every frame.
+/// The index can be used for a [`SpatialQueryFilter`] with [`SpatialQueryFilter::with_matrix_layer`].
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct CollisionMatrixLayer(pub(crate) Option<usize>);
+
+impl CollisionMatrixLayer {
+    /// Returns the index of the layer in the [`CollisionMatrix`], or `None` if the layer doesn't exist in the matrix.
+    pub fn index(&self) -> Option<usize> {
+        self.0
+    }
+}
+
+/// A resource that names collision layers and declares which pairs of layers interact.
+///
+/// Instead of configuring the groups and masks of [`CollisionLayers`] for each entity, entities
+/// can name their layer with a [`CollisionLayerName`], and the rules for which layers interact live
+/// in the matrix. The [broad phase](BroadPhasePlugin) consults the matrix for pairs of named entities,
+/// and uses [`CollisionLayers`] for pairs where either entity has no name in the matrix.
+/// [Spatial queries](crate::spatial_query) consult the matrix for named colliders when the
+/// [`SpatialQueryFilter`] has a [matrix layer](SpatialQueryFilter::with_matrix_layer).
+///
+/// The matrix layers are separate from the [`PhysicsLayer`]s of [`CollisionLayers`], and the matrix
+/// can have at most [`CollisionMatrix::MAX_LAYERS`] layers.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(mut commands: Commands) {
+///     // Players collide with enemies and the ground, and enemies collide with the ground,
+///     // but players don't collide with other players.
+///     commands.insert_resource(
+///         CollisionMatrix::new()
+///             .with_interaction("Player", "Enemy")
+///             .with_interaction("Player", "Ground")
+///             .with_interaction("Enemy", "Ground"),
+///     );
+///
+///     commands.spawn((Collider::ball(0.5), CollisionLayerName::new("Player")));
+/// }
+/// ```
+///
+/// ## Loading from a file
+///
+/// With the `collision-matrix-asset` feature, the matrix can be loaded from a `.collmat.ron` file
+/// by inserting a `CollisionMatrixHandle` resource. The [`CollisionMatrix`] resource is kept in sync
+/// with the asset, so enabling Bevy's `file_watcher` feature also applies changes to the file while
+/// the app is running.
+///
+/// ```ron
+/// (
+///     layers: ["Player", "Enemy", "Ground"],
+///     interactions: [
+///         ("Player", "Enemy"),
+///         ("Player", "Ground"),
+///         ("Enemy", "Ground"),
+///     ],
+/// )
+/// ```
+#[derive(Reflect, Resource, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "collision-matrix-asset", derive(Asset))]
+#[cfg_attr(
+    any(feature = "serialize", feature = "collision-matrix-asset"),
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "CollisionMatrixDescriptor",
+        into = "CollisionMatrixDescriptor"
+    )
+)]
+#[reflect(Resource)]
+pub struct CollisionMatrix {
+    /// The names of the layers. The index of a layer is the index of its bit in the interactions.
+    layers: Vec<String>,
+    /// A bitmask of the layers that each layer interacts with.
+    interactions: Vec<u64>,
+}
+
+impl CollisionMatrix {
+    /// The maximum number of layers in a [`CollisionMatrix`].
+    pub const MAX_LAYERS: usize = u64::BITS as usize;
+
+    /// Creates a new [`CollisionMatrix`] with no layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer with the given name if it doesn't exist yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix already has [`CollisionMatrix::MAX_LAYERS`] layers.
+    pub fn with_layer(mut self, name: impl Into<String>) -> Self {
+        self.add_layer(name);
+        self
+    }
+
+    /// Makes the given layers interact with each other, adding the layers if they don't exist yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a layer has to be added when the matrix already has [`CollisionMatrix::MAX_LAYERS`] layers.
+    pub fn with_interaction(
+        mut self,
+        layer1: impl Into<String>,
+        layer2: impl Into<String>,
+    ) -> Self {
+        let index1 = self.add_layer(layer1);
+        let index2 = self.add_layer(layer2);
+        self.interactions[index1] |= 1 << index2;
+        self.interactions[index2] |= 1 << index1;
+        self
+    }
+
+    /// Adds a layer with the given name if it doesn't exist yet, and returns its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix already has [`CollisionMatrix::MAX_LAYERS`] layers.
+    pub fn add_layer(&mut self, name: impl Into<String>) -> usize {
+        let name = name.into();
+        if let Some(index) = self.layer_index(&name) {
+            return index;
+        }
+        assert!(
+            self.layers.len() < Self::MAX_LAYERS,
+            "a collision matrix can have at most {} layers",
+            Self::MAX_LAYERS
+        );
+        self.layers.push(name);
+        self.interactions.push(0);
+        self.layers.len() - 1
+    }
+
+    /// Sets whether the given layers interact with each other.
+    ///
+    /// Returns `false` if either of the layers doesn't exist.
+    pub fn set_interaction(&mut self, layer1: &str, layer2: &str, interacts: bool) -> bool {
+        let (Some(index1), Some(index2)) = (self.layer_index(layer1), self.layer_index(layer2))
+        else {
+            return false;
+        };
+        if interacts {
+            self.interactions[index1] |= 1 << index2;
+            self.interactions[index2] |= 1 << index1;
+        } else {
+            self.interactions[index1] &= !(1 << index2);
+            self.interactions[index2] &= !(1 << index1);
+        }
+        true
+    }
+
+    /// Returns an iterator over the names of the layers.
+    pub fn layers(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(String::as_str)
+    }
+
+    /// Returns the index of the layer with the given name.
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer == name)
+    }
+
+    /// Returns true if the given layers interact with each other.
+    pub fn interacts(&self, layer1: &str, layer2: &str) -> bool {
+        match (self.layer_index(layer1), self.layer_index(layer2)) {
+            (Some(index1), Some(index2)) => self.interacts_by_index(index1, index2),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the layers with the given [indices](CollisionMatrix::layer_index) interact with each other.
+    pub fn interacts_by_index(&self, index1: usize, index2: usize) -> bool {
+        index2 < Self::MAX_LAYERS
+            && self
+                .interactions
+                .get(index1)
+                .is_some_and(|interactions| interactions & (1 << index2) != 0)
+    }
+}
+
+/// The serialized representation of a [`CollisionMatrix`] that refers to the layers by name.
+#[cfg(any(feature = "serialize", feature = "collision-matrix-asset"))]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CollisionMatrixDescriptor {
+    #[serde(default)]
+    layers: Vec<String>,
+    #[serde(default)]
+    interactions: Vec<(String, String)>,
+}
+
+#[cfg(any(feature = "serialize", feature = "collision-matrix-asset"))]
+impl TryFrom<CollisionMatrixDescriptor> for CollisionMatrix {
+    type Error = String;
+
+    fn try_from(descriptor: CollisionMatrixDescriptor) -> Result<Self, Self::Error> {
+        let layer_count = descriptor
+            .layers
+            .iter()
+            .chain(descriptor.interactions.iter().flat_map(|(a, b)| [a, b]))
+            .collect::<bevy::utils::HashSet<_>>()
+            .len();
+        if layer_count > Self::MAX_LAYERS {
+            return Err(format!(
+                "a collision matrix can have at most {} layers, but {layer_count} were given",
+                Self::MAX_LAYERS
+            ));
+        }
+
+        let matrix = descriptor
+            .layers
+            .into_iter()
+            .fold(CollisionMatrix::new(), |matrix, layer| {
+                matrix.with_layer(layer)
+            });
+        Ok(descriptor
+            .interactions
+            .into_iter()
+            .fold(matrix, |matrix, (layer1, layer2)| {
+                matrix.with_interaction(layer1, layer2)
+            }))
+    }
+}
+
+#[cfg(any(feature = "serialize", feature = "collision-matrix-asset"))]
+impl From<CollisionMatrix> for CollisionMatrixDescriptor {
+    fn from(matrix: CollisionMatrix) -> Self {
+        let mut interactions = vec![];
+        for (index1, layer1) in matrix.layers.iter().enumerate() {
+            for (index2, layer2) in matrix.layers.iter().enumerate().skip(index1) {
+                if matrix.interactions[index1] & (1 << index2) != 0 {
+                    interactions.push((layer1.clone(), layer2.clone()));
+                }
+            }
+        }
+        Self {
+            layers: matrix.layers,
+            interactions,
+        }
+    }
+}
+
+/// A resource with a handle to a [`CollisionMatrix`] asset that the [`CollisionMatrix`] resource is kept in sync with.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     commands.insert_resource(CollisionMatrixHandle(asset_server.load("layers.collmat.ron")));
+/// }
+/// ```
+#[cfg(feature = "collision-matrix-asset")]
+#[derive(Resource, Clone, Debug, Default, Deref, DerefMut)]
+pub struct CollisionMatrixHandle(pub Handle<CollisionMatrix>);
+
+#[cfg(feature = "collision-matrix-asset")]
+impl RonAsset for CollisionMatrix {
+    const EXTENSIONS: &'static [&'static str] = &["collmat.ron"];
+}
+
+/// Loads [`CollisionMatrix`] assets from `.collmat.ron` files.
+#[cfg(feature = "collision-matrix-asset")]
+pub type CollisionMatrixLoader = RonAssetLoader<CollisionMatrix>;
+
+/// Copies the [`CollisionMatrix`] asset of the [`CollisionMatrixHandle`] into the [`CollisionMatrix`] resource
+/// when the asset differs from the resource.
+#[cfg(feature = "collision-matrix-asset")]
+fn update_collision_matrix_from_asset(
+    handle: Option<Res<CollisionMatrixHandle>>,
+    assets: Option<Res<Assets<CollisionMatrix>>>,
+    mut matrix: ResMut<CollisionMatrix>,
+) {
+    let (Some(handle), Some(assets)) = (handle, assets) else {
+        return;
+    };
+    if let Some(asset) = assets.get(&handle.0) {
+        if *asset != *matrix {
+            *matrix = asset.clone();
+        }
+    }
+}
+
+/// Updates the [`CollisionMatrixLayer`] of entities when their [`CollisionLayerName`] or the [`CollisionMatrix`]
+/// changes, and warns about names that don't exist in the matrix. The [broad phase](BroadPhasePlugin)
+/// uses the [`CollisionLayers`] of such entities instead.
+fn update_collision_matrix_layers(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        Ref<CollisionLayerName>,
+        Option<&mut CollisionMatrixLayer>,
+    )>,
+    mut removed_names: RemovedComponents<CollisionLayerName>,
+    matrix: Res<CollisionMatrix>,
+) {
+    for entity in removed_names.read() {
+        if query.contains(entity) {
+            continue;
+        }
+        if let Some(mut commands) = commands.get_entity(entity) {
+            commands.remove::<CollisionMatrixLayer>();
+        }
+    }
+
+    for (entity, name, layer) in &mut query {
+        if !matrix.is_changed() && !name.is_changed() && layer.is_some() {
+            continue;
+        }
+
+        let index = matrix.layer_index(&name);
+        if index.is_none() {
+            warn!(
+                "Collision layer {:?} of entity {:?} doesn't exist in the `CollisionMatrix`",
+                name.0, entity
+            );
+        }
+
+        if let Some(mut layer) = layer {
+            layer.0 = index;
+        } else {
+            commands.entity(entity).insert(CollisionMatrixLayer(index));
+        }
+    }
+}
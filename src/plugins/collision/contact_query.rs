@@ -11,11 +11,14 @@
 //! | [`intersection_test`] | Tests whether two [`Collider`]s are intersecting each other.              |
 //! | [`time_of_impact`]    | Computes when two moving [`Collider`]s hit each other for the first time. |
 //!
+//! The [`contact_manifolds`], [`intersection_test`] and [`time_of_impact`] queries also have `_with_dispatcher`
+//! versions that support custom shapes through a [`PhysicsQueryDispatcher`].
+//!
 //! For geometric queries that query the entire world for intersections, like raycasting, shapecasting
 //! and point projection, see [spatial queries](spatial_query).
 
 use crate::prelude::*;
-use parry::query::{PersistentQueryDispatcher, Unsupported};
+use parry::query::{DefaultQueryDispatcher, PersistentQueryDispatcher, Unsupported};
 
 /// An error indicating that a [contact query](contact_query) is not supported for one of the [`Collider`] shapes.
 pub type UnsupportedShape = Unsupported;
@@ -25,6 +28,9 @@ pub type UnsupportedShape = Unsupported;
 /// Returns `None` if the colliders are separated by a distance greater than `prediction_distance`
 /// or if the given shapes are invalid.
 ///
+/// This uses Parry's default query dispatcher and does not take the [`PhysicsQueryDispatcher`]
+/// resource into account, so custom shapes like voxels and signed distance fields are not supported.
+///
 /// ## Example
 ///
 /// ```
@@ -100,13 +106,13 @@ pub fn contact(
                 return None;
             }
 
-            Some(ContactData {
+            Some(ContactData::new(
                 point1,
                 point2,
                 normal1,
                 normal2,
-                penetration: -contact.dist,
-            })
+                -contact.dist,
+            ))
         } else {
             None
         }
@@ -121,6 +127,10 @@ pub fn contact(
 /// Returns an empty vector if the colliders are separated by a distance greater than `prediction_distance`
 /// or if the given shapes are invalid.
 ///
+/// This uses Parry's default query dispatcher and does not take the [`PhysicsQueryDispatcher`]
+/// resource into account, so custom shapes like voxels and signed distance fields are not supported.
+/// Use [`contact_manifolds_with_dispatcher`] to run the query with a custom dispatcher.
+///
 /// ## Example
 ///
 /// ```
@@ -160,6 +170,33 @@ pub fn contact_manifolds(
     position2: impl Into<Position>,
     rotation2: impl Into<Rotation>,
     prediction_distance: Scalar,
+) -> Vec<ContactManifold> {
+    contact_manifolds_with_dispatcher(
+        &DefaultQueryDispatcher,
+        collider1,
+        position1,
+        rotation1,
+        collider2,
+        position2,
+        rotation2,
+        prediction_distance,
+    )
+}
+
+/// Computes all [`ContactManifold`]s between two [`Collider`]s using the given query dispatcher.
+///
+/// This is the same as [`contact_manifolds`], but it supports custom shapes handled by the dispatcher.
+/// See [`PhysicsQueryDispatcher`] for more information.
+#[allow(clippy::too_many_arguments)]
+pub fn contact_manifolds_with_dispatcher(
+    dispatcher: &dyn PersistentQueryDispatcher,
+    collider1: &Collider,
+    position1: impl Into<Position>,
+    rotation1: impl Into<Rotation>,
+    collider2: &Collider,
+    position2: impl Into<Position>,
+    rotation2: impl Into<Rotation>,
+    prediction_distance: Scalar,
 ) -> Vec<ContactManifold> {
     let isometry1 = utils::make_isometry(position1.into(), rotation1.into());
     let isometry2 = utils::make_isometry(position2.into(), rotation2.into());
@@ -167,7 +204,7 @@ pub fn contact_manifolds(
 
     // TODO: Reuse manifolds from previous frame to improve performance
     let mut manifolds: Vec<parry::query::ContactManifold<(), ()>> = vec![];
-    let _ = parry::query::DefaultQueryDispatcher.contact_manifolds(
+    let _ = dispatcher.contact_manifolds(
         &isometry12,
         collider1.shape_scaled().0.as_ref(),
         collider2.shape_scaled().0.as_ref(),
@@ -202,12 +239,15 @@ pub fn contact_manifolds(
                 contacts: manifold
                     .contacts()
                     .iter()
-                    .map(|contact| ContactData {
-                        point1: subpos1.transform_point(&contact.local_p1).into(),
-                        point2: subpos2.transform_point(&contact.local_p2).into(),
-                        normal1,
-                        normal2,
-                        penetration: -contact.dist,
+                    .map(|contact| {
+                        ContactData::new(
+                            subpos1.transform_point(&contact.local_p1).into(),
+                            subpos2.transform_point(&contact.local_p2).into(),
+                            normal1,
+                            normal2,
+                            -contact.dist,
+                        )
+                        .with_feature_ids(contact.fid1, contact.fid2)
                     })
                     .collect(),
             })
@@ -237,6 +277,9 @@ pub enum ClosestPoints {
 ///
 /// Returns `Err(UnsupportedShape)` if either of the collider shapes is not supported.
 ///
+/// This uses Parry's default query dispatcher and does not take the [`PhysicsQueryDispatcher`]
+/// resource into account, so custom shapes like voxels and signed distance fields are not supported.
+///
 /// ## Example
 ///
 /// ```
@@ -332,6 +375,9 @@ pub fn closest_points(
 /// Returns `0.0` if the colliders are touching or penetrating, and `Err(UnsupportedShape)`
 /// if either of the collider shapes is not supported.
 ///
+/// This uses Parry's default query dispatcher and does not take the [`PhysicsQueryDispatcher`]
+/// resource into account, so custom shapes like voxels and signed distance fields are not supported.
+///
 /// ## Example
 ///
 /// ```
@@ -400,6 +446,10 @@ pub fn distance(
 ///
 /// Returns `Err(UnsupportedShape)` if either of the collider shapes is not supported.
 ///
+/// This uses Parry's default query dispatcher and does not take the [`PhysicsQueryDispatcher`]
+/// resource into account, so custom shapes like voxels and signed distance fields are not supported.
+/// Use [`intersection_test_with_dispatcher`] to run the query with a custom dispatcher.
+///
 /// ## Example
 ///
 /// ```
@@ -450,16 +500,39 @@ pub fn intersection_test(
     collider2: &Collider,
     position2: impl Into<Position>,
     rotation2: impl Into<Rotation>,
+) -> Result<bool, UnsupportedShape> {
+    intersection_test_with_dispatcher(
+        &DefaultQueryDispatcher,
+        collider1,
+        position1,
+        rotation1,
+        collider2,
+        position2,
+        rotation2,
+    )
+}
+
+/// Tests whether two [`Collider`]s are intersecting each other using the given query dispatcher.
+///
+/// This is the same as [`intersection_test`], but it supports custom shapes handled by the dispatcher.
+/// See [`PhysicsQueryDispatcher`] for more information.
+pub fn intersection_test_with_dispatcher(
+    dispatcher: &dyn PersistentQueryDispatcher,
+    collider1: &Collider,
+    position1: impl Into<Position>,
+    rotation1: impl Into<Rotation>,
+    collider2: &Collider,
+    position2: impl Into<Position>,
+    rotation2: impl Into<Rotation>,
 ) -> Result<bool, UnsupportedShape> {
     let rotation1: Rotation = rotation1.into();
     let rotation2: Rotation = rotation2.into();
     let isometry1 = utils::make_isometry(position1.into(), rotation1);
     let isometry2 = utils::make_isometry(position2.into(), rotation2);
 
-    parry::query::intersection_test(
-        &isometry1,
+    dispatcher.intersection_test(
+        &isometry1.inv_mul(&isometry2),
         collider1.shape_scaled().0.as_ref(),
-        &isometry2,
         collider2.shape_scaled().0.as_ref(),
     )
 }
@@ -493,6 +566,10 @@ pub struct TimeOfImpact {
 /// Returns `Ok(None)` if the time of impact is greater than `max_time_of_impact`
 /// and `Err(UnsupportedShape)` if either of the collider shapes is not supported.
 ///
+/// This uses Parry's default query dispatcher and does not take the [`PhysicsQueryDispatcher`]
+/// resource into account, so custom shapes like voxels and signed distance fields are not supported.
+/// Use [`time_of_impact_with_dispatcher`] to run the query with a custom dispatcher.
+///
 /// ## Example
 ///
 /// ```
@@ -534,6 +611,37 @@ pub fn time_of_impact(
     rotation2: impl Into<Rotation>,
     velocity2: impl Into<LinearVelocity>,
     max_time_of_impact: Scalar,
+) -> Result<Option<TimeOfImpact>, UnsupportedShape> {
+    time_of_impact_with_dispatcher(
+        &DefaultQueryDispatcher,
+        collider1,
+        position1,
+        rotation1,
+        velocity1,
+        collider2,
+        position2,
+        rotation2,
+        velocity2,
+        max_time_of_impact,
+    )
+}
+
+/// Computes when two moving [`Collider`]s hit each other for the first time using the given query dispatcher.
+///
+/// This is the same as [`time_of_impact`], but it supports custom shapes handled by the dispatcher.
+/// See [`PhysicsQueryDispatcher`] for more information.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn time_of_impact_with_dispatcher(
+    dispatcher: &dyn PersistentQueryDispatcher,
+    collider1: &Collider,
+    position1: impl Into<Position>,
+    rotation1: impl Into<Rotation>,
+    velocity1: impl Into<LinearVelocity>,
+    collider2: &Collider,
+    position2: impl Into<Position>,
+    rotation2: impl Into<Rotation>,
+    velocity2: impl Into<LinearVelocity>,
+    max_time_of_impact: Scalar,
 ) -> Result<Option<TimeOfImpact>, UnsupportedShape> {
     let rotation1: Rotation = rotation1.into();
     let rotation2: Rotation = rotation2.into();
@@ -544,24 +652,26 @@ pub fn time_of_impact(
     let isometry1 = utils::make_isometry(position1.into(), rotation1);
     let isometry2 = utils::make_isometry(position2.into(), rotation2);
 
-    parry::query::time_of_impact(
-        &isometry1,
-        &velocity1.0.into(),
-        collider1.shape_scaled().0.as_ref(),
-        &isometry2,
-        &velocity2.0.into(),
-        collider2.shape_scaled().0.as_ref(),
-        max_time_of_impact,
-        true,
-    )
-    .map(|toi| {
-        toi.map(|toi| TimeOfImpact {
-            time_of_impact: toi.toi,
-            point1: toi.witness1.into(),
-            point2: toi.witness2.into(),
-            normal1: toi.normal1.into(),
-            normal2: toi.normal2.into(),
-            status: toi.status,
+    // The relative motion of the second collider in the local space of the first collider
+    let velocity12 = isometry1.inverse_transform_vector(&(velocity2.0 - velocity1.0).into());
+
+    dispatcher
+        .time_of_impact(
+            &isometry1.inv_mul(&isometry2),
+            &velocity12,
+            collider1.shape_scaled().0.as_ref(),
+            collider2.shape_scaled().0.as_ref(),
+            max_time_of_impact,
+            true,
+        )
+        .map(|toi| {
+            toi.map(|toi| TimeOfImpact {
+                time_of_impact: toi.toi,
+                point1: toi.witness1.into(),
+                point2: toi.witness2.into(),
+                normal1: toi.normal1.into(),
+                normal2: toi.normal2.into(),
+                status: toi.status,
+            })
         })
-    })
 }
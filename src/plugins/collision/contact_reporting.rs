@@ -3,6 +3,7 @@
 //! See [`ContactReportingPlugin`].
 
 use crate::prelude::*;
+use std::ops::DerefMut;
 
 /// Sends collision events and updates [`CollidingEntities`].
 ///
@@ -14,6 +15,20 @@ use crate::prelude::*;
 /// - [`Collision`]
 /// - [`CollisionStarted`]
 /// - [`CollisionEnded`]
+/// - [`ContactForceEvent`] (only for colliders with a [`ContactForceThreshold`])
+/// - [`SensorEntered`] and [`SensorExited`] (only for [sensors](Sensor))
+///
+/// Entities with the [`CollisionEventsEnabled`] component also receive their own collisions
+/// in the [`OnCollisionStart`] and [`OnCollisionEnd`] components if they have them.
+///
+/// [`Collision`], [`CollisionStarted`] and [`CollisionEnded`] are only sent for collisions where at least one
+/// of the colliders or their rigid bodies has [`CollisionEventsEnabled`]. This keeps the number of events low
+/// in scenes with lots of contacts that nothing is interested in. To send them for all collisions,
+/// disable [`ContactReportingConfig::require_opt_in`].
+///
+/// [Sensors](Sensor) don't generate contacts, so they don't send [`Collision`] events. They send [`SensorEntered`]
+/// and [`SensorExited`] along with [`CollisionStarted`] and [`CollisionEnded`] when other colliders start
+/// and stop intersecting them. Both kinds of intersections are registered in [`CollidingEntities`].
 ///
 /// You can listen to them with normal event readers:
 ///
@@ -25,6 +40,10 @@ use crate::prelude::*;
 /// fn main() {
 ///     App::new()
 ///         .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+///         // Send events for all collisions
+///         .insert_resource(ContactReportingConfig {
+///             require_opt_in: false,
+///         })
 ///         .add_systems(Update, print_collisions)
 ///         .run();
 /// }
@@ -39,25 +58,62 @@ use crate::prelude::*;
 ///     }
 /// }
 /// ```
+///
+/// The [`ContactImpulses`] of rigid bodies that have the component are also updated each frame.
 pub struct ContactReportingPlugin;
 
 impl Plugin for ContactReportingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<Collision>()
+        app.init_resource::<ContactReportingConfig>()
+            .register_type::<ContactReportingConfig>()
+            .add_event::<Collision>()
             .add_event::<CollisionStarted>()
-            .add_event::<CollisionEnded>();
+            .add_event::<CollisionEnded>()
+            .add_event::<ContactForceEvent>()
+            .add_event::<SensorEntered>()
+            .add_event::<SensorExited>();
 
         let physics_schedule = app
             .get_schedule_mut(PhysicsSchedule)
             .expect("add PhysicsSchedule first");
 
-        physics_schedule.add_systems(report_contacts.in_set(PhysicsStepSet::ReportContacts));
+        physics_schedule.add_systems(
+            (
+                (report_contacts, report_sensor_overlaps).chain(),
+                report_contact_forces,
+                update_contact_impulses,
+            )
+                .in_set(PhysicsStepSet::ReportContacts),
+        );
+    }
+}
+
+/// A resource for configuring the [`ContactReportingPlugin`].
+#[derive(Resource, Reflect, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Resource)]
+pub struct ContactReportingConfig {
+    /// If `true`, [`Collision`], [`CollisionStarted`] and [`CollisionEnded`] events are only sent
+    /// for collisions where at least one of the colliders or their rigid bodies has [`CollisionEventsEnabled`].
+    ///
+    /// `true` by default. If `false`, the events are sent for all collisions.
+    pub require_opt_in: bool,
+}
+
+impl Default for ContactReportingConfig {
+    fn default() -> Self {
+        Self {
+            require_opt_in: true,
+        }
     }
 }
 
 /// A [collision event](ContactReportingPlugin#collision-events)
 /// that is sent for each collision.
 ///
+/// The event is only sent if one of the colliders or their rigid bodies has [`CollisionEventsEnabled`],
+/// unless [`ContactReportingConfig::require_opt_in`] is disabled.
+///
 /// ## Example
 ///
 /// ```no_run
@@ -89,6 +145,9 @@ pub struct Collision(pub Contacts);
 /// A [collision event](ContactReportingPlugin#collision-events)
 /// that is sent when two entities start colliding.
 ///
+/// The event is only sent if one of the colliders or their rigid bodies has [`CollisionEventsEnabled`],
+/// unless [`ContactReportingConfig::require_opt_in`] is disabled.
+///
 /// ## Example
 ///
 /// ```no_run
@@ -120,6 +179,9 @@ pub struct CollisionStarted(pub Entity, pub Entity);
 /// A [collision event](ContactReportingPlugin#collision-events)
 /// that is sent when two entities stop colliding.
 ///
+/// The event is only sent if one of the colliders or their rigid bodies has [`CollisionEventsEnabled`],
+/// unless [`ContactReportingConfig::require_opt_in`] is disabled.
+///
 /// ## Example
 ///
 /// ```no_run
@@ -148,35 +210,167 @@ pub struct CollisionStarted(pub Entity, pub Entity);
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct CollisionEnded(pub Entity, pub Entity);
 
-/// Sends collision events and updates [`CollidingEntities`].
+/// A [collision event](ContactReportingPlugin#collision-events)
+/// that is sent when a collider starts intersecting a [`Sensor`].
+///
+/// The first entity is the sensor, and the second entity is the other collider.
+/// If both colliders are sensors, the order is arbitrary.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+///         .add_systems(Update, print_sensor_entered)
+///         .run();
+/// }
+///
+/// fn print_sensor_entered(mut sensor_entered_ev_reader: EventReader<SensorEntered>) {
+///     for SensorEntered(sensor, entity) in sensor_entered_ev_reader.read() {
+///         println!("Entity {:?} entered sensor {:?}", entity, sensor);
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorEntered(pub Entity, pub Entity);
+
+/// A [collision event](ContactReportingPlugin#collision-events)
+/// that is sent when a collider stops intersecting a [`Sensor`].
+///
+/// The first entity is the sensor, and the second entity is the other collider.
+/// If both colliders are sensors, the order is arbitrary.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+///         .add_systems(Update, print_sensor_exited)
+///         .run();
+/// }
+///
+/// fn print_sensor_exited(mut sensor_exited_ev_reader: EventReader<SensorExited>) {
+///     for SensorExited(sensor, entity) in sensor_exited_ev_reader.read() {
+///         println!("Entity {:?} exited sensor {:?}", entity, sensor);
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorExited(pub Entity, pub Entity);
+
+/// A [collision event](ContactReportingPlugin#collision-events) that is sent when the magnitude
+/// of the total impulse applied by the contacts between two colliders during a physics frame
+/// exceeds the [`ContactForceThreshold`] of either of the colliders or their rigid bodies.
+///
+/// The impulses are in world space and applied to the first entity. The second entity receives the opposite impulses.
+/// They include the impulses caused by static and dynamic [friction](Friction) and [restitution](Restitution).
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+///         .add_systems(Update, play_impact_sounds)
+///         .run();
+/// }
+///
+/// fn play_impact_sounds(mut contact_force_ev_reader: EventReader<ContactForceEvent>) {
+///     for event in contact_force_ev_reader.read() {
+///         println!(
+///             "Entities {:?} and {:?} hit each other with an impulse of {}",
+///             event.entity1,
+///             event.entity2,
+///             event.total_impulse().length(),
+///         );
+///     }
+/// }
+/// ```
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContactForceEvent {
+    /// First entity in the contact.
+    pub entity1: Entity,
+    /// Second entity in the contact.
+    pub entity2: Entity,
+    /// The total normal impulse applied to the first entity during the physics frame.
+    pub normal_impulse: Vector,
+    /// The total friction impulse applied to the first entity during the physics frame.
+    pub friction_impulse: Vector,
+}
+
+impl ContactForceEvent {
+    /// Returns the sum of the normal and friction impulses.
+    pub fn total_impulse(&self) -> Vector {
+        self.normal_impulse + self.friction_impulse
+    }
+}
+
+/// Sends collision events, fills the [`OnCollisionStart`] and [`OnCollisionEnd`] components
+/// of entities with [`CollisionEventsEnabled`], and updates [`CollidingEntities`].
+#[allow(clippy::too_many_arguments)]
 pub fn report_contacts(
+    config: Res<ContactReportingConfig>,
     mut colliders: Query<&mut CollidingEntities>,
+    parents: Query<&ColliderParent>,
+    events_enabled: Query<(), With<CollisionEventsEnabled>>,
+    mut on_collision_start: Query<&mut OnCollisionStart>,
+    mut on_collision_end: Query<&mut OnCollisionEnd>,
     collisions: Res<Collisions>,
     mut collision_ev_writer: EventWriter<Collision>,
     mut collision_started_ev_writer: EventWriter<CollisionStarted>,
     mut collision_ended_ev_writer: EventWriter<CollisionEnded>,
 ) {
-    for ((entity1, entity2), contacts) in collisions.get_internal().iter() {
-        if contacts.during_current_frame {
-            collision_ev_writer.send(Collision(contacts.clone()));
+    // Clear the collisions of the previous frame
+    for mut started in &mut on_collision_start {
+        if !started.is_empty() {
+            started.clear();
+        }
+    }
+    for mut ended in &mut on_collision_end {
+        if !ended.is_empty() {
+            ended.clear();
+        }
+    }
 
-            // Collision started
-            if !contacts.during_previous_frame {
-                collision_started_ev_writer.send(CollisionStarted(*entity1, *entity2));
+    // Returns the collider and rigid body that opted in to collision events
+    let get_receivers = |collider: Entity, body: Option<Entity>| {
+        [
+            Some(collider).filter(|collider| events_enabled.contains(*collider)),
+            body.filter(|body| *body != collider && events_enabled.contains(*body)),
+        ]
+    };
 
-                if let Ok(mut colliding_entities1) = colliders.get_mut(*entity1) {
-                    colliding_entities1.insert(*entity2);
-                }
-                if let Ok(mut colliding_entities2) = colliders.get_mut(*entity2) {
-                    colliding_entities2.insert(*entity1);
-                }
+    for ((entity1, entity2), contacts) in collisions.get_internal().iter() {
+        let started = contacts.during_current_frame && !contacts.during_previous_frame;
+        let ended = !contacts.during_current_frame && contacts.during_previous_frame;
+
+        if started {
+            if let Ok(mut colliding_entities1) = colliders.get_mut(*entity1) {
+                colliding_entities1.insert(*entity2);
+            }
+            if let Ok(mut colliding_entities2) = colliders.get_mut(*entity2) {
+                colliding_entities2.insert(*entity1);
             }
         }
 
-        // Collision ended
-        if !contacts.during_current_frame && contacts.during_previous_frame {
-            collision_ended_ev_writer.send(CollisionEnded(*entity1, *entity2));
-
+        if ended {
             if let Ok(mut colliding_entities1) = colliders.get_mut(*entity1) {
                 colliding_entities1.remove(entity2);
             }
@@ -184,5 +378,184 @@ pub fn report_contacts(
                 colliding_entities2.remove(entity1);
             }
         }
+
+        let body1 = parents.get(*entity1).ok().map(|parent| parent.get());
+        let body2 = parents.get(*entity2).ok().map(|parent| parent.get());
+        let receivers1 = get_receivers(*entity1, body1);
+        let receivers2 = get_receivers(*entity2, body2);
+
+        // Skip collisions that no entity is interested in
+        let has_receivers = receivers1
+            .iter()
+            .chain(receivers2.iter())
+            .any(Option::is_some);
+        if config.require_opt_in && !has_receivers {
+            continue;
+        }
+
+        let data1 = CollisionEventData {
+            collider: *entity1,
+            other_collider: *entity2,
+            other_body: body2,
+        };
+        let data2 = CollisionEventData {
+            collider: *entity2,
+            other_collider: *entity1,
+            other_body: body1,
+        };
+
+        if contacts.during_current_frame {
+            collision_ev_writer.send(Collision(contacts.clone()));
+        }
+
+        if started {
+            collision_started_ev_writer.send(CollisionStarted(*entity1, *entity2));
+            push_collision(&mut on_collision_start, receivers1, data1);
+            push_collision(&mut on_collision_start, receivers2, data2);
+        }
+
+        if ended {
+            collision_ended_ev_writer.send(CollisionEnded(*entity1, *entity2));
+            push_collision(&mut on_collision_end, receivers1, data1);
+            push_collision(&mut on_collision_end, receivers2, data2);
+        }
+    }
+}
+
+/// Adds a collision to the [`OnCollisionStart`] or [`OnCollisionEnd`] components of the given entities.
+fn push_collision<T: Component + DerefMut<Target = Vec<CollisionEventData>>>(
+    buffers: &mut Query<&mut T>,
+    receivers: [Option<Entity>; 2],
+    data: CollisionEventData,
+) {
+    for receiver in receivers.into_iter().flatten() {
+        if let Ok(mut buffer) = buffers.get_mut(receiver) {
+            buffer.push(data);
+        }
+    }
+}
+
+/// Sends [`SensorEntered`] and [`SensorExited`] events along with [`CollisionStarted`] and [`CollisionEnded`] events
+/// and updates [`CollidingEntities`] based on [`SensorOverlaps`].
+///
+/// Like in [`report_contacts`], [`CollisionStarted`] and [`CollisionEnded`] are only sent if one of the colliders
+/// or their rigid bodies has [`CollisionEventsEnabled`], unless [`ContactReportingConfig::require_opt_in`] is disabled.
+#[allow(clippy::too_many_arguments)]
+pub fn report_sensor_overlaps(
+    config: Res<ContactReportingConfig>,
+    mut colliders: Query<&mut CollidingEntities>,
+    parents: Query<&ColliderParent>,
+    events_enabled: Query<(), With<CollisionEventsEnabled>>,
+    sensor_overlaps: Res<SensorOverlaps>,
+    mut sensor_entered_ev_writer: EventWriter<SensorEntered>,
+    mut sensor_exited_ev_writer: EventWriter<SensorExited>,
+    mut collision_started_ev_writer: EventWriter<CollisionStarted>,
+    mut collision_ended_ev_writer: EventWriter<CollisionEnded>,
+) {
+    // Returns true if the collider or its rigid body opted in to collision events
+    let is_opted_in = |collider: Entity| {
+        events_enabled.contains(collider)
+            || parents
+                .get(collider)
+                .is_ok_and(|parent| events_enabled.contains(parent.get()))
+    };
+    let send_collision_events = |entity1: Entity, entity2: Entity| {
+        !config.require_opt_in || is_opted_in(entity1) || is_opted_in(entity2)
+    };
+
+    for (entity1, entity2) in sensor_overlaps.started() {
+        sensor_entered_ev_writer.send(SensorEntered(entity1, entity2));
+        if send_collision_events(entity1, entity2) {
+            collision_started_ev_writer.send(CollisionStarted(entity1, entity2));
+        }
+
+        if let Ok(mut colliding_entities1) = colliders.get_mut(entity1) {
+            colliding_entities1.insert(entity2);
+        }
+        if let Ok(mut colliding_entities2) = colliders.get_mut(entity2) {
+            colliding_entities2.insert(entity1);
+        }
+    }
+
+    for (entity1, entity2) in sensor_overlaps.ended() {
+        sensor_exited_ev_writer.send(SensorExited(entity1, entity2));
+        if send_collision_events(entity1, entity2) {
+            collision_ended_ev_writer.send(CollisionEnded(entity1, entity2));
+        }
+
+        if let Ok(mut colliding_entities1) = colliders.get_mut(entity1) {
+            colliding_entities1.remove(&entity2);
+        }
+        if let Ok(mut colliding_entities2) = colliders.get_mut(entity2) {
+            colliding_entities2.remove(&entity1);
+        }
+    }
+}
+
+/// Sends [`ContactForceEvent`]s for collisions whose total impulse exceeds the [`ContactForceThreshold`]
+/// of either of the colliders or their rigid bodies.
+pub fn report_contact_forces(
+    thresholds: Query<&ContactForceThreshold>,
+    parents: Query<&ColliderParent>,
+    collisions: Res<Collisions>,
+    mut contact_force_ev_writer: EventWriter<ContactForceEvent>,
+) {
+    // Colliders without a threshold use the threshold of their rigid body
+    let get_threshold = |entity: Entity| {
+        thresholds
+            .get(entity)
+            .ok()
+            .or_else(|| thresholds.get(parents.get(entity).ok()?.get()).ok())
+            .map(|threshold| threshold.0)
+    };
+
+    for contacts in collisions.iter() {
+        let threshold = match (
+            get_threshold(contacts.entity1),
+            get_threshold(contacts.entity2),
+        ) {
+            (Some(threshold1), Some(threshold2)) => threshold1.min(threshold2),
+            (Some(threshold), None) | (None, Some(threshold)) => threshold,
+            (None, None) => continue,
+        };
+
+        let total_impulse = contacts.total_normal_impulse + contacts.total_friction_impulse;
+        if total_impulse.length() > threshold {
+            contact_force_ev_writer.send(ContactForceEvent {
+                entity1: contacts.entity1,
+                entity2: contacts.entity2,
+                normal_impulse: contacts.total_normal_impulse,
+                friction_impulse: contacts.total_friction_impulse,
+            });
+        }
+    }
+}
+
+/// Sums the impulses applied by the contacts of rigid bodies into their [`ContactImpulses`].
+pub fn update_contact_impulses(
+    mut bodies: Query<&mut ContactImpulses>,
+    parents: Query<&ColliderParent>,
+    collisions: Res<Collisions>,
+) {
+    for mut impulses in &mut bodies {
+        impulses.set_if_neq(ContactImpulses::default());
+    }
+
+    for contacts in collisions.iter() {
+        let body1 = parents
+            .get(contacts.entity1)
+            .map_or(contacts.entity1, |parent| parent.get());
+        let body2 = parents
+            .get(contacts.entity2)
+            .map_or(contacts.entity2, |parent| parent.get());
+
+        if let Ok(mut impulses1) = bodies.get_mut(body1) {
+            impulses1.normal_impulse += contacts.total_normal_impulse;
+            impulses1.friction_impulse += contacts.total_friction_impulse;
+        }
+        if let Ok(mut impulses2) = bodies.get_mut(body2) {
+            impulses2.normal_impulse -= contacts.total_normal_impulse;
+            impulses2.friction_impulse -= contacts.total_friction_impulse;
+        }
     }
 }
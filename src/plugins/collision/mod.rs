@@ -1,23 +1,41 @@
 //! Collision detection is used to detect and compute intersections between [`Collider`]s.
 //!
-//! In `bevy_xpbd`, collision detection is split into three plugins:
+//! In `bevy_xpbd`, collision detection is split into the following plugins:
 //!
+//! - [`CollisionMatrixPlugin`]: Manages the [`CollisionMatrix`] that decides which entities with a
+//!   [`CollisionLayerName`] interact.
 //! - [`BroadPhasePlugin`]: Collects pairs of potentially colliding entities into [`BroadCollisionPairs`].
 //! - [`NarrowPhasePlugin`]: Computes contacts for broad phase collision pairs and adds them to [`Collisions`].
-//! - [`ContactReportingPlugin`] (optional): Sends collision events and updates [`CollidingEntities`] based on [`Collisions`].
+//!   Overlaps with [sensors](Sensor) are stored in [`SensorOverlaps`] instead.
+//! - [`CcdPlugin`] (optional): Prevents fast moving bodies with [`SweptCcd`] from tunneling through colliders.
+//! - [`CollisionFilterPlugin`] (optional): Rejects collision pairs using a custom [`CollisionFilter`].
+//! - [`ContactReportingPlugin`] (optional): Sends collision events and updates [`CollidingEntities`] based on [`Collisions`]
+//!   and [`SensorOverlaps`].
 //!
 //! Spatial queries are handled by the [`SpatialQueryPlugin`].
 //!
 //! You can also find several utility methods for computing contacts in [`contact_query`].
 
 pub mod broad_phase;
+pub mod ccd;
+pub mod collision_filter;
+pub mod collision_matrix;
 pub mod contact_query;
 pub mod contact_reporting;
 pub mod narrow_phase;
+pub mod sdf;
+pub mod voxels;
+
+pub use parry::shape::PackedFeatureId;
+
+use std::sync::Arc;
 
 use crate::prelude::*;
 use bevy::prelude::*;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
+use parry::query::{DefaultQueryDispatcher, PersistentQueryDispatcher};
+use sdf::SdfQueryDispatcher;
+use voxels::VoxelsQueryDispatcher;
 
 // Collisions are stored in an `IndexMap` that uses fxhash.
 // It should have faster iteration than a `HashMap` while mostly retaining other performance characteristics.
@@ -239,6 +257,180 @@ impl Collisions {
     }
 }
 
+/// A resource that selects the query dispatcher used for computing contacts and intersections between [`Collider`]s.
+///
+/// The dispatcher is used by the [narrow phase](NarrowPhasePlugin), [swept CCD](CcdPlugin), the
+/// [character controller](CharacterControllerPlugin) and [spatial queries](spatial_query).
+/// By default, it is Parry's `DefaultQueryDispatcher`, which supports all of the built-in collider shapes.
+///
+/// ## Custom shapes
+///
+/// Custom shapes like an analytic torus can be used by implementing Parry's `Shape` trait for them
+/// and creating colliders with `Collider::from(SharedShape::new(shape))`. Queries involving them are
+/// handled by a custom dispatcher that implements Parry's `QueryDispatcher` and `PersistentQueryDispatcher`
+/// traits. It should return `Err(Unsupported)` for pairs it doesn't handle, so that it can be chained
+/// with the default dispatcher:
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(
+    feature = "2d",
+    doc = "use bevy_xpbd_2d::{parry::query::{DefaultQueryDispatcher, PersistentQueryDispatcher, QueryDispatcher}, prelude::*};"
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "use bevy_xpbd_3d::{parry::query::{DefaultQueryDispatcher, PersistentQueryDispatcher, QueryDispatcher}, prelude::*};"
+)]
+#[cfg_attr(feature = "2d", doc = "# use bevy_xpbd_2d::parry;")]
+#[cfg_attr(feature = "3d", doc = "# use bevy_xpbd_3d::parry;")]
+///
+/// /// Handles the contacts of a custom `Torus` shape and returns `Err(Unsupported)` for other pairs.
+/// struct TorusDispatcher;
+/// # use parry::{math::{Isometry, Real, Vector}, query::{self, Unsupported}, shape::Shape};
+/// # impl QueryDispatcher for TorusDispatcher {
+/// #     fn intersection_test(&self, _: &Isometry<Real>, _: &dyn Shape, _: &dyn Shape) -> Result<bool, Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// #     fn distance(&self, _: &Isometry<Real>, _: &dyn Shape, _: &dyn Shape) -> Result<Real, Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// #     fn contact(&self, _: &Isometry<Real>, _: &dyn Shape, _: &dyn Shape, _: Real) -> Result<Option<query::Contact>, Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// #     fn closest_points(&self, _: &Isometry<Real>, _: &dyn Shape, _: &dyn Shape, _: Real) -> Result<query::ClosestPoints, Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// #     fn time_of_impact(&self, _: &Isometry<Real>, _: &Vector<Real>, _: &dyn Shape, _: &dyn Shape, _: Real, _: bool) -> Result<Option<query::TOI>, Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// #     fn nonlinear_time_of_impact(&self, _: &query::NonlinearRigidMotion, _: &dyn Shape, _: &query::NonlinearRigidMotion, _: &dyn Shape, _: Real, _: Real, _: bool) -> Result<Option<query::TOI>, Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// # }
+/// # impl PersistentQueryDispatcher for TorusDispatcher {
+/// #     fn contact_manifolds(&self, _: &Isometry<Real>, _: &dyn Shape, _: &dyn Shape, _: Real, _: &mut Vec<query::ContactManifold<(), ()>>, _: &mut Option<query::ContactManifoldsWorkspace>) -> Result<(), Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// #     fn contact_manifold_convex_convex(&self, _: &Isometry<Real>, _: &dyn Shape, _: &dyn Shape, _: Real, _: &mut query::ContactManifold<(), ()>) -> Result<(), Unsupported> {
+/// #         Err(Unsupported)
+/// #     }
+/// # }
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((DefaultPlugins, PhysicsPlugins::default()))
+///         .insert_resource(PhysicsQueryDispatcher::new(
+///             TorusDispatcher.chain(DefaultQueryDispatcher),
+///         ))
+///         .run();
+/// }
+/// ```
+#[derive(Resource, Clone)]
+pub struct PhysicsQueryDispatcher(pub(crate) Arc<dyn PersistentQueryDispatcher>);
+
+impl PhysicsQueryDispatcher {
+    /// Creates a new [`PhysicsQueryDispatcher`] that uses the given dispatcher.
+    ///
+    /// The dispatcher is wrapped in a [`VoxelsQueryDispatcher`] and an [`SdfQueryDispatcher`],
+    /// so [voxel colliders](Collider::voxels) and [signed distance field colliders](Collider::sdf)
+    /// are supported regardless of the dispatcher.
+    pub fn new(dispatcher: impl PersistentQueryDispatcher + 'static) -> Self {
+        Self(Arc::new(VoxelsQueryDispatcher(SdfQueryDispatcher(
+            dispatcher,
+        ))))
+    }
+}
+
+impl Default for PhysicsQueryDispatcher {
+    fn default() -> Self {
+        Self::new(DefaultQueryDispatcher)
+    }
+}
+
+impl std::fmt::Debug for PhysicsQueryDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PhysicsQueryDispatcher")
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::ops::Deref for PhysicsQueryDispatcher {
+    type Target = dyn PersistentQueryDispatcher;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// A resource that stores the pairs of colliders that intersect a [`Sensor`] collider.
+///
+/// Sensors don't generate [`Contacts`]. Instead, the [narrow phase](NarrowPhasePlugin) checks
+/// the [`BroadCollisionPairs`] involving sensors once per frame after the substeps using a boolean
+/// [intersection test](contact_query::intersection_test), which is much cheaper than computing contact manifolds.
+///
+/// In each pair, the first entity is the sensor. If both colliders are sensors, the order is arbitrary.
+/// The overlaps are used for sending [`SensorEntered`] and [`SensorExited`] events
+/// and for updating [`CollidingEntities`] in the [`ContactReportingPlugin`].
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct SensorOverlaps {
+    current: IndexSet<(Entity, Entity), fxhash::FxBuildHasher>,
+    previous: IndexSet<(Entity, Entity), fxhash::FxBuildHasher>,
+}
+
+impl SensorOverlaps {
+    /// Returns `true` if the given entities are overlapping during the current frame.
+    ///
+    /// The order of the entities does not matter.
+    pub fn contains(&self, entity1: Entity, entity2: Entity) -> bool {
+        self.current.contains(&(entity1, entity2)) || self.current.contains(&(entity2, entity1))
+    }
+
+    /// Returns an iterator over the pairs of entities that are overlapping during the current frame.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.current.iter().copied()
+    }
+
+    /// Returns an iterator over the pairs of entities that started overlapping during the current frame.
+    pub fn started(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.current
+            .iter()
+            .filter(|(entity1, entity2)| {
+                !self.previous.contains(&(*entity1, *entity2))
+                    && !self.previous.contains(&(*entity2, *entity1))
+            })
+            .copied()
+    }
+
+    /// Returns an iterator over the pairs of entities that stopped overlapping during the current frame.
+    pub fn ended(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.previous
+            .iter()
+            .filter(|(entity1, entity2)| !self.contains(*entity1, *entity2))
+            .copied()
+    }
+
+    /// Returns an iterator over the entities that are overlapping with the given entity during the current frame.
+    pub fn overlaps_with_entity(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.current.iter().filter_map(move |(entity1, entity2)| {
+            if *entity1 == entity {
+                Some(*entity2)
+            } else if *entity2 == entity {
+                Some(*entity1)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Replaces the overlaps of the current frame, moving the old ones to the previous frame.
+    pub(crate) fn set_overlaps(
+        &mut self,
+        overlaps: IndexSet<(Entity, Entity), fxhash::FxBuildHasher>,
+    ) {
+        self.previous = std::mem::replace(&mut self.current, overlaps);
+    }
+}
+
 /// Stores the collision pairs from the previous frame.
 /// This is used for detecting when collisions have started or ended.
 #[derive(Resource, Clone, Debug, Default, Deref, DerefMut, PartialEq)]
@@ -249,6 +441,9 @@ pub(super) struct PreviousCollisions(Collisions);
 /// The contacts are stored in contact manifolds.
 /// Each manifold contains one or more contact points, and each contact
 /// in a given manifold shares the same contact normal.
+///
+/// Contacts can be created with [`Contacts::new`], which marks the entities as being in contact
+/// during the current frame and substep.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contacts {
@@ -266,6 +461,35 @@ pub struct Contacts {
     pub during_current_substep: bool,
     /// True if the bodies were in contact during the previous frame.
     pub during_previous_frame: bool,
+    /// The total normal impulse applied to the first entity by the contacts during the current frame,
+    /// in world space. The second entity receives the opposite impulse.
+    ///
+    /// If both bodies are static or sleeping, the impulse of the last simulated frame is kept.
+    pub total_normal_impulse: Vector,
+    /// The total friction impulse applied to the first entity by the contacts during the current frame,
+    /// in world space. The second entity receives the opposite impulse.
+    ///
+    /// If both bodies are static or sleeping, the impulse of the last simulated frame is kept.
+    pub total_friction_impulse: Vector,
+}
+
+impl Contacts {
+    /// Creates new [`Contacts`] between the given entities with the given contact manifolds.
+    ///
+    /// The entities are marked as being in contact during the current frame and substep,
+    /// and the total impulses are zero.
+    pub fn new(entity1: Entity, entity2: Entity, manifolds: Vec<ContactManifold>) -> Self {
+        Self {
+            entity1,
+            entity2,
+            manifolds,
+            during_current_frame: true,
+            during_current_substep: true,
+            during_previous_frame: false,
+            total_normal_impulse: Vector::ZERO,
+            total_friction_impulse: Vector::ZERO,
+        }
+    }
 }
 
 /// A contact manifold between two colliders, containing a set of contact points.
@@ -296,6 +520,14 @@ impl ContactManifold {
 }
 
 /// Data related to a contact between two bodies.
+///
+/// The contacts are computed by the narrow phase in every substep. Before they are solved,
+/// the [`friction`](Self::friction), [`restitution`](Self::restitution), [`compliance`](Self::compliance),
+/// [`tangent_velocity`](Self::tangent_velocity) and normals of individual contacts can be modified
+/// and contacts can be disabled in the [`PostProcessCollisions`] schedule.
+///
+/// Contacts can be created with [`ContactData::new`], which uses default values for everything
+/// except the contact points, normals and penetration depth.
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContactData {
@@ -309,9 +541,79 @@ pub struct ContactData {
     pub normal2: Vector,
     /// Penetration depth.
     pub penetration: Scalar,
+    /// The feature of the first shape that the contact is on, like a vertex, edge or face.
+    /// This is used for matching contacts across substeps.
+    pub feature_id1: PackedFeatureId,
+    /// The feature of the second shape that the contact is on, like a vertex, edge or face.
+    /// This is used for matching contacts across substeps.
+    pub feature_id2: PackedFeatureId,
+    /// The Lagrange multiplier of the normal constraint from the previous substep.
+    /// It is carried over to matching contacts and used for warm starting the solver.
+    pub normal_lagrange: Scalar,
+    /// The contact points on the first and second entity in local coordinates where static friction
+    /// is holding the entities in place. This is `None` if the contact is new or sliding.
+    pub friction_anchors: Option<(Vector, Vector)>,
+    /// The friction of the contact. If `None`, the [`Friction`] of the colliders
+    /// or the bodies they are attached to is used.
+    pub friction: Option<Friction>,
+    /// The restitution of the contact. If `None`, the [`Restitution`] of the colliders
+    /// or the bodies they are attached to is used.
+    pub restitution: Option<Restitution>,
+    /// The compliance of the contact, the inverse of its stiffness. Higher values make the contact softer.
+    /// `0.0` by default.
+    pub compliance: Scalar,
+    /// The target velocity of the first entity relative to the second entity along the contact surface
+    /// in world space, used for things like conveyor belts. Friction drives the relative tangential velocity
+    /// of the entities at the contact point towards this velocity. Zero by default.
+    ///
+    /// The [`SurfaceVelocity`] of the colliders is added to this velocity when the contact is solved.
+    pub tangent_velocity: Vector,
+    /// If `false`, the contact is ignored by the solver. `true` by default.
+    pub enabled: bool,
 }
 
 impl ContactData {
+    /// Creates a new [`ContactData`] from the given local contact points, local normals and penetration depth.
+    ///
+    /// The feature IDs are [unknown](PackedFeatureId::UNKNOWN), and the contact uses
+    /// the default friction, restitution, compliance and tangent velocity.
+    pub fn new(
+        point1: Vector,
+        point2: Vector,
+        normal1: Vector,
+        normal2: Vector,
+        penetration: Scalar,
+    ) -> Self {
+        Self {
+            point1,
+            point2,
+            normal1,
+            normal2,
+            penetration,
+            feature_id1: PackedFeatureId::UNKNOWN,
+            feature_id2: PackedFeatureId::UNKNOWN,
+            normal_lagrange: 0.0,
+            friction_anchors: None,
+            friction: None,
+            restitution: None,
+            compliance: 0.0,
+            tangent_velocity: Vector::ZERO,
+            enabled: true,
+        }
+    }
+
+    /// Sets the features of the first and second shape that the contact is on.
+    /// They are used for matching contacts across substeps.
+    pub fn with_feature_ids(
+        mut self,
+        feature_id1: PackedFeatureId,
+        feature_id2: PackedFeatureId,
+    ) -> Self {
+        self.feature_id1 = feature_id1;
+        self.feature_id2 = feature_id2;
+        self
+    }
+
     /// Returns the global contact point on the first entity,
     /// transforming the local point by the given entity position and rotation.
     pub fn global_point1(&self, position: &Position, rotation: &Rotation) -> Vector {
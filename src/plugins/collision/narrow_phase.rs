@@ -2,10 +2,15 @@
 //!
 //! See [`NarrowPhasePlugin`].
 
+use super::{broad_phase::BroadPhaseInactivity, collision_filter::CustomCollisionFilter};
 use crate::prelude::*;
-use bevy::ecs::query::Has;
+use bevy::ecs::{
+    query::Has,
+    system::{ReadOnlySystemParam, StaticSystemParam, SystemParamItem},
+};
 #[cfg(feature = "parallel")]
 use bevy::tasks::{ComputeTaskPool, ParallelSlice};
+use indexmap::IndexSet;
 
 /// Computes contacts between entities.
 ///
@@ -13,12 +18,18 @@ use bevy::tasks::{ComputeTaskPool, ParallelSlice};
 /// which is handled by the [`BroadPhasePlugin`].
 ///
 /// The results of the narrow phase are added into [`Collisions`].
+///
+/// Pairs involving a [`Sensor`] don't generate contacts. Instead, they are checked once per frame
+/// after the substeps and [swept CCD](SweptCcd) with a boolean [intersection test](contact_query::intersection_test),
+/// and the intersecting pairs are stored in [`SensorOverlaps`].
 pub struct NarrowPhasePlugin;
 
 impl Plugin for NarrowPhasePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<NarrowPhaseConfig>()
             .init_resource::<Collisions>()
+            .init_resource::<SensorOverlaps>()
+            .init_resource::<PhysicsQueryDispatcher>()
             .register_type::<NarrowPhaseConfig>();
 
         // Manage collision states like `during_current_frame` and remove old contacts
@@ -31,6 +42,10 @@ impl Plugin for NarrowPhasePlugin {
                     reset_collision_states
                         .after(PhysicsStepSet::BroadPhase)
                         .before(PhysicsStepSet::Substeps),
+                    // Check sensor overlaps at the final positions of the frame
+                    collect_sensor_overlaps
+                        .after(PhysicsStepSet::SweptCcd)
+                        .before(PhysicsStepSet::ReportContacts),
                     // Remove ended collisions after contact reporting
                     ((|mut collisions: ResMut<Collisions>| {
                         collisions.retain(|contacts| contacts.during_current_frame)
@@ -46,7 +61,11 @@ impl Plugin for NarrowPhasePlugin {
         app.get_schedule_mut(SubstepSchedule)
             .expect("add SubstepSchedule first")
             .add_systems(
-                (reset_substep_collision_states, collect_collisions)
+                (
+                    reset_substep_collision_states,
+                    collect_collisions::<()>
+                        .run_if(not(resource_exists::<CustomCollisionFilter>())),
+                )
                     .chain()
                     .in_set(SubstepSet::NarrowPhase),
             );
@@ -78,19 +97,31 @@ impl Default for NarrowPhaseConfig {
 }
 
 /// Computes contacts based on [`BroadCollisionPairs`] and adds them to [`Collisions`].
+/// Pairs involving a [`Sensor`] are skipped, as their intersections are stored in [`SensorOverlaps`] instead.
+///
+/// Contacts rejected by the [`CollisionFilter`] `F` are not added. The [`CollisionFilterPlugin`]
+/// replaces the default instance of this system, which uses a filter that accepts all contacts.
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
-pub fn collect_collisions(
+pub fn collect_collisions<F>(
+    filter: StaticSystemParam<F>,
     bodies: Query<(
         &Position,
         Option<&AccumulatedTranslation>,
         &Rotation,
         &Collider,
     )>,
+    sensors: Query<(), With<Sensor>>,
     broad_collision_pairs: Res<BroadCollisionPairs>,
     mut collisions: ResMut<Collisions>,
     narrow_phase_config: Res<NarrowPhaseConfig>,
-) {
+    dispatcher: Res<PhysicsQueryDispatcher>,
+) where
+    F: CollisionFilter + ReadOnlySystemParam + 'static,
+    for<'w, 's> SystemParamItem<'w, 's, F>: CollisionFilter,
+{
+    let filter = &*filter;
+
     #[cfg(feature = "parallel")]
     {
         let pool = ComputeTaskPool::get();
@@ -100,6 +131,9 @@ pub fn collect_collisions(
             .par_splat_map(pool, None, |chunks| {
                 let mut new_collisions: Vec<Contacts> = vec![];
                 for (entity1, entity2) in chunks {
+                    if sensors.contains(*entity1) || sensors.contains(*entity2) {
+                        continue;
+                    }
                     if let Ok([bundle1, bundle2]) = bodies.get_many([*entity1, *entity2]) {
                         let (position1, accumulated_translation1, rotation1, collider1) = bundle1;
                         let (position2, accumulated_translation2, rotation2, collider2) = bundle2;
@@ -111,6 +145,21 @@ pub fn collect_collisions(
 
                         let previous_contact = collisions.get_internal().get(&(*entity1, *entity2));
 
+                        let mut manifolds = contact_query::contact_manifolds_with_dispatcher(
+                            &**dispatcher,
+                            collider1,
+                            position1,
+                            *rotation1,
+                            collider2,
+                            position2,
+                            *rotation2,
+                            narrow_phase_config.prediction_distance,
+                        );
+
+                        if let Some(previous_contact) = previous_contact {
+                            match_contacts(&mut manifolds, &previous_contact.manifolds);
+                        }
+
                         let contacts = Contacts {
                             entity1: *entity1,
                             entity2: *entity2,
@@ -118,18 +167,14 @@ pub fn collect_collisions(
                             during_current_substep: true,
                             during_previous_frame: previous_contact
                                 .map_or(false, |c| c.during_previous_frame),
-                            manifolds: contact_query::contact_manifolds(
-                                collider1,
-                                position1,
-                                *rotation1,
-                                collider2,
-                                position2,
-                                *rotation2,
-                                narrow_phase_config.prediction_distance,
-                            ),
+                            total_normal_impulse: previous_contact
+                                .map_or(Vector::ZERO, |c| c.total_normal_impulse),
+                            total_friction_impulse: previous_contact
+                                .map_or(Vector::ZERO, |c| c.total_friction_impulse),
+                            manifolds,
                         };
 
-                        if !contacts.manifolds.is_empty() {
+                        if !contacts.manifolds.is_empty() && filter.filter_contacts(&contacts) {
                             new_collisions.push(contacts);
                         }
                     }
@@ -143,6 +188,9 @@ pub fn collect_collisions(
     #[cfg(not(feature = "parallel"))]
     {
         for (entity1, entity2) in broad_collision_pairs.0.iter() {
+            if sensors.contains(*entity1) || sensors.contains(*entity2) {
+                continue;
+            }
             if let Ok([bundle1, bundle2]) = bodies.get_many([*entity1, *entity2]) {
                 let (position1, accumulated_translation1, rotation1, collider1) = bundle1;
                 let (position2, accumulated_translation2, rotation2, collider2) = bundle2;
@@ -154,6 +202,21 @@ pub fn collect_collisions(
 
                 let previous_contact = collisions.get_internal().get(&(*entity1, *entity2));
 
+                let mut manifolds = contact_query::contact_manifolds_with_dispatcher(
+                    &**dispatcher,
+                    collider1,
+                    position1,
+                    *rotation1,
+                    collider2,
+                    position2,
+                    *rotation2,
+                    narrow_phase_config.prediction_distance,
+                );
+
+                if let Some(previous_contact) = previous_contact {
+                    match_contacts(&mut manifolds, &previous_contact.manifolds);
+                }
+
                 let contacts = Contacts {
                     entity1: *entity1,
                     entity2: *entity2,
@@ -161,18 +224,14 @@ pub fn collect_collisions(
                     during_current_substep: true,
                     during_previous_frame: previous_contact
                         .map_or(false, |c| c.during_previous_frame),
-                    manifolds: contact_query::contact_manifolds(
-                        collider1,
-                        position1,
-                        *rotation1,
-                        collider2,
-                        position2,
-                        *rotation2,
-                        narrow_phase_config.prediction_distance,
-                    ),
+                    total_normal_impulse: previous_contact
+                        .map_or(Vector::ZERO, |c| c.total_normal_impulse),
+                    total_friction_impulse: previous_contact
+                        .map_or(Vector::ZERO, |c| c.total_friction_impulse),
+                    manifolds,
                 };
 
-                if !contacts.manifolds.is_empty() {
+                if !contacts.manifolds.is_empty() && filter.filter_contacts(&contacts) {
                     collisions.insert_collision_pair(contacts);
                 }
             }
@@ -180,9 +239,104 @@ pub fn collect_collisions(
     }
 }
 
+/// Checks which [`BroadCollisionPairs`] involving a [`Sensor`] are intersecting and stores them in [`SensorOverlaps`].
+///
+/// The broad phase skips pairs where both colliders are inactive, so the overlaps of such pairs
+/// are kept from the previous frame.
+#[allow(clippy::type_complexity)]
+pub(crate) fn collect_sensor_overlaps(
+    colliders: Query<(
+        &Position,
+        Option<&AccumulatedTranslation>,
+        &Rotation,
+        &Collider,
+        Has<Sensor>,
+    )>,
+    broad_collision_pairs: Res<BroadCollisionPairs>,
+    broad_phase_inactivity: BroadPhaseInactivity,
+    mut sensor_overlaps: ResMut<SensorOverlaps>,
+    dispatcher: Res<PhysicsQueryDispatcher>,
+) {
+    let mut overlaps = IndexSet::<(Entity, Entity), fxhash::FxBuildHasher>::default();
+
+    for (entity1, entity2) in broad_collision_pairs.0.iter() {
+        let Ok([collider1, collider2]) = colliders.get_many([*entity1, *entity2]) else {
+            continue;
+        };
+        let (position1, accumulated_translation1, rotation1, collider1, is_sensor1) = collider1;
+        let (position2, accumulated_translation2, rotation2, collider2, is_sensor2) = collider2;
+
+        if !is_sensor1 && !is_sensor2 {
+            continue;
+        }
+
+        let position1 = position1.0 + accumulated_translation1.copied().unwrap_or_default().0;
+        let position2 = position2.0 + accumulated_translation2.copied().unwrap_or_default().0;
+
+        // Shapes that don't support intersection tests never overlap
+        let intersecting = contact_query::intersection_test_with_dispatcher(
+            &**dispatcher,
+            collider1,
+            position1,
+            *rotation1,
+            collider2,
+            position2,
+            *rotation2,
+        )
+        .unwrap_or(false);
+
+        if intersecting {
+            // The sensor is always the first entity
+            overlaps.insert(if is_sensor1 {
+                (*entity1, *entity2)
+            } else {
+                (*entity2, *entity1)
+            });
+        }
+    }
+
+    // Keep the overlaps of pairs that the broad phase skipped because both colliders are inactive
+    let inactive = broad_phase_inactivity.inactive_entities(
+        sensor_overlaps
+            .iter()
+            .flat_map(|(entity1, entity2)| [entity1, entity2]),
+    );
+    for (entity1, entity2) in sensor_overlaps.iter() {
+        if inactive.contains(&entity1) && inactive.contains(&entity2) {
+            overlaps.insert((entity1, entity2));
+        }
+    }
+
+    sensor_overlaps.set_overlaps(overlaps);
+}
+
+/// Carries over the Lagrange multipliers and friction anchors of contacts from the previous substep
+/// to new contacts that are on the same features of the shapes. This is used for warm starting the solver.
+///
+/// Contacts are only matched with contacts in the manifold with the same index.
+/// Contacts without known features are never matched.
+fn match_contacts(manifolds: &mut [ContactManifold], previous_manifolds: &[ContactManifold]) {
+    for (manifold, previous_manifold) in manifolds.iter_mut().zip(previous_manifolds) {
+        for contact in manifold.contacts.iter_mut() {
+            if contact.feature_id1.is_unknown() && contact.feature_id2.is_unknown() {
+                continue;
+            }
+
+            if let Some(previous_contact) = previous_manifold.contacts.iter().find(|previous| {
+                previous.feature_id1 == contact.feature_id1
+                    && previous.feature_id2 == contact.feature_id2
+            }) {
+                contact.normal_lagrange = previous_contact.normal_lagrange;
+                contact.friction_anchors = previous_contact.friction_anchors;
+            }
+        }
+    }
+}
+
 // TODO: The collision state handling feels a bit confusing and error-prone.
 //       Ideally, the narrow phase wouldn't need to handle it at all, or it would at least be simpler.
-/// Resets collision states like `during_current_frame` and `during_previous_frame`.
+/// Resets collision states like `during_current_frame` and `during_previous_frame`,
+/// and the total impulses of the contacts of active bodies.
 pub fn reset_collision_states(
     mut collisions: ResMut<Collisions>,
     query: Query<(Option<&RigidBody>, Has<Sleeping>)>,
@@ -200,6 +354,8 @@ pub fn reset_collision_states(
                 contacts.during_previous_frame = true;
                 contacts.during_current_frame = false;
                 contacts.during_current_substep = false;
+                contacts.total_normal_impulse = Vector::ZERO;
+                contacts.total_friction_impulse = Vector::ZERO;
             } else {
                 contacts.during_previous_frame = true;
                 contacts.during_current_frame = true;
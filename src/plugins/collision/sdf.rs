@@ -0,0 +1,922 @@
+//! Signed distance field colliders for sculpted and procedural shapes.
+//!
+//! See [`SignedDistanceField`] and [`Collider::sdf`].
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, OnceLock,
+};
+
+use super::voxels::translation;
+use crate::prelude::*;
+use parry::{
+    bounding_volume::{Aabb, BoundingSphere, BoundingVolume},
+    mass_properties::MassProperties,
+    math::{Isometry, Real, DIM},
+    query::{
+        ClosestPoints, Contact, ContactManifold, ContactManifoldsWorkspace, NonlinearRigidMotion,
+        PersistentQueryDispatcher, PointProjection, PointQuery, QueryDispatcher, Ray, RayCast,
+        RayIntersection, TOIStatus, TrackedContact, Unsupported, TOI,
+    },
+    shape::{FeatureId, PackedFeatureId, Shape, ShapeType, SupportMap, TypedShape},
+};
+
+#[cfg(feature = "2d")]
+type UVector = UVec2;
+#[cfg(feature = "3d")]
+type UVector = UVec3;
+
+/// The identifier returned by [`TypedShape::Custom`] for [`SignedDistanceField`] shapes.
+pub const SDF_SHAPE_ID: u32 = 2;
+
+/// The number of cells along each axis used for integrating the mass properties of fields defined by closures.
+#[cfg(feature = "2d")]
+const INTEGRATION_RESOLUTION: usize = 128;
+/// The number of cells along each axis used for integrating the mass properties of fields defined by closures.
+#[cfg(feature = "3d")]
+const INTEGRATION_RESOLUTION: usize = 32;
+
+/// The maximum number of steps used for ray marching and conservative advancement.
+const MAX_MARCHING_STEPS: usize = 256;
+
+/// The maximum number of iterations used for finding the deepest point of a shape in the field.
+const MAX_CONTACT_ITERATIONS: usize = 8;
+
+/// A shape defined by a signed distance field, which returns the distance from a point
+/// to the surface of the shape. The distance is negative inside of the shape.
+///
+/// Distance fields are typically used for sculpted or procedural terrain, since shapes can be combined
+/// with boolean operations on their distances, like `a.min(b)` for a union and `a.max(-b)` for subtracting `b` from `a`.
+/// The field is either sampled from a grid with [`from_grid`](Self::from_grid)
+/// or computed by a closure with [`from_fn`](Self::from_fn).
+///
+/// Signed distance field shapes are created with [`Collider::sdf`]. Their contacts against convex shapes
+/// and compounds of convex shapes are computed by the [`SdfQueryDispatcher`], which the [`PhysicsQueryDispatcher`]
+/// uses automatically. Raycasts march along the ray using the distances of the field.
+///
+/// The queries assume that the field doesn't overestimate the distance to the surface.
+/// Distance fields can't be nested in compound shapes or deserialized.
+#[derive(Clone)]
+pub struct SignedDistanceField {
+    source: FieldSource,
+    /// The minimum and maximum corners of the bounds of the field before scaling.
+    bounds: (Vector, Vector),
+    /// The scale applied to the local space of the field.
+    scale: Vector,
+    /// Values derived from the field that are expensive to compute, shared between clones of the shape.
+    cache: Arc<SdfCache>,
+}
+
+/// Lazily computed values of a [`SignedDistanceField`], which are reset when the field is scaled.
+#[derive(Default)]
+struct SdfCache {
+    /// The mass properties of the field with a density of `1.0`.
+    mass_properties: OnceLock<MassProperties>,
+    /// The line segments of the outline of the field in its local space, used for debug rendering.
+    #[cfg(feature = "debug-plugin")]
+    outline: OnceLock<Vec<[Vector; 2]>>,
+}
+
+#[derive(Clone)]
+enum FieldSource {
+    Grid {
+        origin: Vector,
+        cell_size: Scalar,
+        dimensions: UVector,
+        values: Arc<[Scalar]>,
+    },
+    Function(Arc<dyn Fn(Vector) -> Scalar + Send + Sync>),
+}
+
+impl std::fmt::Debug for SignedDistanceField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("SignedDistanceField");
+        if let FieldSource::Grid {
+            cell_size,
+            dimensions,
+            ..
+        } = &self.source
+        {
+            debug
+                .field("cell_size", cell_size)
+                .field("dimensions", dimensions);
+        }
+        debug
+            .field("bounds", &self.bounds())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SignedDistanceField {
+    /// Creates a new [`SignedDistanceField`] from a grid of distances sampled at the given `dimensions`
+    /// with a spacing of `cell_size`, starting at `origin`.
+    ///
+    /// The values are ordered by the X axis first, then by the Y axis and, in 3D, by the Z axis.
+    /// The distances between the samples are interpolated linearly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid doesn't have at least two samples along each axis, if the number of values
+    /// doesn't match the dimensions or if the cell size isn't positive.
+    pub fn from_grid(
+        origin: Vector,
+        cell_size: Scalar,
+        dimensions: UVector,
+        values: Vec<Scalar>,
+    ) -> Self {
+        assert!(
+            dimensions.cmpge(UVector::splat(2)).all(),
+            "The grid must have at least two samples along each axis"
+        );
+        assert_eq!(
+            values.len(),
+            dimensions.to_array().iter().product::<u32>() as usize,
+            "The number of values must match the dimensions of the grid"
+        );
+        assert!(cell_size > 0.0, "The cell size must be positive");
+
+        let cells = Vector::from((dimensions - UVector::ONE).to_array().map(|c| c as Scalar));
+        Self {
+            source: FieldSource::Grid {
+                origin,
+                cell_size,
+                dimensions,
+                values: values.into(),
+            },
+            bounds: (origin, origin + cells * cell_size),
+            scale: Vector::ONE,
+            cache: Arc::default(),
+        }
+    }
+
+    /// Creates a new [`SignedDistanceField`] that computes the distances using the given closure.
+    ///
+    /// The surface of the shape must be within the bounds given by `mins` and `maxs`,
+    /// which are used for the [`ColliderAabb`] and for integrating the mass properties.
+    pub fn from_fn(
+        mins: Vector,
+        maxs: Vector,
+        distance: impl Fn(Vector) -> Scalar + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            source: FieldSource::Function(Arc::new(distance)),
+            bounds: (mins.min(maxs), mins.max(maxs)),
+            scale: Vector::ONE,
+            cache: Arc::default(),
+        }
+    }
+
+    /// Returns the minimum and maximum corners of the bounds of the field.
+    pub fn bounds(&self) -> (Vector, Vector) {
+        (self.bounds.0 * self.scale, self.bounds.1 * self.scale)
+    }
+
+    /// Returns the signed distance from the given point to the surface of the shape.
+    /// The distance is negative inside of the shape.
+    ///
+    /// Points outside of the bounds of a grid use the distance of the closest sample on the bounds
+    /// plus the distance to the bounds.
+    pub fn distance(&self, point: Vector) -> Scalar {
+        let point = point / self.scale;
+        let distance = match &self.source {
+            FieldSource::Grid {
+                origin,
+                cell_size,
+                dimensions,
+                values,
+            } => sample_grid(*origin, *cell_size, *dimensions, values, point),
+            FieldSource::Function(distance) => distance(point),
+        };
+        distance * self.scale.min_element()
+    }
+
+    /// Returns the gradient of the field at the given point, computed using central differences.
+    pub fn gradient(&self, point: Vector) -> Vector {
+        let step = match &self.source {
+            FieldSource::Grid { cell_size, .. } => cell_size * 0.5,
+            FieldSource::Function(_) => (self.bounds.1 - self.bounds.0).max_element() * 0.001,
+        } * self.scale.min_element();
+        let mut gradient = Vector::ZERO;
+        for axis in 0..DIM {
+            let mut offset = Vector::ZERO;
+            offset[axis] = step;
+            gradient[axis] =
+                (self.distance(point + offset) - self.distance(point - offset)) / (2.0 * step);
+        }
+        gradient
+    }
+
+    /// Returns the outward normal of the surface at the given point, or zero if the gradient is zero.
+    pub fn normal(&self, point: Vector) -> Vector {
+        self.gradient(point).normalize_or_zero()
+    }
+
+    /// Returns a copy of the shape with the given scale.
+    ///
+    /// The distances are multiplied by the smallest component of the scale, so non-uniformly scaled
+    /// fields underestimate the distances. Negative scales don't mirror the field.
+    pub fn scaled(&self, scale: Vector) -> Self {
+        Self {
+            scale: self.scale * scale.abs(),
+            cache: Arc::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the size of the smallest features that the queries of the field can resolve.
+    fn resolution(&self) -> Scalar {
+        match &self.source {
+            FieldSource::Grid { cell_size, .. } => cell_size * self.scale.min_element(),
+            FieldSource::Function(_) => {
+                let (mins, maxs) = self.bounds();
+                (maxs - mins).max_element() / INTEGRATION_RESOLUTION as Scalar
+            }
+        }
+    }
+
+    /// Returns the distance below which points are considered to be on the surface.
+    fn tolerance(&self) -> Scalar {
+        self.resolution() * 0.001
+    }
+
+    /// Moves the given point onto the surface along the gradient of the field.
+    fn project_to_surface(&self, point: Vector) -> Vector {
+        let mut projected = point;
+        for _ in 0..MAX_CONTACT_ITERATIONS {
+            let distance = self.distance(projected);
+            if distance.abs() <= self.tolerance() {
+                break;
+            }
+            projected -= self.normal(projected) * distance;
+        }
+        projected
+    }
+
+    /// Integrates the mass properties over a grid of cells, treating each cell as a cuboid
+    /// that is filled based on the distance at its center.
+    fn integrate_mass_properties(&self, density: Real) -> MassProperties {
+        let counts = match &self.source {
+            FieldSource::Grid { dimensions, .. } => {
+                (*dimensions - UVector::ONE).to_array().map(|c| c as usize)
+            }
+            FieldSource::Function(_) => [INTEGRATION_RESOLUTION; DIM],
+        };
+        let (mins, maxs) = self.bounds();
+        let cell_size = (maxs - mins) / Vector::from(counts.map(|c| c as Scalar));
+        let thickness = cell_size.min_element();
+
+        cells(counts)
+            .filter_map(|cell| {
+                let center = mins + (Vector::from(cell.map(|c| c as Scalar)) + 0.5) * cell_size;
+                // Cells that the surface passes through are partially filled
+                let fill = (0.5 - self.distance(center) / thickness).clamp(0.0, 1.0);
+                (fill > 0.0).then(|| {
+                    MassProperties::from_cuboid(density * fill, (cell_size * 0.5).into())
+                        .transform_by(&translation(center))
+                })
+            })
+            .sum()
+    }
+
+    /// Returns the line segments of the outline of the field in its local space.
+    ///
+    /// In 3D, the outline consists of slices of the surface along each axis.
+    /// The outline is computed with marching squares the first time it's requested.
+    #[cfg(feature = "debug-plugin")]
+    pub(crate) fn outline(&self) -> &[[Vector; 2]] {
+        const RESOLUTION: usize = if cfg!(feature = "2d") { 64 } else { 24 };
+
+        self.cache.outline.get_or_init(|| {
+            let (mins, maxs) = self.bounds();
+            let step = (maxs - mins) / (RESOLUTION - 1) as Scalar;
+
+            #[cfg(feature = "2d")]
+            {
+                let to_local = |[x, y]: [Scalar; 2]| mins + Vector::new(x, y) * step;
+                crate::utils::marching_squares([RESOLUTION; 2], |x, y| {
+                    self.distance(to_local([x as Scalar, y as Scalar]))
+                })
+                .into_iter()
+                .map(|[a, b]| [to_local(a), to_local(b)])
+                .collect()
+            }
+
+            #[cfg(feature = "3d")]
+            {
+                const SLICES: usize = 8;
+                let mut outline = vec![];
+                for axis in 0..3 {
+                    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                    for slice in 0..SLICES {
+                        let to_local = |[x, y]: [Scalar; 2]| {
+                            let mut point = mins;
+                            point[axis] += (maxs[axis] - mins[axis]) * (slice as Scalar + 0.5)
+                                / SLICES as Scalar;
+                            point[u] += x * step[u];
+                            point[v] += y * step[v];
+                            point
+                        };
+                        let segments = crate::utils::marching_squares([RESOLUTION; 2], |x, y| {
+                            self.distance(to_local([x as Scalar, y as Scalar]))
+                        });
+                        outline.extend(
+                            segments
+                                .into_iter()
+                                .map(|[a, b]| [to_local(a), to_local(b)]),
+                        );
+                    }
+                }
+                outline
+            }
+        })
+    }
+}
+
+/// Interpolates the distance at the given point from a grid of samples.
+fn sample_grid(
+    origin: Vector,
+    cell_size: Scalar,
+    dimensions: UVector,
+    values: &[Scalar],
+    point: Vector,
+) -> Scalar {
+    let dimensions = dimensions.to_array().map(|d| d as usize);
+    let max = Vector::from(dimensions.map(|d| (d - 1) as Scalar));
+    let local = (point - origin) / cell_size;
+    let clamped = local.clamp(Vector::ZERO, max);
+    let outside = (local - clamped).length() * cell_size;
+    let cell = clamped.floor().min(max - 1.0);
+    let fraction = clamped - cell;
+
+    // Interpolate between the samples at the corners of the cell
+    let mut distance = 0.0;
+    for corner in 0..1 << DIM {
+        let mut weight = 1.0;
+        let mut index = 0;
+        let mut stride = 1;
+        for axis in 0..DIM {
+            let offset = (corner >> axis) & 1;
+            weight *= if offset == 1 {
+                fraction[axis]
+            } else {
+                1.0 - fraction[axis]
+            };
+            index += (cell[axis] as usize + offset) * stride;
+            stride *= dimensions[axis];
+        }
+        distance += weight * values[index];
+    }
+    distance + outside
+}
+
+/// Returns the indices of the cells of a grid with the given number of cells along each axis.
+fn cells(counts: [usize; DIM]) -> impl Iterator<Item = [usize; DIM]> {
+    (0..counts.iter().product()).map(move |mut index| {
+        let mut cell = [0; DIM];
+        for axis in 0..DIM {
+            cell[axis] = index % counts[axis];
+            index /= counts[axis];
+        }
+        cell
+    })
+}
+
+impl Shape for SignedDistanceField {
+    fn compute_local_aabb(&self) -> Aabb {
+        let (mins, maxs) = self.bounds();
+        Aabb::new(mins.into(), maxs.into())
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        self.compute_local_aabb().bounding_sphere()
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    /// Returns the mass properties of the field, which are integrated once and cached on the shape.
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        let mut mass_properties = *self
+            .cache
+            .mass_properties
+            .get_or_init(|| self.integrate_mass_properties(1.0));
+        mass_properties.set_mass(mass_properties.mass() * density, true);
+        mass_properties
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape<'_> {
+        TypedShape::Custom(SDF_SHAPE_ID)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        self.resolution()
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        std::f64::consts::FRAC_PI_2 as Real
+    }
+}
+
+impl RayCast for SignedDistanceField {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_toi: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        let (t_min, t_max) = self.compute_local_aabb().clip_ray_parameters(ray)?;
+        let t_max = t_max.min(max_toi);
+        if t_min > t_max {
+            return None;
+        }
+
+        let origin = Vector::from(ray.origin);
+        let dir = Vector::from(ray.dir);
+        let speed = dir.length();
+        let tolerance = self.tolerance();
+
+        // Rays that start inside of the shape march to the point where they exit it
+        let starts_inside = t_min <= 0.0 && self.distance(origin) < 0.0;
+        if starts_inside && solid {
+            return Some(RayIntersection::new(
+                0.0,
+                parry::math::Vector::zeros(),
+                FeatureId::Unknown,
+            ));
+        }
+        let sign = if starts_inside { -1.0 } else { 1.0 };
+
+        // March along the ray using the distance to the surface as the step size
+        let mut t = t_min.max(0.0);
+        for _ in 0..MAX_MARCHING_STEPS {
+            let point = origin + dir * t;
+            let distance = self.distance(point) * sign;
+            if distance <= tolerance {
+                let normal = self.normal(point);
+                return Some(RayIntersection::new(t, normal.into(), FeatureId::Unknown));
+            }
+            if speed == 0.0 {
+                return None;
+            }
+            t += distance.max(tolerance) / speed;
+            if t > t_max {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+impl PointQuery for SignedDistanceField {
+    fn project_local_point(&self, pt: &parry::math::Point<Real>, solid: bool) -> PointProjection {
+        let point = Vector::from(*pt);
+        let is_inside = self.distance(point) < 0.0;
+        if is_inside && solid {
+            return PointProjection::new(true, *pt);
+        }
+        PointProjection::new(is_inside, self.project_to_surface(point).into())
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &parry::math::Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        (self.project_local_point(pt, false), FeatureId::Unknown)
+    }
+}
+
+/// Returns the error for queries between a distance field and a shape that isn't supported,
+/// and logs a warning the first time it happens, since the shapes would silently pass through each other otherwise.
+fn unsupported(shape: &dyn Shape) -> Unsupported {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        warn!(
+            "Queries between signed distance fields and {:?} shapes are not supported. \
+             Only convex shapes and compounds of convex shapes collide with distance fields.",
+            shape.shape_type()
+        );
+    }
+    Unsupported
+}
+
+/// Returns the support point of a shape in the local space of the field.
+fn support_point(shape: &dyn SupportMap, pos12: &Isometry<Real>, dir: Vector) -> Vector {
+    shape.support_point(pos12, &dir.into()).into()
+}
+
+/// Finds the deepest point of a convex shape in the field and the normal of the field at that point,
+/// in the local space of the field.
+///
+/// The point is found by alternating between taking the support point of the shape opposite to the normal
+/// and computing the normal at the support point until the normal stops changing.
+fn deepest_point(
+    sdf: &SignedDistanceField,
+    pos12: &Isometry<Real>,
+    shape: &dyn SupportMap,
+) -> (Vector, Vector) {
+    let center = Vector::from(pos12.translation.vector);
+    let mut normal = sdf.normal(center);
+    if normal == Vector::ZERO {
+        normal = Vector::Y;
+    }
+    let mut point = support_point(shape, pos12, -normal);
+
+    for _ in 0..MAX_CONTACT_ITERATIONS {
+        let new_normal = sdf.normal(point);
+        if new_normal == Vector::ZERO || new_normal.dot(normal) >= 1.0 - Scalar::EPSILON {
+            break;
+        }
+        normal = new_normal;
+        point = support_point(shape, pos12, -normal);
+    }
+
+    (point, normal)
+}
+
+/// Returns the contact between the field and a shape at the given point of the shape
+/// in the local space of the field.
+fn contact_at(pos12: &Isometry<Real>, point: Vector, normal: Vector, distance: Scalar) -> Contact {
+    let normal1 = nalgebra::Unit::new_unchecked(normal.into());
+    Contact::new(
+        (point - normal * distance).into(),
+        pos12.inverse_transform_point(&point.into()),
+        normal1,
+        pos12.inverse_transform_unit_vector(&-normal1),
+        distance,
+    )
+}
+
+/// Returns the directions perpendicular to the normal that are used for tilting
+/// the support directions when computing contact manifolds.
+#[cfg(feature = "2d")]
+fn tangents(normal: Vector) -> Vec<Vector> {
+    vec![normal.perp(), -normal.perp()]
+}
+
+/// Returns the directions perpendicular to the normal that are used for tilting
+/// the support directions when computing contact manifolds.
+#[cfg(feature = "3d")]
+fn tangents(normal: Vector) -> Vec<Vector> {
+    let (tangent1, tangent2) = normal.any_orthonormal_pair();
+    (0..8)
+        .map(|i| {
+            let angle = i as Scalar * std::f64::consts::FRAC_PI_4 as Scalar;
+            tangent1 * angle.cos() + tangent2 * angle.sin()
+        })
+        .collect()
+}
+
+/// A query dispatcher that computes the contacts and intersections of [`SignedDistanceField`] shapes
+/// and forwards the queries between other shapes to the dispatcher `D`.
+///
+/// The deepest point of a convex shape in the field is found by iterating between the support point
+/// of the shape and the normal of the field. The contact manifolds also include the support points
+/// in directions tilted around the normal, each with the normal of the field at that point,
+/// so that shapes can rest on the field and on curved surfaces.
+///
+/// The [`PhysicsQueryDispatcher`] wraps its dispatcher in an `SdfQueryDispatcher` automatically.
+/// Queries between a distance field and non-convex shapes or other distance fields
+/// and nonlinear time of impact queries are not supported, except for contact manifolds
+/// against compounds of convex shapes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SdfQueryDispatcher<D>(pub D);
+
+impl<D: PersistentQueryDispatcher> SdfQueryDispatcher<D> {
+    /// Computes the contact of a distance field and a convex shape. The contact is in the local spaces of the shapes.
+    fn sdf_contact(
+        &self,
+        pos12: &Isometry<Real>,
+        sdf: &SignedDistanceField,
+        g2: &dyn Shape,
+        prediction: Real,
+    ) -> Result<Option<Contact>, Unsupported> {
+        let shape = g2.as_support_map().ok_or_else(|| unsupported(g2))?;
+        let aabb = g2.compute_aabb(pos12).loosened(prediction);
+        if !sdf.compute_local_aabb().intersects(&aabb) {
+            return Ok(None);
+        }
+
+        let (point, normal) = deepest_point(sdf, pos12, shape);
+        let distance = sdf.distance(point);
+        Ok((distance <= prediction).then(|| contact_at(pos12, point, normal, distance)))
+    }
+
+    /// Computes the contact manifolds of a distance field and a convex shape or a compound of convex shapes.
+    fn sdf_contact_manifolds(
+        &self,
+        pos12: &Isometry<Real>,
+        sdf: &SignedDistanceField,
+        g2: &dyn Shape,
+        prediction: Real,
+        manifolds: &mut Vec<ContactManifold<(), ()>>,
+    ) -> Result<(), Unsupported> {
+        if let Some(compound) = g2.as_compound() {
+            for (i, (part_pos, part)) in compound.shapes().iter().enumerate() {
+                let start = manifolds.len();
+                self.sdf_contact_manifolds(
+                    &(pos12 * part_pos),
+                    sdf,
+                    part.as_ref(),
+                    prediction,
+                    manifolds,
+                )?;
+                for manifold in &mut manifolds[start..] {
+                    manifold.subshape2 = i as u32;
+                    manifold.subshape_pos2 = Some(*part_pos);
+                }
+            }
+            return Ok(());
+        }
+
+        let shape = g2.as_support_map().ok_or_else(|| unsupported(g2))?;
+        let aabb = g2.compute_aabb(pos12).loosened(prediction);
+        if !sdf.compute_local_aabb().intersects(&aabb) {
+            return Ok(());
+        }
+
+        // The deepest point and the support points in directions tilted around the normal
+        let (deepest, normal) = deepest_point(sdf, pos12, shape);
+        let candidates = std::iter::once(deepest).chain(
+            tangents(normal)
+                .into_iter()
+                .map(|tangent| support_point(shape, pos12, tangent - normal)),
+        );
+
+        // Each point gets its own manifold, since the normal of the field can differ between the points
+        let min_separation = g2.compute_local_bounding_sphere().radius * 0.05;
+        let mut points: Vec<Vector> = vec![];
+        for point in candidates {
+            if points
+                .iter()
+                .any(|other| other.distance_squared(point) < min_separation.powi(2))
+            {
+                continue;
+            }
+            let distance = sdf.distance(point);
+            if distance > prediction {
+                continue;
+            }
+            points.push(point);
+
+            let point_normal = Some(sdf.normal(point))
+                .filter(|point_normal| *point_normal != Vector::ZERO)
+                .unwrap_or(normal);
+            let contact = contact_at(pos12, point, point_normal, distance);
+            let mut manifold = ContactManifold::new();
+            manifold.local_n1 = contact.normal1.into_inner();
+            manifold.local_n2 = contact.normal2.into_inner();
+            manifold.points.push(TrackedContact::new(
+                contact.point1,
+                contact.point2,
+                PackedFeatureId::UNKNOWN,
+                PackedFeatureId::UNKNOWN,
+                distance,
+            ));
+            manifolds.push(manifold);
+        }
+
+        Ok(())
+    }
+
+    /// Computes when a convex shape moving with the given velocity hits a distance field
+    /// using conservative advancement.
+    fn sdf_time_of_impact(
+        &self,
+        pos12: &Isometry<Real>,
+        local_vel12: &parry::math::Vector<Real>,
+        sdf: &SignedDistanceField,
+        g2: &dyn Shape,
+        max_toi: Real,
+        stop_at_penetration: bool,
+    ) -> Result<Option<TOI>, Unsupported> {
+        let shape = g2.as_support_map().ok_or_else(|| unsupported(g2))?;
+        let velocity = Vector::from(*local_vel12);
+        let speed = velocity.length();
+        let tolerance = sdf.tolerance();
+
+        let mut pos = *pos12;
+        let mut t = 0.0;
+        for _ in 0..MAX_MARCHING_STEPS {
+            pos.translation.vector = pos12.translation.vector + local_vel12 * t;
+            let (point, normal) = deepest_point(sdf, &pos, shape);
+            let distance = sdf.distance(point);
+
+            if distance <= tolerance {
+                let penetrating = t == 0.0 && distance < 0.0;
+                if penetrating && !stop_at_penetration && velocity.dot(normal) >= 0.0 {
+                    return Ok(None);
+                }
+                let contact = contact_at(&pos, point, normal, distance);
+                return Ok(Some(TOI {
+                    toi: t,
+                    witness1: contact.point1,
+                    witness2: contact.point2,
+                    normal1: contact.normal1,
+                    normal2: contact.normal2,
+                    status: if penetrating {
+                        TOIStatus::Penetrating
+                    } else {
+                        TOIStatus::Converged
+                    },
+                }));
+            }
+
+            // The shape can't reach the surface before moving at least the distance to it
+            if speed == 0.0 {
+                return Ok(None);
+            }
+            t += distance / speed;
+            if t > max_toi {
+                return Ok(None);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<D: PersistentQueryDispatcher> QueryDispatcher for SdfQueryDispatcher<D> {
+    fn intersection_test(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+    ) -> Result<bool, Unsupported> {
+        if let Some(sdf) = g1.as_shape::<SignedDistanceField>() {
+            Ok(self
+                .sdf_contact(pos12, sdf, g2, 0.0)?
+                .is_some_and(|contact| contact.dist <= 0.0))
+        } else if g2.as_shape::<SignedDistanceField>().is_some() {
+            self.intersection_test(&pos12.inverse(), g2, g1)
+        } else {
+            self.0.intersection_test(pos12, g1, g2)
+        }
+    }
+
+    fn distance(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+    ) -> Result<Real, Unsupported> {
+        if let Some(sdf) = g1.as_shape::<SignedDistanceField>() {
+            let shape = g2.as_support_map().ok_or_else(|| unsupported(g2))?;
+            let (point, _) = deepest_point(sdf, pos12, shape);
+            Ok(sdf.distance(point).max(0.0))
+        } else if g2.as_shape::<SignedDistanceField>().is_some() {
+            self.distance(&pos12.inverse(), g2, g1)
+        } else {
+            self.0.distance(pos12, g1, g2)
+        }
+    }
+
+    fn contact(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        prediction: Real,
+    ) -> Result<Option<Contact>, Unsupported> {
+        if let Some(sdf) = g1.as_shape::<SignedDistanceField>() {
+            self.sdf_contact(pos12, sdf, g2, prediction)
+        } else if let Some(sdf) = g2.as_shape::<SignedDistanceField>() {
+            Ok(self
+                .sdf_contact(&pos12.inverse(), sdf, g1, prediction)?
+                .map(Contact::flipped))
+        } else {
+            self.0.contact(pos12, g1, g2, prediction)
+        }
+    }
+
+    fn closest_points(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        max_dist: Real,
+    ) -> Result<ClosestPoints, Unsupported> {
+        if let Some(sdf) = g1.as_shape::<SignedDistanceField>() {
+            Ok(match self.sdf_contact(pos12, sdf, g2, max_dist)? {
+                Some(contact) if contact.dist <= 0.0 => ClosestPoints::Intersecting,
+                Some(contact) => ClosestPoints::WithinMargin(contact.point1, contact.point2),
+                None => ClosestPoints::Disjoint,
+            })
+        } else if g2.as_shape::<SignedDistanceField>().is_some() {
+            Ok(self
+                .closest_points(&pos12.inverse(), g2, g1, max_dist)?
+                .flipped())
+        } else {
+            self.0.closest_points(pos12, g1, g2, max_dist)
+        }
+    }
+
+    fn time_of_impact(
+        &self,
+        pos12: &Isometry<Real>,
+        local_vel12: &parry::math::Vector<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        max_toi: Real,
+        stop_at_penetration: bool,
+    ) -> Result<Option<TOI>, Unsupported> {
+        if let Some(sdf) = g1.as_shape::<SignedDistanceField>() {
+            self.sdf_time_of_impact(pos12, local_vel12, sdf, g2, max_toi, stop_at_penetration)
+        } else if g2.as_shape::<SignedDistanceField>().is_some() {
+            let local_vel21 = pos12.inverse_transform_vector(&-local_vel12);
+            Ok(self
+                .time_of_impact(
+                    &pos12.inverse(),
+                    &local_vel21,
+                    g2,
+                    g1,
+                    max_toi,
+                    stop_at_penetration,
+                )?
+                .map(TOI::swapped))
+        } else {
+            self.0
+                .time_of_impact(pos12, local_vel12, g1, g2, max_toi, stop_at_penetration)
+        }
+    }
+
+    fn nonlinear_time_of_impact(
+        &self,
+        motion1: &NonlinearRigidMotion,
+        g1: &dyn Shape,
+        motion2: &NonlinearRigidMotion,
+        g2: &dyn Shape,
+        start_time: Real,
+        end_time: Real,
+        stop_at_penetration: bool,
+    ) -> Result<Option<TOI>, Unsupported> {
+        if g1.as_shape::<SignedDistanceField>().is_some()
+            || g2.as_shape::<SignedDistanceField>().is_some()
+        {
+            return Err(Unsupported);
+        }
+        self.0.nonlinear_time_of_impact(
+            motion1,
+            g1,
+            motion2,
+            g2,
+            start_time,
+            end_time,
+            stop_at_penetration,
+        )
+    }
+}
+
+impl<D: PersistentQueryDispatcher> PersistentQueryDispatcher for SdfQueryDispatcher<D> {
+    fn contact_manifolds(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        prediction: Real,
+        manifolds: &mut Vec<ContactManifold<(), ()>>,
+        workspace: &mut Option<ContactManifoldsWorkspace>,
+    ) -> Result<(), Unsupported> {
+        if let Some(sdf) = g1.as_shape::<SignedDistanceField>() {
+            manifolds.clear();
+            self.sdf_contact_manifolds(pos12, sdf, g2, prediction, manifolds)
+        } else if let Some(sdf) = g2.as_shape::<SignedDistanceField>() {
+            manifolds.clear();
+            self.sdf_contact_manifolds(&pos12.inverse(), sdf, g1, prediction, manifolds)?;
+            for manifold in manifolds.iter_mut() {
+                std::mem::swap(&mut manifold.local_n1, &mut manifold.local_n2);
+                std::mem::swap(&mut manifold.subshape1, &mut manifold.subshape2);
+                std::mem::swap(&mut manifold.subshape_pos1, &mut manifold.subshape_pos2);
+                for contact in manifold.points.iter_mut() {
+                    std::mem::swap(&mut contact.local_p1, &mut contact.local_p2);
+                    std::mem::swap(&mut contact.fid1, &mut contact.fid2);
+                }
+            }
+            Ok(())
+        } else {
+            self.0
+                .contact_manifolds(pos12, g1, g2, prediction, manifolds, workspace)
+        }
+    }
+
+    fn contact_manifold_convex_convex(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        prediction: Real,
+        manifold: &mut ContactManifold<(), ()>,
+    ) -> Result<(), Unsupported> {
+        if g1.as_shape::<SignedDistanceField>().is_some()
+            || g2.as_shape::<SignedDistanceField>().is_some()
+        {
+            return Err(Unsupported);
+        }
+        self.0
+            .contact_manifold_convex_convex(pos12, g1, g2, prediction, manifold)
+    }
+}
@@ -0,0 +1,808 @@
+//! Voxel grid colliders that can be edited incrementally.
+//!
+//! See [`Voxels`] and [`Collider::voxels`].
+
+use std::collections::BTreeMap;
+
+use crate::prelude::*;
+use parry::{
+    bounding_volume::{Aabb, BoundingSphere, BoundingVolume},
+    mass_properties::MassProperties,
+    math::{Isometry, Real, DIM},
+    query::{
+        ClosestPoints, Contact, ContactManifold, ContactManifoldsWorkspace, NonlinearRigidMotion,
+        PersistentQueryDispatcher, PointProjection, PointQuery, QueryDispatcher, Ray, RayCast,
+        RayIntersection, Unsupported, TOI,
+    },
+    shape::{Cuboid, FeatureId, Shape, ShapeType, TypedShape},
+};
+
+#[cfg(feature = "2d")]
+type IVector = IVec2;
+#[cfg(feature = "3d")]
+type IVector = IVec3;
+
+/// The number of voxels along each axis of a chunk, chosen so that a chunk fits in a `u64`.
+#[cfg(feature = "2d")]
+const CHUNK_WIDTH: i32 = 8;
+/// The number of voxels along each axis of a chunk, chosen so that a chunk fits in a `u64`.
+#[cfg(feature = "3d")]
+const CHUNK_WIDTH: i32 = 4;
+
+/// The identifier returned by [`TypedShape::Custom`] for [`Voxels`] shapes.
+pub const VOXELS_SHAPE_ID: u32 = 1;
+
+/// A shape made of a grid of equally sized voxels that are either filled or empty.
+///
+/// Voxel shapes are typically used for block-based worlds. Unlike compound shapes or triangle meshes
+/// built from the voxels, they can be edited incrementally with [`set_voxel`](Self::set_voxel),
+/// and the contacts between neighboring voxels don't cause bumps when shapes slide over them.
+///
+/// The voxel at index `i` spans from `i * voxel_size` to `(i + 1) * voxel_size` in the local space of the shape.
+/// The filled voxels are stored sparsely in small chunks, so memory usage depends on the number of filled voxels
+/// rather than on the distance between them.
+///
+/// Voxel shapes are created with [`Collider::voxels`] and edited with [`Collider::set_voxel`].
+/// Their contacts are computed by the [`VoxelsQueryDispatcher`], which the [`PhysicsQueryDispatcher`]
+/// uses automatically. Voxel shapes can't be nested in compound shapes or deserialized.
+#[derive(Clone)]
+pub struct Voxels {
+    voxel_size: Vector,
+    /// The chunks that contain filled voxels, with one bit per voxel set for filled voxels.
+    chunks: BTreeMap<[i32; DIM], u64>,
+    /// The number of filled voxels.
+    len: usize,
+    /// The minimum and maximum indices of the filled voxels. Only valid if `len > 0`.
+    bounds: (IVector, IVector),
+}
+
+impl std::fmt::Debug for Voxels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Voxels")
+            .field("voxel_size", &self.voxel_size)
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Voxels {
+    /// Creates a new [`Voxels`] shape with the given size of each voxel and the indices of the filled voxels.
+    pub fn new(voxel_size: Vector, voxels: &[IVector]) -> Self {
+        let mut shape = Self {
+            voxel_size,
+            chunks: BTreeMap::new(),
+            len: 0,
+            bounds: (IVector::ZERO, IVector::ZERO),
+        };
+        for voxel in voxels {
+            shape.set_voxel(*voxel, true);
+        }
+        shape
+    }
+
+    /// Returns the size of each voxel.
+    pub fn voxel_size(&self) -> Vector {
+        self.voxel_size
+    }
+
+    /// Returns the number of filled voxels.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no voxels are filled.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the voxel at the given index is filled.
+    pub fn contains(&self, voxel: IVector) -> bool {
+        let (chunk, bit) = chunk_and_bit(voxel);
+        self.chunks
+            .get(&chunk)
+            .is_some_and(|bits| bits & (1 << bit) != 0)
+    }
+
+    /// Fills or clears the voxel at the given index. Returns `true` if the voxel was changed.
+    ///
+    /// Only the bit of the voxel is updated. Chunks are added when their first voxel is filled
+    /// and removed when their last voxel is cleared.
+    pub fn set_voxel(&mut self, voxel: IVector, filled: bool) -> bool {
+        if filled == self.contains(voxel) {
+            return false;
+        }
+
+        let (chunk, bit) = chunk_and_bit(voxel);
+        if filled {
+            *self.chunks.entry(chunk).or_default() |= 1 << bit;
+            self.bounds = if self.len == 0 {
+                (voxel, voxel)
+            } else {
+                (self.bounds.0.min(voxel), self.bounds.1.max(voxel))
+            };
+            self.len += 1;
+        } else {
+            if let Some(bits) = self.chunks.get_mut(&chunk) {
+                *bits &= !(1 << bit);
+                if *bits == 0 {
+                    self.chunks.remove(&chunk);
+                }
+            }
+            self.len -= 1;
+
+            // The bounds only change if the voxel was on their boundary
+            if voxel.cmpeq(self.bounds.0).any() || voxel.cmpeq(self.bounds.1).any() {
+                self.bounds = self.iter().fold(
+                    (IVector::splat(i32::MAX), IVector::splat(i32::MIN)),
+                    |(min, max), voxel| (min.min(voxel), max.max(voxel)),
+                );
+            }
+        }
+
+        true
+    }
+
+    /// Returns an iterator over the indices of the filled voxels.
+    pub fn iter(&self) -> impl Iterator<Item = IVector> + '_ {
+        self.chunks.iter().flat_map(|(&chunk, &bits)| {
+            let mut bits = bits;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let bit = bits.trailing_zeros();
+                bits &= bits - 1;
+                Some(voxel_from_chunk_and_bit(chunk, bit))
+            })
+        })
+    }
+
+    /// Returns the index of the voxel that contains the given point in the local space of the shape.
+    pub fn voxel_at_point(&self, point: Vector) -> IVector {
+        IVector::from(
+            (point / self.voxel_size)
+                .floor()
+                .to_array()
+                .map(|c| c as i32),
+        )
+    }
+
+    /// Returns the center of the voxel at the given index in the local space of the shape.
+    pub fn voxel_center(&self, voxel: IVector) -> Vector {
+        (to_vector(voxel) + 0.5) * self.voxel_size
+    }
+
+    /// Returns `true` if the voxel at the given index is filled and at least one of its neighbors is empty.
+    pub fn is_exposed(&self, voxel: IVector) -> bool {
+        self.contains(voxel) && neighbors(voxel).any(|neighbor| !self.contains(neighbor))
+    }
+
+    /// Returns a copy of the shape with the voxel size multiplied by the given scale.
+    ///
+    /// Negative scales don't mirror the voxels.
+    pub fn scaled(&self, scale: Vector) -> Self {
+        Self {
+            voxel_size: self.voxel_size * scale.abs(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the shape of a single voxel centered at the origin.
+    pub(crate) fn voxel_cuboid(&self) -> Cuboid {
+        Cuboid::new((self.voxel_size * 0.5).into())
+    }
+
+    /// Returns the filled voxels whose bounds intersect the given AABB in the local space of the shape.
+    pub(crate) fn voxels_in_aabb(&self, aabb: &Aabb) -> impl Iterator<Item = IVector> + '_ {
+        let (min, max) = if self.is_empty() {
+            (IVector::ONE, IVector::ZERO)
+        } else {
+            (
+                self.voxel_at_point(aabb.mins.into()).max(self.bounds.0),
+                self.voxel_at_point(aabb.maxs.into()).min(self.bounds.1),
+            )
+        };
+        voxels_in_range(min, max).filter(|voxel| self.contains(*voxel))
+    }
+
+    /// Removes the components of a contact normal that point into filled neighbors of the voxel.
+    ///
+    /// Contacts against the faces shared by two filled voxels are internal to the shape,
+    /// so removing them prevents bumps when shapes slide from one voxel to another.
+    /// Returns `None` if the whole normal points into neighbors.
+    pub(crate) fn corrected_normal(&self, voxel: IVector, normal: Vector) -> Option<Vector> {
+        let mut corrected = normal;
+        for axis in 0..DIM {
+            if normal[axis].abs() > Scalar::EPSILON {
+                let mut neighbor = voxel;
+                neighbor[axis] = neighbor[axis].saturating_add(normal[axis].signum() as i32);
+                if self.contains(neighbor) {
+                    corrected[axis] = 0.0;
+                }
+            }
+        }
+        corrected.try_normalize()
+    }
+
+    /// Returns an identifier for the voxel at the given index that doesn't change when other voxels are edited.
+    ///
+    /// The identifier is made from the wrapped coordinates of the voxel, so it is unique
+    /// within 65536 voxels along each axis in 2D, and within 2048 × 2048 × 1024 voxels in 3D.
+    fn subshape_id(voxel: IVector) -> u32 {
+        #[cfg(feature = "2d")]
+        {
+            voxel.x as u16 as u32 | ((voxel.y as u16 as u32) << 16)
+        }
+        #[cfg(feature = "3d")]
+        {
+            (voxel.x as u32 & 0x7ff)
+                | ((voxel.y as u32 & 0x7ff) << 11)
+                | ((voxel.z as u32 & 0x3ff) << 22)
+        }
+    }
+
+    /// Returns the bounds of the voxel at the given index in the local space of the shape.
+    fn voxel_aabb(&self, voxel: IVector) -> Aabb {
+        let mins = to_vector(voxel) * self.voxel_size;
+        Aabb::new(mins.into(), (mins + self.voxel_size).into())
+    }
+}
+
+fn to_vector(voxel: IVector) -> Vector {
+    Vector::from(voxel.to_array().map(|c| c as Scalar))
+}
+
+/// Returns the chunk that contains the given voxel and the bit of the voxel in the chunk.
+fn chunk_and_bit(voxel: IVector) -> ([i32; DIM], u32) {
+    let voxel = voxel.to_array();
+    let chunk = voxel.map(|c| c.div_euclid(CHUNK_WIDTH));
+    let bit = (0..DIM).rev().fold(0, |bit, axis| {
+        bit * CHUNK_WIDTH as u32 + voxel[axis].rem_euclid(CHUNK_WIDTH) as u32
+    });
+    (chunk, bit)
+}
+
+/// Returns the voxel at the given bit of the given chunk.
+fn voxel_from_chunk_and_bit(chunk: [i32; DIM], mut bit: u32) -> IVector {
+    let mut voxel = [0; DIM];
+    for axis in 0..DIM {
+        voxel[axis] = chunk[axis] * CHUNK_WIDTH + (bit % CHUNK_WIDTH as u32) as i32;
+        bit /= CHUNK_WIDTH as u32;
+    }
+    IVector::from(voxel)
+}
+
+pub(super) fn translation(translation: Vector) -> Isometry<Real> {
+    let mut isometry = Isometry::identity();
+    isometry.translation.vector = translation.into();
+    isometry
+}
+
+/// Returns the isometry of the second shape relative to the given voxel.
+fn isometry_to_voxel(voxels: &Voxels, voxel: IVector, pos12: &Isometry<Real>) -> Isometry<Real> {
+    let mut pos = *pos12;
+    pos.translation.vector -= parry::math::Vector::from(voxels.voxel_center(voxel));
+    pos
+}
+
+/// Returns the neighbors of a voxel that share a face with it.
+fn neighbors(voxel: IVector) -> impl Iterator<Item = IVector> {
+    (0..DIM).flat_map(move |axis| {
+        [-1, 1].into_iter().map(move |sign| {
+            let mut neighbor = voxel;
+            neighbor[axis] = neighbor[axis].saturating_add(sign);
+            neighbor
+        })
+    })
+}
+
+/// Returns the indices from `min` to `max`, inclusive.
+#[cfg(feature = "2d")]
+fn voxels_in_range(min: IVector, max: IVector) -> impl Iterator<Item = IVector> {
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVector::new(x, y)))
+}
+
+/// Returns the indices from `min` to `max`, inclusive.
+#[cfg(feature = "3d")]
+fn voxels_in_range(min: IVector, max: IVector) -> impl Iterator<Item = IVector> {
+    (min.z..=max.z).flat_map(move |z| {
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVector::new(x, y, z)))
+    })
+}
+
+impl Shape for Voxels {
+    fn compute_local_aabb(&self) -> Aabb {
+        if self.is_empty() {
+            return Aabb::new(Vector::ZERO.into(), Vector::ZERO.into());
+        }
+        let mins = to_vector(self.bounds.0) * self.voxel_size;
+        let maxs = (to_vector(self.bounds.1) + 1.0) * self.voxel_size;
+        Aabb::new(mins.into(), maxs.into())
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        self.compute_local_aabb().bounding_sphere()
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        let voxel = MassProperties::from_cuboid(density, self.voxel_cuboid().half_extents);
+        self.iter()
+            .map(|index| voxel.transform_by(&translation(self.voxel_center(index))))
+            .sum()
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape<'_> {
+        TypedShape::Custom(VOXELS_SHAPE_ID)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        self.voxel_size.min_element() * 0.5
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        std::f64::consts::FRAC_PI_2 as Real
+    }
+}
+
+impl RayCast for Voxels {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_toi: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        let (t_min, t_max) = self.compute_local_aabb().clip_ray_parameters(ray)?;
+        let t_max = t_max.min(max_toi);
+        if t_min > t_max {
+            return None;
+        }
+
+        // Traverse the voxels along the ray using a DDA algorithm
+        let origin = Vector::from(ray.origin);
+        let dir = Vector::from(ray.dir);
+        let mut voxel = self
+            .voxel_at_point(origin + dir * t_min)
+            .clamp(self.bounds.0, self.bounds.1);
+        let mut step = IVector::ZERO;
+        let mut next_t = Vector::splat(Scalar::INFINITY);
+        let mut delta_t = Vector::splat(Scalar::INFINITY);
+        for axis in 0..DIM {
+            if dir[axis] != 0.0 {
+                step[axis] = dir[axis].signum() as i32;
+                let boundary = voxel[axis] as Scalar + (step[axis] > 0) as i32 as Scalar;
+                next_t[axis] = (boundary * self.voxel_size[axis] - origin[axis]) / dir[axis];
+                delta_t[axis] = self.voxel_size[axis] / dir[axis].abs();
+            }
+        }
+
+        let starts_inside = t_min <= 0.0 && self.contains(voxel);
+        loop {
+            if starts_inside && !solid {
+                // Find the point where the ray exits the filled voxels
+                if !self.contains(voxel) {
+                    return None;
+                }
+            } else if self.contains(voxel) {
+                return self
+                    .voxel_aabb(voxel)
+                    .cast_local_ray_and_get_normal(ray, max_toi, true)
+                    .map(|hit| RayIntersection::new(hit.toi, hit.normal, FeatureId::Unknown));
+            }
+
+            let axis = (0..DIM)
+                .min_by(|a, b| next_t[*a].total_cmp(&next_t[*b]))
+                .unwrap_or_default();
+            let t = next_t[axis];
+            let previous = voxel;
+            voxel[axis] = voxel[axis].saturating_add(step[axis]);
+            next_t[axis] += delta_t[axis];
+
+            if starts_inside && !solid && !self.contains(voxel) {
+                return (t <= max_toi).then(|| {
+                    self.voxel_aabb(previous)
+                        .cast_local_ray_and_get_normal(ray, max_toi, false)
+                        .map_or_else(
+                            || {
+                                let mut normal = Vector::ZERO;
+                                normal[axis] = step[axis] as Scalar;
+                                RayIntersection::new(t, normal.into(), FeatureId::Unknown)
+                            },
+                            |hit| RayIntersection::new(hit.toi, hit.normal, FeatureId::Unknown),
+                        )
+                });
+            }
+            if t > t_max {
+                return None;
+            }
+        }
+    }
+}
+
+impl PointQuery for Voxels {
+    fn project_local_point(&self, pt: &parry::math::Point<Real>, solid: bool) -> PointProjection {
+        let point = Vector::from(*pt);
+        let is_inside = self.contains(self.voxel_at_point(point));
+        if is_inside && solid {
+            return PointProjection::new(true, *pt);
+        }
+
+        // The closest point on the boundary is either on an exposed voxel if the point is outside,
+        // or on an empty neighbor of an exposed voxel if the point is inside.
+        let exposed = self.iter().filter(|voxel| self.is_exposed(*voxel));
+        let candidates: Box<dyn Iterator<Item = IVector>> =
+            if is_inside {
+                Box::new(exposed.flat_map(|voxel| {
+                    neighbors(voxel).filter(|neighbor| !self.contains(*neighbor))
+                }))
+            } else {
+                Box::new(exposed)
+            };
+        let projected = candidates
+            .map(|voxel| {
+                let aabb = self.voxel_aabb(voxel);
+                point.clamp(aabb.mins.into(), aabb.maxs.into())
+            })
+            .min_by(|a, b| {
+                a.distance_squared(point)
+                    .total_cmp(&b.distance_squared(point))
+            })
+            .unwrap_or(point);
+
+        PointProjection::new(is_inside, projected.into())
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &parry::math::Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        (self.project_local_point(pt, false), FeatureId::Unknown)
+    }
+}
+
+/// A query dispatcher that computes the contacts and intersections of [`Voxels`] shapes
+/// and forwards the queries between other shapes to the dispatcher `D`.
+///
+/// Queries against a voxel shape are split into queries against the cuboids of the filled voxels
+/// near the other shape. Contact normals that point into filled neighbors of a voxel are corrected
+/// to avoid bumps at the internal edges between voxels.
+///
+/// The [`PhysicsQueryDispatcher`] wraps its dispatcher in a `VoxelsQueryDispatcher` automatically.
+/// Nonlinear time of impact queries against voxels are not supported.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VoxelsQueryDispatcher<D>(pub D);
+
+impl<D: PersistentQueryDispatcher> VoxelsQueryDispatcher<D> {
+    /// Computes the contact of a voxel shape and another shape. The contact is in the local spaces of the shapes.
+    fn voxels_contact(
+        &self,
+        pos12: &Isometry<Real>,
+        voxels: &Voxels,
+        g2: &dyn Shape,
+        prediction: Real,
+    ) -> Result<Option<Contact>, Unsupported> {
+        let cuboid = voxels.voxel_cuboid();
+        let aabb = g2.compute_aabb(pos12).loosened(prediction);
+        let mut deepest: Option<Contact> = None;
+
+        for voxel in voxels.voxels_in_aabb(&aabb) {
+            let pos = isometry_to_voxel(voxels, voxel, pos12);
+            let Some(mut contact) = self.contact(&pos, &cuboid, g2, prediction)? else {
+                continue;
+            };
+            contact.transform1_by_mut(&translation(voxels.voxel_center(voxel)));
+
+            let normal = Vector::from(contact.normal1.into_inner());
+            let Some(corrected) = voxels.corrected_normal(voxel, normal) else {
+                continue;
+            };
+            if corrected != normal {
+                let point2 = Vector::from(pos12 * contact.point2);
+                contact.dist = (point2 - Vector::from(contact.point1)).dot(corrected);
+                contact.point1 = (point2 - corrected * contact.dist).into();
+                contact.normal1 = nalgebra::Unit::new_unchecked(corrected.into());
+                contact.normal2 = pos12.inverse_transform_unit_vector(&-contact.normal1);
+            }
+
+            if deepest.map_or(true, |deepest| contact.dist < deepest.dist) {
+                deepest = Some(contact);
+            }
+        }
+
+        Ok(deepest)
+    }
+
+    /// Computes the contact manifolds of a voxel shape and another shape.
+    fn voxels_contact_manifolds(
+        &self,
+        pos12: &Isometry<Real>,
+        voxels: &Voxels,
+        g2: &dyn Shape,
+        prediction: Real,
+        manifolds: &mut Vec<ContactManifold<(), ()>>,
+    ) -> Result<(), Unsupported> {
+        let cuboid = voxels.voxel_cuboid();
+        let aabb = g2.compute_aabb(pos12).loosened(prediction);
+        let mut voxel_manifolds = vec![];
+
+        for voxel in voxels.voxels_in_aabb(&aabb) {
+            let pos = isometry_to_voxel(voxels, voxel, pos12);
+            voxel_manifolds.clear();
+            self.contact_manifolds(
+                &pos,
+                &cuboid,
+                g2,
+                prediction,
+                &mut voxel_manifolds,
+                &mut None,
+            )?;
+
+            for mut manifold in voxel_manifolds.drain(..) {
+                // The manifold is in the local space of the voxel
+                let normal = Vector::from(manifold.local_n1);
+                let Some(corrected) = voxels.corrected_normal(voxel, normal) else {
+                    continue;
+                };
+                if corrected != normal {
+                    let pos2 = manifold
+                        .subshape_pos2
+                        .map_or(pos, |subshape| pos * subshape);
+                    manifold.local_n1 = corrected.into();
+                    manifold.local_n2 = pos2.inverse_transform_vector(&-manifold.local_n1);
+                    for contact in manifold.points.iter_mut() {
+                        let point2 = Vector::from(pos2 * contact.local_p2);
+                        contact.dist = (point2 - Vector::from(contact.local_p1)).dot(corrected);
+                        contact.local_p1 = (point2 - corrected * contact.dist).into();
+                    }
+                }
+                manifold.subshape1 = Voxels::subshape_id(voxel);
+                manifold.subshape_pos1 = Some(translation(voxels.voxel_center(voxel)));
+                manifolds.push(manifold);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<D: PersistentQueryDispatcher> QueryDispatcher for VoxelsQueryDispatcher<D> {
+    fn intersection_test(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+    ) -> Result<bool, Unsupported> {
+        if let Some(voxels) = g1.as_shape::<Voxels>() {
+            let cuboid = voxels.voxel_cuboid();
+            for voxel in voxels.voxels_in_aabb(&g2.compute_aabb(pos12)) {
+                let pos = isometry_to_voxel(voxels, voxel, pos12);
+                if self.intersection_test(&pos, &cuboid, g2)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        } else if g2.as_shape::<Voxels>().is_some() {
+            self.intersection_test(&pos12.inverse(), g2, g1)
+        } else {
+            self.0.intersection_test(pos12, g1, g2)
+        }
+    }
+
+    fn distance(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+    ) -> Result<Real, Unsupported> {
+        if let Some(voxels) = g1.as_shape::<Voxels>() {
+            let cuboid = voxels.voxel_cuboid();
+            let mut min_distance = Real::MAX;
+            for voxel in voxels.iter().filter(|voxel| voxels.is_exposed(*voxel)) {
+                let pos = isometry_to_voxel(voxels, voxel, pos12);
+                min_distance = min_distance.min(self.distance(&pos, &cuboid, g2)?);
+                if min_distance <= 0.0 {
+                    break;
+                }
+            }
+            Ok(min_distance)
+        } else if g2.as_shape::<Voxels>().is_some() {
+            self.distance(&pos12.inverse(), g2, g1)
+        } else {
+            self.0.distance(pos12, g1, g2)
+        }
+    }
+
+    fn contact(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        prediction: Real,
+    ) -> Result<Option<Contact>, Unsupported> {
+        if let Some(voxels) = g1.as_shape::<Voxels>() {
+            self.voxels_contact(pos12, voxels, g2, prediction)
+        } else if let Some(voxels) = g2.as_shape::<Voxels>() {
+            Ok(self
+                .voxels_contact(&pos12.inverse(), voxels, g1, prediction)?
+                .map(Contact::flipped))
+        } else {
+            self.0.contact(pos12, g1, g2, prediction)
+        }
+    }
+
+    fn closest_points(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        max_dist: Real,
+    ) -> Result<ClosestPoints, Unsupported> {
+        if let Some(voxels) = g1.as_shape::<Voxels>() {
+            let cuboid = voxels.voxel_cuboid();
+            let aabb = g2.compute_aabb(pos12).loosened(max_dist);
+            let mut closest = ClosestPoints::Disjoint;
+            let mut min_distance = Real::MAX;
+
+            for voxel in voxels.voxels_in_aabb(&aabb) {
+                let pos = isometry_to_voxel(voxels, voxel, pos12);
+                match self.closest_points(&pos, &cuboid, g2, max_dist)? {
+                    ClosestPoints::Intersecting => return Ok(ClosestPoints::Intersecting),
+                    ClosestPoints::WithinMargin(point1, point2) => {
+                        let distance = (pos * point2 - point1).norm();
+                        if distance < min_distance {
+                            min_distance = distance;
+                            closest = ClosestPoints::WithinMargin(point1, point2).transform_by(
+                                &translation(voxels.voxel_center(voxel)),
+                                &Isometry::identity(),
+                            );
+                        }
+                    }
+                    ClosestPoints::Disjoint => (),
+                }
+            }
+
+            Ok(closest)
+        } else if g2.as_shape::<Voxels>().is_some() {
+            Ok(self
+                .closest_points(&pos12.inverse(), g2, g1, max_dist)?
+                .flipped())
+        } else {
+            self.0.closest_points(pos12, g1, g2, max_dist)
+        }
+    }
+
+    fn time_of_impact(
+        &self,
+        pos12: &Isometry<Real>,
+        local_vel12: &parry::math::Vector<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        max_toi: Real,
+        stop_at_penetration: bool,
+    ) -> Result<Option<TOI>, Unsupported> {
+        if let Some(voxels) = g1.as_shape::<Voxels>() {
+            let cuboid = voxels.voxel_cuboid();
+            let start = g2.compute_aabb(pos12);
+            let offset = local_vel12 * max_toi;
+            let swept = if offset.iter().all(|c| c.is_finite()) {
+                start.merged(&Aabb::new(start.mins + offset, start.maxs + offset))
+            } else {
+                voxels.compute_local_aabb()
+            };
+            let mut first: Option<TOI> = None;
+
+            for voxel in voxels.voxels_in_aabb(&swept) {
+                let pos = isometry_to_voxel(voxels, voxel, pos12);
+                let toi = self.time_of_impact(
+                    &pos,
+                    local_vel12,
+                    &cuboid,
+                    g2,
+                    max_toi,
+                    stop_at_penetration,
+                )?;
+                if let Some(toi) = toi {
+                    if first.map_or(true, |first| toi.toi < first.toi) {
+                        first = Some(toi.transform1_by(&translation(voxels.voxel_center(voxel))));
+                    }
+                }
+            }
+
+            Ok(first)
+        } else if g2.as_shape::<Voxels>().is_some() {
+            let local_vel21 = pos12.inverse_transform_vector(&-local_vel12);
+            Ok(self
+                .time_of_impact(
+                    &pos12.inverse(),
+                    &local_vel21,
+                    g2,
+                    g1,
+                    max_toi,
+                    stop_at_penetration,
+                )?
+                .map(TOI::swapped))
+        } else {
+            self.0
+                .time_of_impact(pos12, local_vel12, g1, g2, max_toi, stop_at_penetration)
+        }
+    }
+
+    fn nonlinear_time_of_impact(
+        &self,
+        motion1: &NonlinearRigidMotion,
+        g1: &dyn Shape,
+        motion2: &NonlinearRigidMotion,
+        g2: &dyn Shape,
+        start_time: Real,
+        end_time: Real,
+        stop_at_penetration: bool,
+    ) -> Result<Option<TOI>, Unsupported> {
+        if g1.as_shape::<Voxels>().is_some() || g2.as_shape::<Voxels>().is_some() {
+            return Err(Unsupported);
+        }
+        self.0.nonlinear_time_of_impact(
+            motion1,
+            g1,
+            motion2,
+            g2,
+            start_time,
+            end_time,
+            stop_at_penetration,
+        )
+    }
+}
+
+impl<D: PersistentQueryDispatcher> PersistentQueryDispatcher for VoxelsQueryDispatcher<D> {
+    fn contact_manifolds(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        prediction: Real,
+        manifolds: &mut Vec<ContactManifold<(), ()>>,
+        workspace: &mut Option<ContactManifoldsWorkspace>,
+    ) -> Result<(), Unsupported> {
+        if let Some(voxels) = g1.as_shape::<Voxels>() {
+            manifolds.clear();
+            self.voxels_contact_manifolds(pos12, voxels, g2, prediction, manifolds)
+        } else if let Some(voxels) = g2.as_shape::<Voxels>() {
+            manifolds.clear();
+            self.voxels_contact_manifolds(&pos12.inverse(), voxels, g1, prediction, manifolds)?;
+            for manifold in manifolds.iter_mut() {
+                std::mem::swap(&mut manifold.local_n1, &mut manifold.local_n2);
+                std::mem::swap(&mut manifold.subshape1, &mut manifold.subshape2);
+                std::mem::swap(&mut manifold.subshape_pos1, &mut manifold.subshape_pos2);
+                for contact in manifold.points.iter_mut() {
+                    std::mem::swap(&mut contact.local_p1, &mut contact.local_p2);
+                    std::mem::swap(&mut contact.fid1, &mut contact.fid2);
+                }
+            }
+            Ok(())
+        } else {
+            self.0
+                .contact_manifolds(pos12, g1, g2, prediction, manifolds, workspace)
+        }
+    }
+
+    fn contact_manifold_convex_convex(
+        &self,
+        pos12: &Isometry<Real>,
+        g1: &dyn Shape,
+        g2: &dyn Shape,
+        prediction: Real,
+        manifold: &mut ContactManifold<(), ()>,
+    ) -> Result<(), Unsupported> {
+        if g1.as_shape::<Voxels>().is_some() || g2.as_shape::<Voxels>().is_some() {
+            return Err(Unsupported);
+        }
+        self.0
+            .contact_manifold_convex_convex(pos12, g1, g2, prediction, manifold)
+    }
+}
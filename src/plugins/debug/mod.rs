@@ -278,14 +278,14 @@ fn debug_render_colliders(
 
 fn debug_render_contacts(
     colliders: Query<(&Position, &Rotation), With<Collider>>,
-    mut collisions: EventReader<Collision>,
+    collisions: Res<Collisions>,
     mut debug_renderer: PhysicsDebugRenderer,
     config: Res<PhysicsDebugConfig>,
 ) {
     let Some(color) = config.contact_color else {
         return;
     };
-    for Collision(contacts) in collisions.read() {
+    for contacts in collisions.iter() {
         let Ok((position1, rotation1)) = colliders.get(contacts.entity1) else {
             continue;
         };
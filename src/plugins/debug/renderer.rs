@@ -378,7 +378,54 @@ impl<'w, 's> PhysicsDebugRenderer<'w, 's> {
                     color,
                 );
             }
-            TypedShape::Custom(_) => (),
+            TypedShape::Custom(_) => {
+                let shape = collider.shape_scaled();
+                if let Some(voxels) = shape.as_shape::<Voxels>() {
+                    self.draw_voxels(voxels, position, rotation, color);
+                } else if let Some(sdf) = shape.as_shape::<SignedDistanceField>() {
+                    self.draw_sdf(sdf, position, rotation, color);
+                }
+            }
+        }
+    }
+
+    /// Draws the exposed voxels of a [`Voxels`] shape with a given position and rotation.
+    pub fn draw_voxels(
+        &mut self,
+        voxels: &Voxels,
+        position: &Position,
+        rotation: &Rotation,
+        color: Color,
+    ) {
+        for voxel in voxels.iter().filter(|voxel| voxels.is_exposed(*voxel)) {
+            let center = position.0 + rotation.rotate(voxels.voxel_center(voxel));
+            #[cfg(feature = "2d")]
+            let transform = Transform::from_scale(voxels.voxel_size().extend(0.0).as_f32())
+                .with_translation(center.extend(0.0).as_f32())
+                .with_rotation(Quaternion::from(*rotation).as_f32());
+            #[cfg(feature = "3d")]
+            let transform = Transform::from_scale(voxels.voxel_size().as_f32())
+                .with_translation(center.as_f32())
+                .with_rotation(rotation.as_f32());
+            self.gizmos.cuboid(transform, color);
+        }
+    }
+
+    /// Draws the surface of a [`SignedDistanceField`] with a given position and rotation.
+    ///
+    /// In 2D, the outline of the shape is drawn. In 3D, the outlines of slices of the shape
+    /// along each axis are drawn. The outline is computed once and cached on the shape.
+    pub fn draw_sdf(
+        &mut self,
+        sdf: &SignedDistanceField,
+        position: &Position,
+        rotation: &Rotation,
+        color: Color,
+    ) {
+        for [a, b] in sdf.outline() {
+            let a = position.0 + rotation.rotate(*a);
+            let b = position.0 + rotation.rotate(*b);
+            self.draw_line(a, b, color);
         }
     }
 
@@ -0,0 +1,629 @@
+//! Particle-based fluids that are simulated using position-based fluids (PBF) with XPBD density constraints.
+//!
+//! See [`FluidPlugin`] and [`Fluid`].
+
+use super::soft_body::solve_particle_collision;
+use crate::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+
+/// Simulates [fluids](Fluid) made of [particles](Particle) using position-based fluids (PBF).
+///
+/// Each particle of a fluid has a density constraint that keeps the density around the particle
+/// at the [rest density](Fluid::rest_density) of the fluid. The density is estimated using
+/// [smoothed particle hydrodynamics (SPH)](https://en.wikipedia.org/wiki/Smoothed-particle_hydrodynamics)
+/// kernels, and the constraints are solved using Lagrange multipliers like other [constraints].
+///
+/// The particles are simulated in the [`SubstepSchedule`] alongside rigid bodies:
+///
+/// 1. **Integration**: Gravity and damping are applied to the particles, they are moved according to
+///    their velocities, and the neighbors of each particle are found. Runs in [`SubstepSet::Integrate`].
+/// 2. **Constraint projection**: The density constraints are solved, and particles that penetrate
+///    [colliders](Collider) are pushed out. Runs after [`SubstepSet::SolveUserConstraints`].
+/// 3. **Velocity update**: The velocities of the particles are updated based on their positional changes,
+///    and [viscosity](Fluid::viscosity) is applied. Runs in [`SubstepSet::UpdateVelocities`].
+///
+/// Particles collide with colliders using the [`SpatialQueryPipeline`], so collisions require the
+/// [`SpatialQueryPlugin`]. When particles are pushed out of [dynamic](RigidBody::Dynamic) bodies, the
+/// correction is split between the particle and the body based on their masses, like for other constraints.
+/// This way, fluids can push bodies around, and the pressure of the fluid makes bodies that are less dense
+/// than the fluid float.
+pub struct FluidPlugin;
+
+impl Plugin for FluidPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Fluid>();
+
+        let substeps = app
+            .get_schedule_mut(SubstepSchedule)
+            .expect("add SubstepSchedule first");
+
+        substeps.add_systems(integrate_fluids.in_set(SubstepSet::Integrate));
+        substeps.add_systems(
+            solve_fluids
+                .after(SubstepSet::SolveUserConstraints)
+                .before(SubstepSet::UpdateVelocities),
+        );
+        substeps.add_systems(update_fluid_velocities.in_set(SubstepSet::UpdateVelocities));
+    }
+}
+
+/// A fluid made of [particles](Particle) that is simulated using position-based fluids (PBF).
+/// It can be used for simulating liquids like water.
+///
+/// The fluid is configured using the [spacing](Self::spacing) of the particles at rest and the
+/// [rest density](Self::rest_density) of the fluid. The mass of the particles is computed from these
+/// so that particles that are packed at the given spacing have the rest density. By default, the rest density
+/// is `1.0`, which is the same as the default [`ColliderDensity`], so bodies with a smaller density float.
+///
+/// The particles collide with [colliders](Collider) as spheres with a radius of half the spacing.
+/// If the entity has [`CollisionLayers`], only colliders in the layers that the fluid interacts with
+/// are taken into account. The [`GravityScale`], [`GravityDirection`] and [`LinearDamping`] components
+/// of the entity also affect the particles.
+///
+/// The positions of the particles are in world space, so the entity doesn't need a rigid body or a `Transform`.
+/// Particles of different fluids don't interact with each other.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::{math::*, prelude::*};")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::{math::*, prelude::*};")]
+///
+/// fn setup(mut commands: Commands) {
+///     // A block of water with particles that are 0.1 units apart
+///     commands.spawn(
+///         Fluid::new(0.1)
+///             .with_viscosity(0.05)
+#[cfg_attr(
+    feature = "2d",
+    doc = "            .with_particles_in_aabb(Vector::ZERO, Vector::new(2.0, 1.0)),"
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "            .with_particles_in_aabb(Vector::ZERO, Vector::new(2.0, 1.0, 2.0)),"
+)]
+///     );
+/// }
+/// ```
+#[derive(Reflect, Clone, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct Fluid {
+    /// The particles of the fluid.
+    pub particles: Vec<Particle>,
+    /// The compliance of the density constraints, the inverse of their stiffness.
+    /// Higher values make the fluid more compressible.
+    pub compliance: Scalar,
+    /// The strength of the viscosity of the fluid, which smooths out the velocities of nearby particles.
+    /// Higher values make the fluid thicker.
+    pub viscosity: Scalar,
+    spacing: Scalar,
+    rest_density: Scalar,
+    particle_mass: Scalar,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    neighbors: FluidNeighbors,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    lagranges: Vec<Scalar>,
+}
+
+impl Default for Fluid {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl Fluid {
+    /// Creates a new [`Fluid`] without particles. The particles are kept at the given `spacing` from each other.
+    pub fn new(spacing: Scalar) -> Self {
+        let mut fluid = Self {
+            particles: vec![],
+            compliance: 0.0,
+            viscosity: 0.01,
+            spacing,
+            rest_density: 1.0,
+            particle_mass: 0.0,
+            neighbors: FluidNeighbors::default(),
+            lagranges: vec![],
+        };
+        fluid.update_particle_mass();
+        fluid
+    }
+
+    /// Sets the rest density of the fluid.
+    pub fn with_rest_density(mut self, rest_density: Scalar) -> Self {
+        self.rest_density = rest_density;
+        self.update_particle_mass();
+        self
+    }
+
+    /// Sets the compliance of the density constraints, the inverse of their stiffness.
+    pub fn with_compliance(self, compliance: Scalar) -> Self {
+        Self { compliance, ..self }
+    }
+
+    /// Sets the strength of the viscosity of the fluid.
+    pub fn with_viscosity(self, viscosity: Scalar) -> Self {
+        Self { viscosity, ..self }
+    }
+
+    /// Fills the axis-aligned box between `min` and `max` with particles that are placed
+    /// at the [spacing](Self::spacing) of the fluid.
+    pub fn with_particles_in_aabb(mut self, min: Vector, max: Vector) -> Self {
+        let counts = ((max - min) / self.spacing).floor();
+        let start = min + ((max - min) - counts * self.spacing) * 0.5 + self.spacing * 0.5;
+
+        for i in 0..counts.x as usize {
+            for j in 0..counts.y as usize {
+                #[cfg(feature = "2d")]
+                self.add_particle(
+                    start + Vector::new(i as Scalar, j as Scalar) * self.spacing,
+                    Vector::ZERO,
+                );
+                #[cfg(feature = "3d")]
+                for k in 0..counts.z as usize {
+                    self.add_particle(
+                        start + Vector::new(i as Scalar, j as Scalar, k as Scalar) * self.spacing,
+                        Vector::ZERO,
+                    );
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Adds a particle with the given position and velocity to the fluid.
+    pub fn add_particle(&mut self, position: Vector, velocity: Vector) {
+        self.particles.push(Particle {
+            velocity,
+            ..Particle::new(position, self.particle_mass)
+        });
+    }
+
+    /// Returns the distance between the particles of the fluid at rest.
+    pub fn spacing(&self) -> Scalar {
+        self.spacing
+    }
+
+    /// Returns the rest density of the fluid.
+    pub fn rest_density(&self) -> Scalar {
+        self.rest_density
+    }
+
+    /// Returns the mass of each particle.
+    pub fn particle_mass(&self) -> Scalar {
+        self.particle_mass
+    }
+
+    /// Returns the radius of the particles used for collisions, which is half of the [spacing](Self::spacing).
+    pub fn particle_radius(&self) -> Scalar {
+        0.5 * self.spacing
+    }
+
+    /// Returns the radius of the kernels used for estimating the density, which is twice the [spacing](Self::spacing).
+    pub fn smoothing_radius(&self) -> Scalar {
+        2.0 * self.spacing
+    }
+
+    /// Computes the mass of the particles so that the density of particles packed in a grid
+    /// at the spacing of the fluid is the rest density.
+    fn update_particle_mass(&mut self) {
+        let h = self.smoothing_radius();
+        let mut kernel_sum = 0.0;
+
+        for i in -2..=2 {
+            for j in -2..=2 {
+                #[cfg(feature = "2d")]
+                {
+                    let offset = Vector::new(i as Scalar, j as Scalar) * self.spacing;
+                    kernel_sum += spiky_kernel(offset.length(), h);
+                }
+                #[cfg(feature = "3d")]
+                for k in -2..=2 {
+                    let offset = Vector::new(i as Scalar, j as Scalar, k as Scalar) * self.spacing;
+                    kernel_sum += spiky_kernel(offset.length(), h);
+                }
+            }
+        }
+
+        self.particle_mass = self.rest_density / kernel_sum;
+        for particle in self.particles.iter_mut() {
+            particle.inverse_mass = 1.0 / self.particle_mass;
+        }
+    }
+}
+
+/// The neighbors of each particle of a [`Fluid`], stored contiguously.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct FluidNeighbors {
+    /// The start of the neighbors of each particle in `indices`, followed by the total number of neighbors.
+    offsets: Vec<usize>,
+    indices: Vec<usize>,
+}
+
+#[cfg(feature = "2d")]
+type GridCell = IVec2;
+#[cfg(feature = "3d")]
+type GridCell = IVec3;
+
+impl FluidNeighbors {
+    /// Returns the indices of the neighbors of the given particle.
+    fn get(&self, particle: usize) -> &[usize] {
+        &self.indices[self.offsets[particle]..self.offsets[particle + 1]]
+    }
+
+    /// Finds the particles that are within the given `radius` of each particle using a uniform grid.
+    fn update(&mut self, particles: &[Particle], radius: Scalar) {
+        #[cfg(feature = "2d")]
+        let cell_of = |position: Vector| (position / radius).floor().as_ivec2();
+        #[cfg(feature = "3d")]
+        let cell_of = |position: Vector| (position / radius).floor().as_ivec3();
+
+        let mut grid: HashMap<GridCell, Vec<usize>> = HashMap::default();
+        for (index, particle) in particles.iter().enumerate() {
+            grid.entry(cell_of(particle.position))
+                .or_default()
+                .push(index);
+        }
+
+        self.offsets.clear();
+        self.indices.clear();
+        self.offsets.push(0);
+
+        for (index, particle) in particles.iter().enumerate() {
+            let cell = cell_of(particle.position);
+
+            for x in -1..=1 {
+                for y in -1..=1 {
+                    #[cfg(feature = "2d")]
+                    let cells = [cell + IVec2::new(x, y)];
+                    #[cfg(feature = "3d")]
+                    let cells = [-1, 0, 1].map(|z| cell + IVec3::new(x, y, z));
+
+                    for neighbors in cells.iter().filter_map(|cell| grid.get(cell)) {
+                        self.indices.extend(neighbors.iter().filter(|&&other| {
+                            other != index
+                                && particle
+                                    .position
+                                    .distance_squared(particles[other].position)
+                                    < radius * radius
+                        }));
+                    }
+                }
+            }
+
+            self.offsets.push(self.indices.len());
+        }
+    }
+}
+
+/// The poly6 kernel used for estimating densities, taking the squared distance between particles.
+fn poly6_kernel(distance_squared: Scalar, h: Scalar) -> Scalar {
+    let h_squared = h * h;
+
+    if distance_squared >= h_squared {
+        return 0.0;
+    }
+
+    #[cfg(feature = "2d")]
+    {
+        4.0 / (PI * h.powi(8)) * (h_squared - distance_squared).powi(3)
+    }
+    #[cfg(feature = "3d")]
+    {
+        315.0 / (64.0 * PI * h.powi(9)) * (h_squared - distance_squared).powi(3)
+    }
+}
+
+/// The spiky kernel used for estimating densities.
+fn spiky_kernel(distance: Scalar, h: Scalar) -> Scalar {
+    if distance >= h {
+        return 0.0;
+    }
+
+    #[cfg(feature = "2d")]
+    {
+        10.0 / (PI * h.powi(5)) * (h - distance).powi(3)
+    }
+    #[cfg(feature = "3d")]
+    {
+        15.0 / (PI * h.powi(6)) * (h - distance).powi(3)
+    }
+}
+
+/// The gradient of the spiky kernel used for computing density constraint gradients.
+fn spiky_kernel_gradient(offset: Vector, h: Scalar) -> Vector {
+    let distance = offset.length();
+
+    if distance >= h || distance <= Scalar::EPSILON {
+        return Vector::ZERO;
+    }
+
+    #[cfg(feature = "2d")]
+    let scale = -30.0 / (PI * h.powi(5));
+    #[cfg(feature = "3d")]
+    let scale = -45.0 / (PI * h.powi(6));
+
+    scale * (h - distance).powi(2) * offset / distance
+}
+
+/// Applies gravity and damping to the particles of fluids, moves them according to their velocities
+/// and finds their neighbors.
+#[allow(clippy::type_complexity)]
+fn integrate_fluids(
+    mut fluids: Query<(
+        &mut Fluid,
+        Option<&GravityScale>,
+        Option<&GravityDirection>,
+        Option<&LinearDamping>,
+    )>,
+    gravity: Res<Gravity>,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_seconds_adjusted();
+
+    for (mut fluid, gravity_scale, gravity_direction, linear_damping) in &mut fluids {
+        let gravity = gravity_direction.map_or(gravity.0, |direction| {
+            direction.0.normalize_or_zero() * gravity.0.length()
+        }) * gravity_scale.map_or(1.0, |scale| scale.0);
+        let damping = 1.0 / (1.0 + delta_secs * linear_damping.map_or(0.0, |damping| damping.0));
+
+        let fluid = fluid.as_mut();
+
+        for particle in fluid.particles.iter_mut() {
+            particle.previous_position = particle.position;
+            particle.velocity = (particle.velocity + gravity * delta_secs) * damping;
+            particle.position += particle.velocity * delta_secs;
+        }
+
+        let smoothing_radius = fluid.smoothing_radius();
+        fluid.neighbors.update(&fluid.particles, smoothing_radius);
+    }
+}
+
+/// Solves the density constraints of fluids and pushes their particles out of colliders.
+/// When particles are pushed out of dynamic bodies, the bodies are pushed in the opposite direction.
+#[allow(clippy::type_complexity)]
+fn solve_fluids(
+    mut fluids: Query<(&mut Fluid, Option<&CollisionLayers>)>,
+    mut bodies: Query<RigidBodyQuery, Without<Sleeping>>,
+    colliders: Query<(
+        &Collider,
+        Option<&ColliderParent>,
+        Option<&ColliderTransform>,
+    )>,
+    spatial_query_pipeline: Option<Res<SpatialQueryPipeline>>,
+    time: Res<Time>,
+    mut warned_no_pipeline: Local<bool>,
+) {
+    let delta_secs = time.delta_seconds_adjusted();
+    let spatial_query_pipeline = pipeline_or_warn_once(
+        &spatial_query_pipeline,
+        &mut warned_no_pipeline,
+        "Collisions of `Fluid` particles",
+    );
+
+    for (mut fluid, layers) in &mut fluids {
+        let h = fluid.smoothing_radius();
+        let particle_radius = fluid.particle_radius();
+        let Fluid {
+            particles,
+            compliance,
+            neighbors,
+            lagranges,
+            rest_density,
+            particle_mass,
+            ..
+        } = fluid.as_mut();
+
+        let inverse_mass = 1.0 / *particle_mass;
+        let tilde_compliance = *compliance / delta_secs.powi(2);
+
+        // Compute the Lagrange multipliers of the density constraints.
+        // The constraints are unilateral, so particles only push each other apart.
+        lagranges.clear();
+        lagranges.extend((0..particles.len()).map(|i| {
+            let mut density = *particle_mass * spiky_kernel(0.0, h);
+            let mut gradient_i = Vector::ZERO;
+            let mut gradient_lengths_squared = 0.0;
+
+            for &j in neighbors.get(i) {
+                let offset = particles[i].position - particles[j].position;
+                density += *particle_mass * spiky_kernel(offset.length(), h);
+
+                let gradient = *particle_mass / *rest_density * spiky_kernel_gradient(offset, h);
+                gradient_i += gradient;
+                gradient_lengths_squared += gradient.length_squared();
+            }
+
+            let c = (density / *rest_density - 1.0).max(0.0);
+            let w_sum = inverse_mass * (gradient_i.length_squared() + gradient_lengths_squared);
+
+            if w_sum + tilde_compliance <= Scalar::EPSILON {
+                0.0
+            } else {
+                -c / (w_sum + tilde_compliance)
+            }
+        }));
+
+        // Apply the position corrections of all constraints at once
+        let corrections: Vec<Vector> = (0..particles.len())
+            .map(|i| {
+                neighbors
+                    .get(i)
+                    .iter()
+                    .map(|&j| {
+                        let offset = particles[i].position - particles[j].position;
+                        (lagranges[i] + lagranges[j]) * spiky_kernel_gradient(offset, h)
+                    })
+                    .sum::<Vector>()
+                    / *rest_density
+            })
+            .collect();
+
+        for (particle, correction) in particles.iter_mut().zip(corrections) {
+            particle.position += correction;
+        }
+
+        let Some(spatial_query_pipeline) = spatial_query_pipeline else {
+            continue;
+        };
+        let filter = layers.map_or(SpatialQueryFilter::default(), |layers| {
+            SpatialQueryFilter::new().with_masks_from_bits(layers.masks_bits())
+        });
+
+        for particle in particles.iter_mut() {
+            let Some(projection) =
+                spatial_query_pipeline.project_point(particle.position, false, filter.clone())
+            else {
+                continue;
+            };
+
+            let Ok((collider, collider_parent, collider_transform)) =
+                colliders.get(projection.entity)
+            else {
+                continue;
+            };
+
+            let entity = collider_parent.map_or(projection.entity, |parent| parent.get());
+            let Some(mut body) = bodies
+                .get_mut(entity)
+                .ok()
+                .filter(|body| body.rb.is_dynamic())
+            else {
+                // Particles are pushed out of static and kinematic colliders like soft bodies
+                if projection.is_inside
+                    || projection.point.distance_squared(particle.position)
+                        < particle_radius * particle_radius
+                {
+                    solve_particle_collision(
+                        particle,
+                        particle_radius,
+                        0.0,
+                        spatial_query_pipeline,
+                        &filter,
+                    );
+                }
+                continue;
+            };
+
+            // Dynamic bodies have moved since the spatial query pipeline was updated,
+            // so the particle is projected onto the collider at the current pose of the body.
+            let collider_transform = collider_transform.copied().unwrap_or_default();
+            let collider_position =
+                body.current_position() + body.rotation.rotate(collider_transform.translation);
+            #[cfg(feature = "2d")]
+            let collider_rotation = body.rotation.mul(collider_transform.rotation);
+            #[cfg(feature = "3d")]
+            let collider_rotation = Rotation(body.rotation.0 * collider_transform.rotation.0);
+
+            let projection = collider.shape_scaled().project_point(
+                &utils::make_isometry(collider_position, collider_rotation),
+                &particle.position.into(),
+                false,
+            );
+            let point: Vector = projection.point.into();
+            let offset = particle.position - point;
+            let distance = offset.length();
+
+            if distance <= Scalar::EPSILON || (!projection.is_inside && distance >= particle_radius)
+            {
+                continue;
+            }
+
+            // The normal points out of the collider
+            let (normal, penetration) = if projection.is_inside {
+                (-offset / distance, distance + particle_radius)
+            } else {
+                (offset / distance, particle_radius - distance)
+            };
+
+            // Split the correction between the particle and the body based on their generalized inverse masses
+            let world_center_of_mass =
+                body.current_position() + body.rotation.rotate(body.center_of_mass.0);
+            let r = point - world_center_of_mass;
+            let inverse_inertia = body.effective_world_inv_inertia();
+
+            #[cfg(feature = "2d")]
+            let body_inverse_mass =
+                body.inverse_mass.0 + inverse_inertia * r.perp_dot(normal).powi(2);
+            #[cfg(feature = "3d")]
+            let body_inverse_mass = {
+                let r_cross_n = r.cross(normal);
+                body.inverse_mass.0 + r_cross_n.dot(inverse_inertia * r_cross_n)
+            };
+
+            let impulse = normal * penetration / (particle.inverse_mass + body_inverse_mass);
+            particle.position += impulse * particle.inverse_mass;
+
+            let translation = impulse * body.effective_inv_mass();
+            body.accumulated_translation.0 -= translation;
+            #[cfg(feature = "2d")]
+            {
+                *body.rotation -= Rotation::from_radians(inverse_inertia * r.perp_dot(impulse));
+            }
+            #[cfg(feature = "3d")]
+            {
+                let rotation = *body.rotation;
+                *body.rotation -= Rotation(
+                    Quaternion::from_vec4(0.5 * (inverse_inertia * r.cross(impulse)).extend(0.0))
+                        * rotation.0,
+                );
+            }
+        }
+    }
+}
+
+/// Updates the velocities of the particles of fluids based on their positional changes and applies viscosity.
+fn update_fluid_velocities(mut fluids: Query<&mut Fluid>, time: Res<Time>) {
+    let delta_secs = time.delta_seconds_adjusted();
+
+    if delta_secs == 0.0 {
+        return;
+    }
+
+    for mut fluid in &mut fluids {
+        let h = fluid.smoothing_radius();
+        let volume = fluid.particle_mass / fluid.rest_density;
+        let viscosity = fluid.viscosity;
+        let Fluid {
+            particles,
+            neighbors,
+            ..
+        } = fluid.as_mut();
+
+        for particle in particles.iter_mut() {
+            particle.velocity = (particle.position - particle.previous_position) / delta_secs;
+        }
+
+        if viscosity <= 0.0 {
+            continue;
+        }
+
+        // XSPH viscosity
+        let velocity_changes: Vec<Vector> = (0..particles.len())
+            .map(|i| {
+                neighbors
+                    .get(i)
+                    .iter()
+                    .map(|&j| {
+                        let distance_squared = particles[i]
+                            .position
+                            .distance_squared(particles[j].position);
+                        (particles[j].velocity - particles[i].velocity)
+                            * volume
+                            * poly6_kernel(distance_squared, h)
+                    })
+                    .sum::<Vector>()
+                    * viscosity
+            })
+            .collect();
+
+        for (particle, velocity_change) in particles.iter_mut().zip(velocity_changes) {
+            particle.velocity += velocity_change;
+        }
+    }
+}
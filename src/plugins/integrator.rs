@@ -265,7 +265,7 @@ type ImpulseQueryComponents = (
     Option<&'static LockedAxes>,
 );
 
-fn apply_impulses(mut bodies: Query<ImpulseQueryComponents, Without<Sleeping>>) {
+pub(crate) fn apply_impulses(mut bodies: Query<ImpulseQueryComponents, Without<Sleeping>>) {
     for (
         rb,
         impulse,
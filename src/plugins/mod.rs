@@ -20,31 +20,46 @@
 //! - [`PhysicsSchedule`] and [`PhysicsStepSet`]
 //! - [`SubstepSchedule`] and [`SubstepSet`]
 
+pub mod character_controller;
 pub mod collision;
 #[cfg(feature = "debug-plugin")]
 pub mod debug;
+pub mod fluid;
 pub mod integrator;
+#[cfg(feature = "physics-material")]
+pub mod physics_material;
 pub mod prepare;
+#[cfg(any(feature = "physics-material", feature = "collision-matrix-asset"))]
+pub mod ron_asset;
 pub mod setup;
 pub mod sleeping;
+pub mod soft_body;
 pub mod solver;
 pub mod spatial_query;
 pub mod sync;
+pub mod vehicle;
 
 use bevy::utils::intern::Interned;
+pub use character_controller::CharacterControllerPlugin;
 pub use collision::{
-    broad_phase::BroadPhasePlugin, contact_reporting::ContactReportingPlugin,
+    broad_phase::BroadPhasePlugin, ccd::CcdPlugin, collision_filter::CollisionFilterPlugin,
+    collision_matrix::CollisionMatrixPlugin, contact_reporting::ContactReportingPlugin,
     narrow_phase::NarrowPhasePlugin,
 };
 #[cfg(feature = "debug-plugin")]
 pub use debug::PhysicsDebugPlugin;
+pub use fluid::FluidPlugin;
 pub use integrator::IntegratorPlugin;
+#[cfg(feature = "physics-material")]
+pub use physics_material::PhysicsMaterialPlugin;
 pub use prepare::PreparePlugin;
 pub use setup::PhysicsSetupPlugin;
 pub use sleeping::SleepingPlugin;
+pub use soft_body::SoftBodyPlugin;
 pub use solver::SolverPlugin;
 pub use spatial_query::SpatialQueryPlugin;
 pub use sync::SyncPlugin;
+pub use vehicle::VehiclePlugin;
 
 #[allow(unused_imports)]
 use crate::prelude::*; // For doc comments
@@ -57,15 +72,25 @@ use bevy::prelude::*;
 /// - [`PhysicsSetupPlugin`]: Sets up the physics engine by initializing the necessary schedules, sets and resources.
 /// - [`PreparePlugin`]: Runs systems at the start of each physics frame; initializes [rigid bodies](RigidBody)
 /// and [colliders](Collider) and updates components.
+/// - [`CollisionMatrixPlugin`]: Manages the [`CollisionMatrix`] that decides which entities with a
+/// [`CollisionLayerName`] interact.
 /// - [`BroadPhasePlugin`]: Collects pairs of potentially colliding entities into [`BroadCollisionPairs`] using
 /// [AABB](ColliderAabb) intersection checks.
 /// - [`IntegratorPlugin`]: Integrates Newton's 2nd law of motion, applying forces and moving entities according to their velocities.
 /// - [`NarrowPhasePlugin`]: Computes contacts between entities and sends collision events.
+/// - [`CcdPlugin`]: Prevents fast moving bodies with [`SweptCcd`] from tunneling through colliders.
 /// - [`ContactReportingPlugin`]: Sends collision events and updates [`CollidingEntities`].
 /// - [`SolverPlugin`]: Solves positional and angular [constraints], updates velocities and solves velocity constraints
 /// (dynamic [friction](Friction) and [restitution](Restitution)).
 /// - [`SleepingPlugin`]: Controls when bodies should be deactivated and marked as [`Sleeping`] to improve performance.
 /// - [`SpatialQueryPlugin`]: Handles spatial queries like [raycasting](RayCaster) and shapecasting.
+/// - [`CharacterControllerPlugin`]: Moves [kinematic](RigidBody::Kinematic) bodies with a [`CharacterController`]
+/// using move-and-slide.
+/// - [`VehiclePlugin`]: Simulates the suspension and tires of [vehicles](Vehicle) using raycasts.
+/// - [`SoftBodyPlugin`]: Simulates [soft bodies](SoftBody) and cloth made of particles.
+/// - [`FluidPlugin`]: Simulates [fluids](Fluid) made of particles using position-based fluids.
+/// - `PhysicsMaterialPlugin`: Loads physics materials from files and applies their density
+/// to colliders (only with `physics-material` feature enabled). The plugin must be added separately.
 /// - [`SyncPlugin`]: Keeps [`Position`] and [`Rotation`] in sync with `Transform`.
 /// - `PhysicsDebugPlugin`: Renders physics objects and events like [AABBs](ColliderAabb) and [contacts](Collision)
 /// for debugging purposes (only with `debug-plugin` feature enabled).
@@ -189,13 +214,19 @@ impl PluginGroup for PhysicsPlugins {
         PluginGroupBuilder::start::<Self>()
             .add(PhysicsSetupPlugin::new(self.schedule))
             .add(PreparePlugin::new(self.schedule))
+            .add(CollisionMatrixPlugin::new(self.schedule))
             .add(BroadPhasePlugin)
             .add(IntegratorPlugin)
             .add(NarrowPhasePlugin)
+            .add(CcdPlugin)
             .add(ContactReportingPlugin)
             .add(SolverPlugin)
             .add(SleepingPlugin)
             .add(SpatialQueryPlugin::new(self.schedule))
+            .add(CharacterControllerPlugin)
+            .add(VehiclePlugin)
+            .add(SoftBodyPlugin::new(self.schedule))
+            .add(FluidPlugin)
             .add(SyncPlugin::new(self.schedule))
     }
 }
@@ -0,0 +1,268 @@
+//! Physics materials that can be shared between colliders and loaded from files.
+//!
+//! See [`PhysicsMaterialPlugin`] and [`PhysicsMaterial`].
+
+use super::ron_asset::{RonAsset, RonAssetLoader};
+use crate::prelude::*;
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    utils::{intern::Interned, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+/// Registers the [`PhysicsMaterial`] asset and its loader, and keeps the [`ColliderDensity`]
+/// of colliders in sync with their materials.
+///
+/// Materials can be loaded from `.physmat.ron` files. The loader is only registered if the app has an
+/// `AssetServer` when the plugin is built or finished, so the `AssetPlugin` should be added before
+/// the physics plugins.
+///
+/// This plugin is not included in [`PhysicsPlugins`] and must be added separately:
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins((
+///             DefaultPlugins,
+///             PhysicsPlugins::default(),
+///             PhysicsMaterialPlugin::default(),
+///         ))
+///         .run();
+/// }
+/// ```
+///
+/// The density is updated in [`PhysicsSet::Prepare`] whenever the handle of a collider changes or
+/// the material is modified. Friction and restitution are resolved by the [`SolverPlugin`] when it
+/// creates the [`PenetrationConstraint`]s.
+pub struct PhysicsMaterialPlugin {
+    schedule: Interned<dyn ScheduleLabel>,
+}
+
+impl PhysicsMaterialPlugin {
+    /// Creates a [`PhysicsMaterialPlugin`] with the schedule that is used for running the [`PhysicsSchedule`].
+    ///
+    /// The default schedule is `PostUpdate`.
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for PhysicsMaterialPlugin {
+    fn default() -> Self {
+        Self::new(PostUpdate)
+    }
+}
+
+impl Plugin for PhysicsMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        register_physics_material_asset(app);
+
+        app.add_systems(
+            self.schedule,
+            update_material_densities
+                .run_if(resource_exists::<Assets<PhysicsMaterial>>())
+                .before(PhysicsSet::Prepare),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // The asset server might have been added after the physics plugins.
+        register_physics_material_asset(app);
+    }
+}
+
+/// Initializes the [`PhysicsMaterial`] asset and registers the [`PhysicsMaterialLoader`]
+/// if an `AssetServer` exists and the asset hasn't been registered yet.
+fn register_physics_material_asset(app: &mut App) {
+    if app.world.contains_resource::<AssetServer>()
+        && !app.world.contains_resource::<Assets<PhysicsMaterial>>()
+    {
+        app.init_asset::<PhysicsMaterial>()
+            .init_asset_loader::<PhysicsMaterialLoader>();
+    }
+}
+
+/// A [`PhysicsMaterial`] asset that bundles the [`Friction`], [`Restitution`] and [`ColliderDensity`]
+/// of a surface, like ice, rubber or metal.
+///
+/// Colliders use a material by having a `Handle<PhysicsMaterial>` component. Colliders without a handle
+/// use the material of their rigid body, if it has one. When a collider has a material, the material
+/// overrides the collider's [`Friction`], [`Restitution`] and [`ColliderDensity`]. The coefficients of colliding materials are combined using
+/// the [`CoefficientCombine`] rules of the friction and restitution.
+///
+/// Materials can be loaded from `.physmat.ron` files, which allows tuning them without recompiling.
+/// Enabling Bevy's `file_watcher` feature also reloads the materials when the files are modified.
+/// Fields that are not specified use their default values:
+///
+/// ```ron
+/// (
+///     friction: (
+///         dynamic_coefficient: 0.05,
+///         static_coefficient: 0.1,
+///         combine_rule: Min,
+///     ),
+///     restitution: (
+///         coefficient: 0.0,
+///         combine_rule: Average,
+///     ),
+///     density: 0.9,
+/// )
+/// ```
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn setup(
+///     mut commands: Commands,
+///     asset_server: Res<AssetServer>,
+///     mut materials: ResMut<Assets<PhysicsMaterial>>,
+/// ) {
+///     // Load a material from a file
+///     commands.spawn((
+///         RigidBody::Static,
+#[cfg_attr(feature = "2d", doc = "        Collider::cuboid(10.0, 0.5),")]
+#[cfg_attr(feature = "3d", doc = "        Collider::cuboid(10.0, 0.5, 10.0),")]
+///         asset_server.load::<PhysicsMaterial>("materials/ice.physmat.ron"),
+///     ));
+///
+///     // Create a material in code
+///     let rubber = materials.add(
+///         PhysicsMaterial::default()
+///             .with_friction(Friction::new(1.0))
+///             .with_restitution(Restitution::new(0.8).with_combine_rule(CoefficientCombine::Max))
+///             .with_density(1.5),
+///     );
+///     commands.spawn((RigidBody::Dynamic, Collider::ball(0.5), rubber));
+/// }
+/// ```
+#[derive(Asset, Reflect, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsMaterial {
+    /// The friction of the material.
+    pub friction: Friction,
+    /// The restitution of the material.
+    pub restitution: Restitution,
+    /// The density of the material, used for computing the mass properties of colliders.
+    pub density: Scalar,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            friction: Friction::default(),
+            restitution: Restitution::default(),
+            density: ColliderDensity::default().0,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    /// Creates a new [`PhysicsMaterial`] with the given friction, restitution and density.
+    pub fn new(friction: Friction, restitution: Restitution, density: Scalar) -> Self {
+        Self {
+            friction,
+            restitution,
+            density,
+        }
+    }
+
+    /// Sets the [`Friction`] of the material.
+    pub fn with_friction(self, friction: Friction) -> Self {
+        Self { friction, ..self }
+    }
+
+    /// Sets the [`Restitution`] of the material.
+    pub fn with_restitution(self, restitution: Restitution) -> Self {
+        Self {
+            restitution,
+            ..self
+        }
+    }
+
+    /// Sets the density of the material.
+    pub fn with_density(self, density: Scalar) -> Self {
+        Self { density, ..self }
+    }
+}
+
+impl RonAsset for PhysicsMaterial {
+    const EXTENSIONS: &'static [&'static str] = &["physmat.ron"];
+}
+
+/// Loads [`PhysicsMaterial`]s from `.physmat.ron` files.
+pub type PhysicsMaterialLoader = RonAssetLoader<PhysicsMaterial>;
+
+/// A [`SystemParam`] for getting the [`PhysicsMaterial`] of an entity.
+#[derive(SystemParam)]
+pub(crate) struct PhysicsMaterials<'w, 's> {
+    handles: Query<'w, 's, &'static Handle<PhysicsMaterial>>,
+    parents: Query<'w, 's, &'static ColliderParent>,
+    assets: Option<Res<'w, Assets<PhysicsMaterial>>>,
+}
+
+impl<'w, 's> PhysicsMaterials<'w, 's> {
+    /// Returns the [`PhysicsMaterial`] of the given collider entity if it or its rigid body
+    /// has a handle to a loaded material. The handle of the collider takes precedence.
+    pub(crate) fn get(&self, entity: Entity) -> Option<&PhysicsMaterial> {
+        let handle = self.handles.get(entity).ok().or_else(|| {
+            let parent = self.parents.get(entity).ok()?;
+            self.handles.get(parent.get()).ok()
+        })?;
+        self.assets.as_ref()?.get(handle)
+    }
+}
+
+/// Updates the [`ColliderDensity`] of colliders when their [`PhysicsMaterial`] is changed or modified.
+///
+/// Colliders without a material use the material of their rigid body.
+#[allow(clippy::type_complexity)]
+fn update_material_densities(
+    mut colliders: Query<(
+        Option<Ref<Handle<PhysicsMaterial>>>,
+        Option<Ref<ColliderParent>>,
+        &mut ColliderDensity,
+    )>,
+    handles: Query<Ref<Handle<PhysicsMaterial>>>,
+    materials: Res<Assets<PhysicsMaterial>>,
+    mut material_events: EventReader<AssetEvent<PhysicsMaterial>>,
+) {
+    let modified: HashSet<AssetId<PhysicsMaterial>> = material_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (handle, parent, mut density) in &mut colliders {
+        let parent_changed = parent.as_ref().is_some_and(|parent| parent.is_changed());
+        let Some(handle) =
+            handle.or_else(|| parent.and_then(|parent| handles.get(parent.get()).ok()))
+        else {
+            continue;
+        };
+        if !handle.is_changed()
+            && !parent_changed
+            && !density.is_added()
+            && !modified.contains(&handle.id())
+        {
+            continue;
+        }
+        if let Some(material) = materials.get(handle.id()) {
+            density.set_if_neq(ColliderDensity(material.density));
+        }
+    }
+}
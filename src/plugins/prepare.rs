@@ -98,6 +98,8 @@ impl Plugin for PreparePlugin {
             ),
         );
 
+        #[cfg(all(feature = "2d", feature = "async-collider"))]
+        app.add_systems(Update, init_async_colliders);
         #[cfg(all(feature = "3d", feature = "async-collider"))]
         app.add_systems(Update, (init_async_colliders, init_async_scene_colliders));
     }
@@ -408,6 +410,27 @@ fn init_colliders(
     }
 }
 
+/// Creates [`Collider`]s from [`AsyncCollider`]s if the meshes of their `Mesh2dHandle`s have become available.
+#[cfg(all(feature = "2d", feature = "async-collider"))]
+pub fn init_async_colliders(
+    mut commands: Commands,
+    meshes: Res<Assets<Mesh>>,
+    async_colliders: Query<(Entity, &bevy::sprite::Mesh2dHandle, &AsyncCollider)>,
+) {
+    for (entity, mesh_handle, async_collider) in async_colliders.iter() {
+        if let Some(mesh) = meshes.get(&mesh_handle.0) {
+            if let Some(collider) = Collider::from_mesh_2d(mesh, &async_collider.0) {
+                commands.entity(entity).insert(collider);
+            } else {
+                error!("Unable to generate collider from mesh {:?}", mesh);
+            }
+            // The component is removed even if the collider couldn't be generated
+            // so that the error isn't logged again every frame
+            commands.entity(entity).remove::<AsyncCollider>();
+        }
+    }
+}
+
 /// Creates [`Collider`]s from [`AsyncCollider`]s if the meshes have become available.
 #[cfg(all(feature = "3d", feature = "async-collider"))]
 pub fn init_async_colliders(
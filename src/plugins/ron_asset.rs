@@ -0,0 +1,81 @@
+//! A generic loader for assets stored in RON files.
+//!
+//! See [`RonAssetLoader`].
+
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, LoadContext},
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+/// An [`Asset`] that can be loaded from RON files by a [`RonAssetLoader`].
+pub trait RonAsset: Asset + DeserializeOwned {
+    /// The file extensions of the asset, like `"physmat.ron"`.
+    const EXTENSIONS: &'static [&'static str];
+}
+
+/// Loads assets of type `A` from RON files with the extensions given by [`RonAsset::EXTENSIONS`].
+pub struct RonAssetLoader<A: RonAsset>(PhantomData<fn() -> A>);
+
+impl<A: RonAsset> Default for RonAssetLoader<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// An error that can occur when loading an asset with a [`RonAssetLoader`].
+#[derive(Debug)]
+pub enum RonAssetLoaderError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not a valid RON representation of the asset.
+    Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for RonAssetLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read asset: {err}"),
+            Self::Ron(err) => write!(f, "could not parse asset: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetLoaderError {}
+
+impl From<std::io::Error> for RonAssetLoaderError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for RonAssetLoaderError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Ron(err)
+    }
+}
+
+impl<A: RonAsset> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<A>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        A::EXTENSIONS
+    }
+}
@@ -92,6 +92,7 @@ impl Plugin for PhysicsSetupPlugin {
             .register_type::<PreSolveAngularVelocity>()
             .register_type::<Restitution>()
             .register_type::<Friction>()
+            .register_type::<SurfaceVelocity>()
             .register_type::<LinearDamping>()
             .register_type::<AngularDamping>()
             .register_type::<ExternalForce>()
@@ -110,7 +111,13 @@ impl Plugin for PhysicsSetupPlugin {
             .register_type::<ColliderParent>()
             .register_type::<Dominance>()
             .register_type::<CollisionLayers>()
+            .register_type::<CollisionGroup>()
             .register_type::<CollidingEntities>()
+            .register_type::<CollisionEventsEnabled>()
+            .register_type::<OnCollisionStart>()
+            .register_type::<OnCollisionEnd>()
+            .register_type::<ContactForceThreshold>()
+            .register_type::<ContactImpulses>()
             .register_type::<CoefficientCombine>()
             .register_type::<Sensor>()
             .register_type::<ColliderTransform>()
@@ -142,6 +149,7 @@ impl Plugin for PhysicsSetupPlugin {
                 (
                     PhysicsStepSet::BroadPhase,
                     PhysicsStepSet::Substeps,
+                    PhysicsStepSet::SweptCcd,
                     PhysicsStepSet::ReportContacts,
                     PhysicsStepSet::Sleeping,
                     PhysicsStepSet::SpatialQuery,
@@ -3,13 +3,18 @@
 //! See [`SleepingPlugin`].
 
 use crate::prelude::*;
-use bevy::prelude::*;
+use bevy::{ecs::query::Has, prelude::*, utils::HashMap};
 
 /// Controls when bodies should be deactivated and marked as [`Sleeping`] to improve performance.
 ///
 /// Bodies are marked as [`Sleeping`] when their linear and angular velocities are below the [`SleepingThreshold`]
 /// for a duration indicated by [`DeactivationTime`].
 ///
+/// Sleeping is handled per [simulation island](SimulationIslands), a group of dynamic bodies connected by contacts
+/// or [constraints]. An island is only put to sleep once all of its bodies have been still for long enough,
+/// and if any body in a sleeping island is woken up, the rest of the island is woken up as well.
+/// This prevents stacks and other connected structures from being partially asleep.
+///
 /// Bodies are woken up when an active body or constraint interacts with them, or when gravity changes,
 /// or when the body's position, rotation, velocity, or external forces are changed.
 ///
@@ -20,15 +25,25 @@ pub struct SleepingPlugin;
 
 impl Plugin for SleepingPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationIslands>();
+
         app.get_schedule_mut(PhysicsSchedule)
             .expect("add PhysicsSchedule first")
             .add_systems(wake_on_collision_ended.in_set(PhysicsStepSet::ReportContacts))
             .add_systems(
                 (
+                    link_constraint_islands::<FixedJoint, 2>,
+                    link_constraint_islands::<RevoluteJoint, 2>,
+                    link_constraint_islands::<SphericalJoint, 2>,
+                    link_constraint_islands::<PrismaticJoint, 2>,
+                    link_constraint_islands::<DistanceJoint, 2>,
+                    build_islands,
                     mark_sleeping_bodies,
                     wake_on_changed,
                     wake_on_collider_removed,
                     wake_all_sleeping_bodies.run_if(resource_changed::<Gravity>()),
+                    apply_deferred,
+                    wake_islands,
                 )
                     .chain()
                     .in_set(PhysicsStepSet::Sleeping),
@@ -36,26 +51,183 @@ impl Plugin for SleepingPlugin {
     }
 }
 
+/// A resource that stores the *simulation islands* of the world.
+///
+/// An island is a group of [dynamic](RigidBody::Dynamic) bodies that are connected to each other
+/// through contacts in [`Collisions`] or through [constraints] like [joints]. Static and kinematic bodies
+/// don't connect islands, so bodies resting on the same static ground can still be in separate islands.
+///
+/// Bodies in different islands can't affect each other, which is why the [`SleepingPlugin`] puts whole
+/// islands to sleep and wakes them up together.
+///
+/// Currently, the islands are only used for sleeping. The [solver](SolverPlugin) still solves
+/// all islands serially, and solving independent islands in parallel is left for the future.
+///
+/// The islands are rebuilt each physics step in [`PhysicsStepSet::Sleeping`].
+/// By default, contacts and the built-in joints are taken into account. The bodies of custom constraints
+/// can be linked by adding the [`link_constraint_islands`] system for the constraint type
+/// in [`PhysicsStepSet::Sleeping`], before the islands are built.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct SimulationIslands {
+    islands: Vec<Vec<Entity>>,
+    body_islands: HashMap<Entity, usize>,
+    /// Pairs of bodies connected by constraints, collected for the next time the islands are built.
+    constraint_links: Vec<(Entity, Entity)>,
+}
+
+impl SimulationIslands {
+    /// Returns an iterator over the bodies in each island.
+    pub fn iter(&self) -> impl Iterator<Item = &[Entity]> {
+        self.islands.iter().map(|island| island.as_slice())
+    }
+
+    /// Returns the bodies in the island that contains the given body,
+    /// or `None` if the body isn't a dynamic rigid body.
+    pub fn island(&self, entity: Entity) -> Option<&[Entity]> {
+        self.body_islands
+            .get(&entity)
+            .map(|index| self.islands[*index].as_slice())
+    }
+
+    /// Returns the number of islands.
+    pub fn len(&self) -> usize {
+        self.islands.len()
+    }
+
+    /// Returns `true` if there are no islands.
+    pub fn is_empty(&self) -> bool {
+        self.islands.is_empty()
+    }
+}
+
+/// Links the bodies of each constraint of type `C` in the [`SimulationIslands`].
+///
+/// This is added automatically for the built-in joints. For custom constraints, add it to
+/// [`PhysicsStepSet::Sleeping`] so that it runs before the islands are built:
+///
+/// ```ignore
+/// app.get_schedule_mut(PhysicsSchedule)
+///     .expect("add PhysicsSchedule first")
+///     .add_systems(
+///         link_constraint_islands::<CustomConstraint, 2>
+///             .in_set(PhysicsStepSet::Sleeping)
+///             .before(link_constraint_islands::<FixedJoint, 2>),
+///     );
+/// ```
+pub fn link_constraint_islands<
+    C: XpbdConstraint<ENTITY_COUNT> + Component,
+    const ENTITY_COUNT: usize,
+>(
+    constraints: Query<&C>,
+    mut islands: ResMut<SimulationIslands>,
+) {
+    for constraint in &constraints {
+        let entities = constraint.entities();
+        for window in entities.windows(2) {
+            islands.constraint_links.push((window[0], window[1]));
+        }
+    }
+}
+
+/// Builds the [`SimulationIslands`] from the contacts in [`Collisions`] and the constraint links.
+fn build_islands(
+    bodies: Query<(Entity, &RigidBody)>,
+    colliders: Query<&ColliderParent>,
+    collisions: Res<Collisions>,
+    mut islands: ResMut<SimulationIslands>,
+) {
+    let mut body_indices = HashMap::<Entity, usize>::default();
+    let mut entities = vec![];
+
+    for (entity, rb) in &bodies {
+        if rb.is_dynamic() {
+            body_indices.insert(entity, entities.len());
+            entities.push(entity);
+        }
+    }
+
+    // Union-find over the bodies, each body starts in its own set
+    let mut parents: Vec<usize> = (0..entities.len()).collect();
+
+    let contact_links = collisions.iter().map(|contacts| {
+        let body1 = colliders
+            .get(contacts.entity1)
+            .map_or(contacts.entity1, |parent| parent.get());
+        let body2 = colliders
+            .get(contacts.entity2)
+            .map_or(contacts.entity2, |parent| parent.get());
+        (body1, body2)
+    });
+    let constraint_links = std::mem::take(&mut islands.constraint_links);
+
+    for (body1, body2) in contact_links.chain(constraint_links) {
+        // Only dynamic bodies are linked
+        let (Some(&index1), Some(&index2)) = (body_indices.get(&body1), body_indices.get(&body2))
+        else {
+            continue;
+        };
+
+        let root1 = find_root(&mut parents, index1);
+        let root2 = find_root(&mut parents, index2);
+        if root1 != root2 {
+            parents[root2] = root1;
+        }
+    }
+
+    // Group the bodies by their roots, in the order the islands are first encountered
+    let SimulationIslands {
+        islands,
+        body_islands,
+        ..
+    } = &mut *islands;
+    islands.clear();
+    body_islands.clear();
+
+    let mut root_islands = HashMap::<usize, usize>::default();
+
+    for (index, entity) in entities.iter().enumerate() {
+        let root = find_root(&mut parents, index);
+        let island_index = *root_islands.entry(root).or_insert_with(|| {
+            islands.push(vec![]);
+            islands.len() - 1
+        });
+        islands[island_index].push(*entity);
+        body_islands.insert(*entity, island_index);
+    }
+}
+
+/// Finds the root of the set that the given index belongs to, compressing the path along the way.
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
 type SleepingQueryComponents = (
-    Entity,
     &'static RigidBody,
     &'static mut LinearVelocity,
     &'static mut AngularVelocity,
     &'static mut TimeSleeping,
+    Has<Sleeping>,
+    Has<SleepingDisabled>,
 );
 
-/// Adds the [`Sleeping`] component to bodies whose linear and anigular velocities have been
-/// under the [`SleepingThreshold`] for a duration indicated by [`DeactivationTime`].
+/// Adds the [`Sleeping`] component to the bodies of [simulation islands](SimulationIslands) whose
+/// bodies' linear and angular velocities have all been under the [`SleepingThreshold`]
+/// for a duration indicated by [`DeactivationTime`].
 fn mark_sleeping_bodies(
     mut commands: Commands,
-    mut bodies: Query<SleepingQueryComponents, (Without<Sleeping>, Without<SleepingDisabled>)>,
+    mut bodies: Query<SleepingQueryComponents>,
+    islands: Res<SimulationIslands>,
     deactivation_time: Res<DeactivationTime>,
     sleep_threshold: Res<SleepingThreshold>,
     dt: Res<Time>,
 ) {
-    for (entity, rb, mut lin_vel, mut ang_vel, mut time_sleeping) in &mut bodies {
+    for (rb, lin_vel, ang_vel, mut time_sleeping, is_sleeping, sleeping_disabled) in &mut bodies {
         // Only dynamic bodies can sleep.
-        if !rb.is_dynamic() {
+        if !rb.is_dynamic() || is_sleeping || sleeping_disabled {
             continue;
         }
 
@@ -77,12 +249,35 @@ fn mark_sleeping_bodies(
         } else {
             time_sleeping.0 = 0.0;
         }
+    }
+
+    for island in islands.iter() {
+        // An island can only sleep if all of its bodies have been still for long enough.
+        let mut can_sleep = false;
+        for entity in island {
+            let Ok((_, _, _, time_sleeping, is_sleeping, sleeping_disabled)) = bodies.get(*entity)
+            else {
+                continue;
+            };
+            if sleeping_disabled || (!is_sleeping && time_sleeping.0 <= deactivation_time.0) {
+                can_sleep = false;
+                break;
+            }
+            // Islands that are already sleeping don't need to be marked again.
+            can_sleep |= !is_sleeping;
+        }
 
-        // If the body has been still for long enough, set it to sleep and reset velocities.
-        if time_sleeping.0 > deactivation_time.0 {
-            commands.entity(entity).insert(Sleeping);
-            *lin_vel = LinearVelocity::ZERO;
-            *ang_vel = AngularVelocity::ZERO;
+        if !can_sleep {
+            continue;
+        }
+
+        // Set the bodies to sleep and reset velocities.
+        for entity in island {
+            if let Ok((_, mut lin_vel, mut ang_vel, _, false, _)) = bodies.get_mut(*entity) {
+                commands.entity(*entity).insert(Sleeping);
+                *lin_vel = LinearVelocity::ZERO;
+                *ang_vel = AngularVelocity::ZERO;
+            }
         }
     }
 }
@@ -156,14 +351,49 @@ fn wake_all_sleeping_bodies(
     }
 }
 
+/// Wakes up the sleeping bodies of [simulation islands](SimulationIslands) that contain awake bodies,
+/// so that islands always sleep and wake up together.
+fn wake_islands(
+    mut commands: Commands,
+    mut bodies: Query<(&mut TimeSleeping, Has<Sleeping>)>,
+    islands: Res<SimulationIslands>,
+) {
+    for island in islands.iter() {
+        let has_awake_bodies = island.iter().any(|entity| {
+            bodies
+                .get(*entity)
+                .is_ok_and(|(_, is_sleeping)| !is_sleeping)
+        });
+
+        if !has_awake_bodies {
+            continue;
+        }
+
+        for entity in island {
+            if let Ok((mut time_sleeping, true)) = bodies.get_mut(*entity) {
+                commands.entity(*entity).remove::<Sleeping>();
+                time_sleeping.0 = 0.0;
+            }
+        }
+    }
+}
+
 /// Wakes up bodies when they stop colliding.
+#[allow(clippy::type_complexity)]
 fn wake_on_collision_ended(
     mut commands: Commands,
-    moved_bodies: Query<(), (Changed<Position>, Without<Sleeping>)>,
+    moved_bodies: Query<
+        (),
+        (
+            Or<(Changed<Position>, Changed<Collider>)>,
+            Without<Sleeping>,
+        ),
+    >,
     collisions: Res<Collisions>,
     mut sleeping: Query<(Entity, &mut TimeSleeping), With<Sleeping>>,
 ) {
-    // Wake up bodies when a body they're colliding with moves.
+    // Wake up bodies when a body they're colliding with moves or changes its collider,
+    // for example when a voxel is cleared.
     for (entity, mut time_sleeping) in &mut sleeping {
         // Here we could use CollidingEntities, but it'd be empty if the ContactReportingPlugin was disabled.
         let mut colliding_entities = collisions.collisions_with_entity(entity).map(|c| {
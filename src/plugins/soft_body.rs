@@ -0,0 +1,872 @@
+//! Particle-based soft bodies and cloth that are simulated using XPBD constraints.
+//!
+//! See [`SoftBodyPlugin`] and [`SoftBody`].
+
+use crate::prelude::*;
+#[cfg(feature = "soft-body-mesh")]
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::{
+    prelude::*,
+    utils::{intern::Interned, HashMap},
+};
+
+/// Simulates [soft bodies](SoftBody) and cloth made of [particles](Particle).
+///
+/// The particles are integrated, constrained and collided in the [`SubstepSchedule`] alongside rigid bodies:
+///
+/// 1. **Integration**: Gravity and damping are applied to the particles, and they are moved according to
+///    their velocities. Runs in [`SubstepSet::Integrate`].
+/// 2. **Constraint projection**: The [distance](ParticleDistanceConstraint), bending and
+///    [volume](ParticleVolumeConstraint) constraints of each soft body are solved, and particles that
+///    penetrate [colliders](Collider) are pushed out. Runs in [`SubstepSet::SolveConstraints`].
+/// 3. **Velocity update**: The velocities of the particles are updated based on their positional changes.
+///    Runs in [`SubstepSet::UpdateVelocities`].
+///
+/// Particles collide with colliders using the [`SpatialQueryPipeline`], so collisions require the
+/// [`SpatialQueryPlugin`]. The collisions are one-way: colliders push particles, but particles don't
+/// apply forces to rigid bodies.
+///
+/// With the `soft-body-mesh` feature, the plugin also updates the vertices of the `Mesh` of each soft body
+/// based on the positions of its particles at the end of each physics frame.
+pub struct SoftBodyPlugin {
+    #[cfg_attr(not(feature = "soft-body-mesh"), allow(dead_code))]
+    schedule: Interned<dyn ScheduleLabel>,
+}
+
+impl SoftBodyPlugin {
+    /// Creates a [`SoftBodyPlugin`] with the schedule that is used for running the [`PhysicsSchedule`].
+    ///
+    /// The default schedule is `PostUpdate`.
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for SoftBodyPlugin {
+    fn default() -> Self {
+        Self::new(PostUpdate)
+    }
+}
+
+impl Plugin for SoftBodyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SoftBody>()
+            .register_type::<Particle>()
+            .register_type::<ParticleDistanceConstraint>()
+            .register_type::<ParticleVolumeConstraint>();
+
+        let substeps = app
+            .get_schedule_mut(SubstepSchedule)
+            .expect("add SubstepSchedule first");
+
+        substeps.add_systems(integrate_soft_bodies.in_set(SubstepSet::Integrate));
+        substeps.add_systems(solve_soft_bodies.in_set(SubstepSet::SolveConstraints));
+        substeps.add_systems(update_soft_body_velocities.in_set(SubstepSet::UpdateVelocities));
+
+        #[cfg(feature = "soft-body-mesh")]
+        app.add_systems(self.schedule, sync_soft_body_meshes.after(PhysicsSet::Sync));
+    }
+}
+
+/// A point mass that a [`SoftBody`] is made of.
+///
+/// The position and velocity of a particle are in world space.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Particle {
+    /// The position of the particle in world space.
+    pub position: Vector,
+    /// The position of the particle at the start of the current substep.
+    pub previous_position: Vector,
+    /// The velocity of the particle.
+    pub velocity: Vector,
+    /// The inverse of the mass of the particle. Particles with an inverse mass of zero are pinned
+    /// in place and aren't affected by gravity, constraints or collisions.
+    pub inverse_mass: Scalar,
+}
+
+impl Particle {
+    /// Creates a new [`Particle`] at the given position with the given mass.
+    pub fn new(position: Vector, mass: Scalar) -> Self {
+        Self {
+            position,
+            previous_position: position,
+            velocity: Vector::ZERO,
+            inverse_mass: if mass > 0.0 { 1.0 / mass } else { 0.0 },
+        }
+    }
+
+    /// Returns true if the particle is pinned in place.
+    pub fn is_pinned(&self) -> bool {
+        self.inverse_mass <= 0.0
+    }
+}
+
+/// A constraint that keeps two [particles](Particle) of a [`SoftBody`] at a given distance from each other.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleDistanceConstraint {
+    /// The index of the first particle.
+    pub particle1: usize,
+    /// The index of the second particle.
+    pub particle2: usize,
+    /// The distance that the particles are kept at.
+    pub rest_length: Scalar,
+    /// The compliance of the constraint, the inverse of its stiffness.
+    pub compliance: Scalar,
+    /// Lagrange multiplier for the constraint.
+    pub lagrange: Scalar,
+}
+
+impl ParticleDistanceConstraint {
+    /// Creates a new rigid [`ParticleDistanceConstraint`] between the given particles.
+    pub fn new(particle1: usize, particle2: usize, rest_length: Scalar) -> Self {
+        Self {
+            particle1,
+            particle2,
+            rest_length,
+            compliance: 0.0,
+            lagrange: 0.0,
+        }
+    }
+
+    /// Sets the compliance of the constraint, the inverse of its stiffness.
+    pub fn with_compliance(self, compliance: Scalar) -> Self {
+        Self { compliance, ..self }
+    }
+
+    fn solve(&mut self, particles: &mut [Particle], delta_secs: Scalar) {
+        let (i1, i2) = (self.particle1, self.particle2);
+        let w1 = particles[i1].inverse_mass;
+        let w2 = particles[i2].inverse_mass;
+        let w_sum = w1 + w2;
+
+        if w_sum <= Scalar::EPSILON {
+            return;
+        }
+
+        let delta = particles[i1].position - particles[i2].position;
+        let length = delta.length();
+
+        if length <= Scalar::EPSILON {
+            return;
+        }
+
+        let n = delta / length;
+        let c = length - self.rest_length;
+        let tilde_compliance = self.compliance / delta_secs.powi(2);
+        let delta_lagrange = (-c - tilde_compliance * self.lagrange) / (w_sum + tilde_compliance);
+        self.lagrange += delta_lagrange;
+
+        particles[i1].position += n * delta_lagrange * w1;
+        particles[i2].position -= n * delta_lagrange * w2;
+    }
+}
+
+/// A constraint that preserves the
+#[cfg_attr(feature = "2d", doc = "area")]
+#[cfg_attr(feature = "3d", doc = "volume")]
+/// of a [`SoftBody`].
+///
+#[cfg_attr(
+    feature = "2d",
+    doc = "The area is the total signed area of the [triangles](SoftBody::triangles) of the soft body."
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "The volume is the volume enclosed by the [triangles](SoftBody::triangles) of the soft body,
+so the triangles should form a closed surface with a consistent winding order."
+)]
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleVolumeConstraint {
+    /// The
+    #[cfg_attr(feature = "2d", doc = "area")]
+    #[cfg_attr(feature = "3d", doc = "volume")]
+    /// of the soft body at rest.
+    pub rest_volume: Scalar,
+    /// A multiplier for the rest volume. Values above `1.0` inflate the soft body, and values below `1.0` deflate it.
+    pub pressure: Scalar,
+    /// The compliance of the constraint, the inverse of its stiffness.
+    pub compliance: Scalar,
+    /// Lagrange multiplier for the constraint.
+    pub lagrange: Scalar,
+}
+
+impl Default for ParticleVolumeConstraint {
+    fn default() -> Self {
+        Self {
+            rest_volume: 0.0,
+            pressure: 1.0,
+            compliance: 0.0,
+            lagrange: 0.0,
+        }
+    }
+}
+
+impl ParticleVolumeConstraint {
+    fn solve(&mut self, particles: &mut [Particle], triangles: &[[usize; 3]], delta_secs: Scalar) {
+        let mut gradients = vec![Vector::ZERO; particles.len()];
+
+        for &[a, b, c] in triangles {
+            let (gradient_a, gradient_b, gradient_c) = triangle_volume_gradients(
+                particles[a].position,
+                particles[b].position,
+                particles[c].position,
+            );
+            gradients[a] += gradient_a;
+            gradients[b] += gradient_b;
+            gradients[c] += gradient_c;
+        }
+
+        let w_sum: Scalar = particles
+            .iter()
+            .zip(gradients.iter())
+            .map(|(particle, gradient)| particle.inverse_mass * gradient.length_squared())
+            .sum();
+
+        if w_sum <= Scalar::EPSILON {
+            return;
+        }
+
+        let c = enclosed_volume(particles, triangles) - self.pressure * self.rest_volume;
+        let tilde_compliance = self.compliance / delta_secs.powi(2);
+        let delta_lagrange = (-c - tilde_compliance * self.lagrange) / (w_sum + tilde_compliance);
+        self.lagrange += delta_lagrange;
+
+        for (particle, gradient) in particles.iter_mut().zip(gradients.iter()) {
+            particle.position += *gradient * particle.inverse_mass * delta_lagrange;
+        }
+    }
+}
+
+/// A deformable body made of [particles](Particle) that are held together by XPBD constraints.
+/// It can be used for simulating things like cloth, ropes and squishy objects.
+///
+/// A soft body has the following constraints:
+///
+/// - [Distance constraints](Self::distance_constraints) that resist stretching
+/// - [Bending constraints](Self::bending_constraints) that resist bending. They are distance constraints
+/// between the opposite particles of two triangles that share an edge.
+/// - An optional [volume constraint](Self::volume_constraint) that preserves the
+#[cfg_attr(feature = "2d", doc = "area")]
+#[cfg_attr(feature = "3d", doc = "volume")]
+/// of the body
+///
+/// The particles collide with [colliders](Collider) as spheres with the given [radius](Self::particle_radius).
+/// If the entity has [`CollisionLayers`], only colliders in the layers that the soft body interacts with
+/// are taken into account. The [`GravityScale`], [`GravityDirection`] and [`LinearDamping`] components
+/// of the entity also affect the particles.
+///
+/// Soft bodies are typically created from triangles using [`SoftBody::from_triangles`]
+#[cfg_attr(
+    feature = "soft-body-mesh",
+    doc = "or from a `Mesh` using [`SoftBody::from_mesh`]."
+)]
+#[cfg_attr(not(feature = "soft-body-mesh"), doc = ".")]
+/// The positions of the particles are in world space, so the entity doesn't need a rigid body or a `Transform`.
+///
+/// ## Syncing meshes
+///
+/// With the `soft-body-mesh` feature, if the entity has a `Handle<Mesh>`, the vertex positions of the mesh
+/// are updated to match the particles at the end of each physics frame. The vertices are transformed
+/// into the local space of the entity's `GlobalTransform`, so a soft body is usually spawned with the default `Transform`.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::{math::*, prelude::*};")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::{math::*, prelude::*};")]
+///
+/// fn setup(mut commands: Commands) {
+///     // A square made of two triangles
+///     let positions = vec![
+#[cfg_attr(
+    feature = "2d",
+    doc = "        Vector::new(-50.0, -50.0),
+        Vector::new(50.0, -50.0),
+        Vector::new(50.0, 50.0),
+        Vector::new(-50.0, 50.0),"
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "        Vector::new(-0.5, 1.0, -0.5),
+        Vector::new(0.5, 1.0, -0.5),
+        Vector::new(0.5, 1.0, 0.5),
+        Vector::new(-0.5, 1.0, 0.5),"
+)]
+///     ];
+///     let triangles = vec![[0, 1, 2], [0, 2, 3]];
+///
+///     commands.spawn(
+///         SoftBody::from_triangles(positions, triangles)
+///             .with_mass(2.0)
+///             .with_bending_compliance(0.01)
+///             // Pin one of the corners in place
+///             .with_pinned_particles([0]),
+///     );
+/// }
+/// ```
+#[derive(Reflect, Clone, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct SoftBody {
+    /// The particles of the soft body.
+    pub particles: Vec<Particle>,
+    /// Distance constraints between the particles that resist stretching.
+    pub distance_constraints: Vec<ParticleDistanceConstraint>,
+    /// Distance constraints between the opposite particles of adjacent triangles that resist bending.
+    pub bending_constraints: Vec<ParticleDistanceConstraint>,
+    /// A constraint that preserves the
+    #[cfg_attr(feature = "2d", doc = "area")]
+    #[cfg_attr(feature = "3d", doc = "volume")]
+    /// of the soft body.
+    pub volume_constraint: Option<ParticleVolumeConstraint>,
+    /// The triangles of the soft body as indices into [`particles`](Self::particles).
+    pub triangles: Vec<[usize; 3]>,
+    /// The indices of the particles that correspond to the vertices of the soft body's mesh.
+    /// Mesh vertices that share a position are welded into the same particle.
+    pub vertex_particles: Vec<usize>,
+    /// The radius of the particles used for collisions.
+    pub particle_radius: Scalar,
+    /// The friction coefficient used for collisions between the particles and colliders.
+    pub friction: Scalar,
+}
+
+impl Default for SoftBody {
+    fn default() -> Self {
+        Self {
+            particles: vec![],
+            distance_constraints: vec![],
+            bending_constraints: vec![],
+            volume_constraint: None,
+            triangles: vec![],
+            vertex_particles: vec![],
+            #[cfg(feature = "2d")]
+            particle_radius: 1.0,
+            #[cfg(feature = "3d")]
+            particle_radius: 0.02,
+            friction: 0.3,
+        }
+    }
+}
+
+impl SoftBody {
+    /// The default compliance of the bending constraints of new soft bodies.
+    pub const DEFAULT_BENDING_COMPLIANCE: Scalar = 0.001;
+
+    /// Creates a [`SoftBody`] from the given particle positions and triangles, where each triangle
+    /// consists of three indices into `positions`.
+    ///
+    /// A distance constraint is created for each unique edge, and a bending constraint is created
+    /// for each edge that is shared by two triangles. The soft body has a total mass of `1.0`
+    /// that is distributed evenly among the particles.
+    pub fn from_triangles(positions: Vec<Vector>, triangles: Vec<[usize; 3]>) -> Self {
+        let mass = 1.0 / positions.len().max(1) as Scalar;
+        let particles: Vec<Particle> = positions
+            .iter()
+            .map(|position| Particle::new(*position, mass))
+            .collect();
+
+        let mut distance_constraints = vec![];
+        let mut bending_constraints = vec![];
+        // The opposite particle of the first triangle that each edge was found in
+        let mut edges: HashMap<(usize, usize), Option<usize>> = HashMap::default();
+
+        for &[a, b, c] in triangles.iter() {
+            for (i1, i2, opposite) in [(a, b, c), (b, c, a), (c, a, b)] {
+                let edge = (i1.min(i2), i1.max(i2));
+                match edges.get_mut(&edge) {
+                    None => {
+                        edges.insert(edge, Some(opposite));
+                        let rest_length = positions[i1].distance(positions[i2]);
+                        distance_constraints.push(ParticleDistanceConstraint::new(
+                            i1,
+                            i2,
+                            rest_length,
+                        ));
+                    }
+                    Some(first_opposite) => {
+                        // Edges that are shared by more than two triangles are only bent across once
+                        if let Some(first_opposite) = first_opposite.take() {
+                            let rest_length =
+                                positions[first_opposite].distance(positions[opposite]);
+                            bending_constraints.push(
+                                ParticleDistanceConstraint::new(
+                                    first_opposite,
+                                    opposite,
+                                    rest_length,
+                                )
+                                .with_compliance(Self::DEFAULT_BENDING_COMPLIANCE),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            vertex_particles: (0..particles.len()).collect(),
+            particles,
+            distance_constraints,
+            bending_constraints,
+            triangles,
+            ..default()
+        }
+    }
+
+    /// Creates a [`SoftBody`] from the vertices and triangles of the given `Mesh`.
+    /// Returns `None` if the mesh has no vertex positions.
+    ///
+    /// Vertices that share a position, like the vertices at the seams of UV mapped meshes, are welded
+    /// into the same particle. The mesh uses the same constraints as [`SoftBody::from_triangles`].
+    #[cfg_attr(feature = "2d", doc = "")]
+    #[cfg_attr(
+        feature = "2d",
+        doc = "In 2D, the Z coordinates of the vertices are ignored."
+    )]
+    #[cfg(feature = "soft-body-mesh")]
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        let Some(VertexAttributeValues::Float32x3(vertices)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+
+        let mut positions = vec![];
+        let mut vertex_particles = Vec::with_capacity(vertices.len());
+        let mut welded: HashMap<[i64; 3], usize> = HashMap::default();
+
+        for vertex in vertices.iter() {
+            // Quantize the positions so that vertices with tiny floating point differences are welded
+            let quantize = |coordinate: f32| (coordinate as f64 * 1.0e5).round() as i64;
+            #[cfg(feature = "2d")]
+            let key = [quantize(vertex[0]), quantize(vertex[1]), 0];
+            #[cfg(feature = "3d")]
+            let key = vertex.map(quantize);
+
+            let particle = *welded.entry(key).or_insert_with(|| {
+                #[cfg(feature = "2d")]
+                positions.push(Vec2::new(vertex[0], vertex[1]).adjust_precision());
+                #[cfg(feature = "3d")]
+                positions.push(Vec3::from(*vertex).adjust_precision());
+                positions.len() - 1
+            });
+            vertex_particles.push(particle);
+        }
+
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(indices) => indices.iter().collect(),
+            None => (0..vertices.len()).collect(),
+        };
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|triangle| triangle.iter().map(|&i| vertex_particles[i]))
+            .map(|mut triangle| {
+                [
+                    triangle.next().unwrap(),
+                    triangle.next().unwrap(),
+                    triangle.next().unwrap(),
+                ]
+            })
+            .filter(|[a, b, c]| a != b && b != c && c != a)
+            .collect();
+
+        Some(Self {
+            vertex_particles,
+            ..Self::from_triangles(positions, triangles)
+        })
+    }
+
+    /// Applies the given transform to the positions of the particles.
+    ///
+    /// The rest lengths of the constraints and the rest
+    #[cfg_attr(feature = "2d", doc = "area")]
+    #[cfg_attr(feature = "3d", doc = "volume")]
+    /// are recomputed from the transformed positions.
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        for particle in self.particles.iter_mut() {
+            #[cfg(feature = "2d")]
+            let position = transform
+                .transform_point(particle.position.as_f32().extend(0.0))
+                .truncate()
+                .adjust_precision();
+            #[cfg(feature = "3d")]
+            let position = transform
+                .transform_point(particle.position.as_f32())
+                .adjust_precision();
+            particle.position = position;
+            particle.previous_position = position;
+        }
+
+        for constraint in self
+            .distance_constraints
+            .iter_mut()
+            .chain(self.bending_constraints.iter_mut())
+        {
+            constraint.rest_length = self.particles[constraint.particle1]
+                .position
+                .distance(self.particles[constraint.particle2].position);
+        }
+
+        let volume = self.volume();
+        if let Some(constraint) = &mut self.volume_constraint {
+            constraint.rest_volume = volume;
+        }
+
+        self
+    }
+
+    /// Sets the total mass of the soft body, distributing it evenly among the particles that aren't pinned.
+    pub fn with_mass(mut self, mass: Scalar) -> Self {
+        let count = self.particles.iter().filter(|p| !p.is_pinned()).count();
+        let inverse_mass = count as Scalar / mass;
+        for particle in self.particles.iter_mut().filter(|p| !p.is_pinned()) {
+            particle.inverse_mass = inverse_mass;
+        }
+        self
+    }
+
+    /// Pins the particles with the given indices in place by setting their inverse mass to zero.
+    pub fn with_pinned_particles(mut self, indices: impl IntoIterator<Item = usize>) -> Self {
+        for index in indices {
+            self.particles[index].inverse_mass = 0.0;
+        }
+        self
+    }
+
+    /// Sets the compliance of the distance constraints that resist stretching.
+    pub fn with_stretch_compliance(mut self, compliance: Scalar) -> Self {
+        for constraint in self.distance_constraints.iter_mut() {
+            constraint.compliance = compliance;
+        }
+        self
+    }
+
+    /// Sets the compliance of the bending constraints.
+    pub fn with_bending_compliance(mut self, compliance: Scalar) -> Self {
+        for constraint in self.bending_constraints.iter_mut() {
+            constraint.compliance = compliance;
+        }
+        self
+    }
+
+    /// Preserves the current
+    #[cfg_attr(feature = "2d", doc = "area")]
+    #[cfg_attr(feature = "3d", doc = "volume")]
+    /// of the soft body using a volume constraint with the given compliance.
+    pub fn with_volume_preservation(mut self, compliance: Scalar) -> Self {
+        self.volume_constraint = Some(ParticleVolumeConstraint {
+            rest_volume: self.volume(),
+            compliance,
+            ..default()
+        });
+        self
+    }
+
+    /// Sets the pressure of the volume constraint, a multiplier for the rest
+    #[cfg_attr(feature = "2d", doc = "area.")]
+    #[cfg_attr(feature = "3d", doc = "volume.")]
+    /// If the soft body doesn't have a volume constraint, a rigid one is added.
+    pub fn with_pressure(mut self, pressure: Scalar) -> Self {
+        if self.volume_constraint.is_none() {
+            self = self.with_volume_preservation(0.0);
+        }
+        if let Some(constraint) = &mut self.volume_constraint {
+            constraint.pressure = pressure;
+        }
+        self
+    }
+
+    /// Sets the radius of the particles used for collisions.
+    pub fn with_particle_radius(self, radius: Scalar) -> Self {
+        Self {
+            particle_radius: radius,
+            ..self
+        }
+    }
+
+    /// Sets the friction coefficient used for collisions between the particles and colliders.
+    pub fn with_friction(self, friction: Scalar) -> Self {
+        Self { friction, ..self }
+    }
+
+    /// Computes the current
+    #[cfg_attr(feature = "2d", doc = "area")]
+    #[cfg_attr(feature = "3d", doc = "volume")]
+    /// of the soft body from its [triangles](Self::triangles).
+    pub fn volume(&self) -> Scalar {
+        enclosed_volume(&self.particles, &self.triangles)
+    }
+}
+
+/// Computes the signed area of the given triangles in 2D or the signed volume enclosed by them in 3D.
+fn enclosed_volume(particles: &[Particle], triangles: &[[usize; 3]]) -> Scalar {
+    triangles
+        .iter()
+        .map(|&[a, b, c]| {
+            let (a, b, c) = (
+                particles[a].position,
+                particles[b].position,
+                particles[c].position,
+            );
+            #[cfg(feature = "2d")]
+            {
+                0.5 * (b - a).perp_dot(c - a)
+            }
+            #[cfg(feature = "3d")]
+            {
+                a.dot(b.cross(c)) / 6.0
+            }
+        })
+        .sum()
+}
+
+/// Computes the gradients of the contribution of a triangle to [`enclosed_volume`] with respect to its vertices.
+fn triangle_volume_gradients(a: Vector, b: Vector, c: Vector) -> (Vector, Vector, Vector) {
+    #[cfg(feature = "2d")]
+    {
+        (
+            0.5 * (c - b).perp(),
+            0.5 * (a - c).perp(),
+            0.5 * (b - a).perp(),
+        )
+    }
+    #[cfg(feature = "3d")]
+    {
+        (b.cross(c) / 6.0, c.cross(a) / 6.0, a.cross(b) / 6.0)
+    }
+}
+
+/// Applies gravity and damping to the particles of soft bodies and moves them according to their velocities.
+#[allow(clippy::type_complexity)]
+fn integrate_soft_bodies(
+    mut bodies: Query<(
+        &mut SoftBody,
+        Option<&GravityScale>,
+        Option<&GravityDirection>,
+        Option<&LinearDamping>,
+    )>,
+    gravity: Res<Gravity>,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_seconds_adjusted();
+
+    for (mut soft_body, gravity_scale, gravity_direction, linear_damping) in &mut bodies {
+        let gravity = gravity_direction.map_or(gravity.0, |direction| {
+            direction.0.normalize_or_zero() * gravity.0.length()
+        }) * gravity_scale.map_or(1.0, |scale| scale.0);
+        let damping = 1.0 / (1.0 + delta_secs * linear_damping.map_or(0.0, |damping| damping.0));
+
+        let soft_body = soft_body.as_mut();
+
+        for particle in soft_body.particles.iter_mut() {
+            particle.previous_position = particle.position;
+
+            if particle.is_pinned() {
+                continue;
+            }
+
+            particle.velocity = (particle.velocity + gravity * delta_secs) * damping;
+            particle.position += particle.velocity * delta_secs;
+        }
+
+        // Reset the Lagrange multipliers at the start of each substep
+        for constraint in soft_body
+            .distance_constraints
+            .iter_mut()
+            .chain(soft_body.bending_constraints.iter_mut())
+        {
+            constraint.lagrange = 0.0;
+        }
+        if let Some(constraint) = &mut soft_body.volume_constraint {
+            constraint.lagrange = 0.0;
+        }
+    }
+}
+
+/// Solves the constraints of soft bodies and pushes their particles out of colliders.
+fn solve_soft_bodies(
+    mut bodies: Query<(&mut SoftBody, Option<&CollisionLayers>)>,
+    spatial_query_pipeline: Option<Res<SpatialQueryPipeline>>,
+    time: Res<Time>,
+    mut warned_no_pipeline: Local<bool>,
+) {
+    let delta_secs = time.delta_seconds_adjusted();
+    let spatial_query_pipeline = pipeline_or_warn_once(
+        &spatial_query_pipeline,
+        &mut warned_no_pipeline,
+        "Collisions of `SoftBody` particles",
+    );
+
+    for (mut soft_body, layers) in &mut bodies {
+        let SoftBody {
+            particles,
+            distance_constraints,
+            bending_constraints,
+            volume_constraint,
+            triangles,
+            particle_radius,
+            friction,
+            ..
+        } = soft_body.as_mut();
+
+        for constraint in distance_constraints.iter_mut() {
+            constraint.solve(particles, delta_secs);
+        }
+        for constraint in bending_constraints.iter_mut() {
+            constraint.solve(particles, delta_secs);
+        }
+        if let Some(constraint) = volume_constraint {
+            constraint.solve(particles, triangles, delta_secs);
+        }
+
+        let Some(spatial_query_pipeline) = spatial_query_pipeline else {
+            continue;
+        };
+        let filter = layers.map_or(SpatialQueryFilter::default(), |layers| {
+            SpatialQueryFilter::new().with_masks_from_bits(layers.masks_bits())
+        });
+
+        for particle in particles.iter_mut().filter(|p| !p.is_pinned()) {
+            solve_particle_collision(
+                particle,
+                *particle_radius,
+                *friction,
+                spatial_query_pipeline,
+                &filter,
+            );
+        }
+    }
+}
+
+/// Pushes a particle out of the closest collider and applies friction to its tangential movement.
+pub(crate) fn solve_particle_collision(
+    particle: &mut Particle,
+    radius: Scalar,
+    friction: Scalar,
+    spatial_query_pipeline: &SpatialQueryPipeline,
+    filter: &SpatialQueryFilter,
+) {
+    let Some(projection) =
+        spatial_query_pipeline.project_point(particle.position, false, filter.clone())
+    else {
+        return;
+    };
+
+    let offset = particle.position - projection.point;
+    let distance = offset.length();
+
+    if distance <= Scalar::EPSILON || (!projection.is_inside && distance >= radius) {
+        return;
+    }
+
+    // The normal points out of the collider
+    let (normal, penetration) = if projection.is_inside {
+        (-offset / distance, distance + radius)
+    } else {
+        (offset / distance, radius - distance)
+    };
+
+    particle.position += normal * penetration;
+
+    // Cancel tangential movement up to a limit that depends on the penetration depth
+    let displacement = particle.position - particle.previous_position;
+    let tangential = displacement - normal * displacement.dot(normal);
+    let tangential_length = tangential.length();
+
+    if tangential_length <= Scalar::EPSILON {
+        return;
+    }
+
+    let max_correction = friction * penetration;
+    if tangential_length <= max_correction {
+        particle.position -= tangential;
+    } else {
+        particle.position -= tangential * (max_correction / tangential_length);
+    }
+}
+
+/// Updates the velocities of the particles of soft bodies based on their positional changes.
+fn update_soft_body_velocities(mut bodies: Query<&mut SoftBody>, time: Res<Time>) {
+    let delta_secs = time.delta_seconds_adjusted();
+
+    if delta_secs == 0.0 {
+        return;
+    }
+
+    for mut soft_body in &mut bodies {
+        for particle in soft_body.particles.iter_mut() {
+            particle.velocity = (particle.position - particle.previous_position) / delta_secs;
+        }
+    }
+}
+
+/// Updates the vertex positions and normals of the meshes of soft bodies based on the positions of their particles.
+#[cfg(feature = "soft-body-mesh")]
+#[allow(clippy::type_complexity)]
+fn sync_soft_body_meshes(
+    bodies: Query<(&SoftBody, &Handle<Mesh>, Option<&GlobalTransform>), Changed<SoftBody>>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+) {
+    let Some(mut meshes) = meshes else {
+        return;
+    };
+
+    for (soft_body, handle, global_transform) in &bodies {
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+
+        let global_transform = global_transform.copied().unwrap_or_default();
+        let world_to_local = global_transform.affine().inverse();
+
+        if let Some(VertexAttributeValues::Float32x3(vertices)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        {
+            for (vertex, &particle) in vertices.iter_mut().zip(soft_body.vertex_particles.iter()) {
+                let position = soft_body.particles[particle].position.as_f32();
+                #[cfg(feature = "2d")]
+                {
+                    let local = world_to_local
+                        .transform_point3(position.extend(global_transform.translation().z));
+                    vertex[0] = local.x;
+                    vertex[1] = local.y;
+                }
+                #[cfg(feature = "3d")]
+                {
+                    *vertex = world_to_local.transform_point3(position).to_array();
+                }
+            }
+        }
+
+        #[cfg(feature = "3d")]
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+        {
+            // Compute smooth area-weighted normals for the particles
+            let mut particle_normals = vec![Vector::ZERO; soft_body.particles.len()];
+            for &[a, b, c] in soft_body.triangles.iter() {
+                let (pa, pb, pc) = (
+                    soft_body.particles[a].position,
+                    soft_body.particles[b].position,
+                    soft_body.particles[c].position,
+                );
+                let normal = (pb - pa).cross(pc - pa);
+                particle_normals[a] += normal;
+                particle_normals[b] += normal;
+                particle_normals[c] += normal;
+            }
+
+            for (normal, &particle) in normals.iter_mut().zip(soft_body.vertex_particles.iter()) {
+                *normal = world_to_local
+                    .transform_vector3(particle_normals[particle].as_f32())
+                    .normalize_or_zero()
+                    .to_array();
+            }
+        }
+    }
+}
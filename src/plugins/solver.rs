@@ -13,6 +13,9 @@ use bevy::{
 };
 use constraints::penetration::PenetrationConstraint;
 
+#[cfg(feature = "physics-material")]
+use super::physics_material::PhysicsMaterials;
+
 /// Solves positional and angular [constraints], updates velocities and solves velocity constraints
 /// (dynamic [friction](Friction) and [restitution](Restitution) and [joint damping](joints#damping)).
 ///
@@ -32,11 +35,39 @@ use constraints::penetration::PenetrationConstraint;
 /// In the case of collisions, [`PenetrationConstraint`]s are created for each contact pair.
 /// The constraints are resolved by moving the bodies so that they no longer penetrate.
 /// Then, the velocities are updated, and velocity corrections caused by dynamic friction and restitution are applied.
+///
+/// Contacts are matched across substeps using their [feature IDs](ContactData::feature_id1).
+/// The penetration constraints of matching contacts are warm started with the Lagrange multipliers
+/// of the previous substep, and static friction holds the contacts at their [anchors](ContactData::friction_anchors).
+/// This helps stacks of bodies settle and keeps them from creeping without needing more substeps.
+///
+/// After the substeps, joints whose [`BreakForce`] or [`BreakTorque`] has been exceeded are removed,
+/// and [`JointBroken`] events are sent for them.
 pub struct SolverPlugin;
 
 impl Plugin for SolverPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<PenetrationConstraints>();
+        app.init_resource::<PenetrationConstraints>()
+            .init_resource::<ContactVelocityImpulses>()
+            .add_event::<JointBroken>()
+            .register_type::<BreakForce>()
+            .register_type::<BreakTorque>();
+
+        app.get_schedule_mut(PhysicsSchedule)
+            .expect("add PhysicsSchedule first")
+            .add_systems(
+                (
+                    break_joints::<FixedJoint>,
+                    break_joints::<RevoluteJoint>,
+                    break_joints::<SphericalJoint>,
+                    break_joints::<PrismaticJoint>,
+                    break_joints::<DistanceJoint>,
+                    store_contact_velocity_impulses,
+                )
+                    .chain()
+                    .after(PhysicsStepSet::Substeps)
+                    .before(PhysicsStepSet::ReportContacts),
+            );
 
         let substeps = app
             .get_schedule_mut(SubstepSchedule)
@@ -74,11 +105,21 @@ impl Plugin for SolverPlugin {
     }
 }
 
+/// The fraction of the Lagrange multipliers of contacts from the previous substep that is used
+/// for warm starting the penetration constraints. A value slightly below `1.0` reduces jitter,
+/// as the contact points and normals change between substeps.
+const WARM_START_COEFFICIENT: Scalar = 0.9;
+
 /// Stores penetration constraints for colliding entity pairs.
 #[derive(Resource, Debug, Default)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct PenetrationConstraints(pub Vec<PenetrationConstraint>);
 
+/// The impulses applied by dynamic friction and restitution to each colliding entity pair during the current frame.
+/// They are added to the total impulses of the [`Contacts`] once the substeps are done.
+#[derive(Resource, Debug, Default)]
+struct ContactVelocityImpulses(Vec<((Entity, Entity), Vector, Vector)>);
+
 /// A [`WorldQuery`] to make code handling colliders in collisions cleaner.
 #[derive(WorldQuery)]
 struct ColliderQuery<'w> {
@@ -88,6 +129,7 @@ struct ColliderQuery<'w> {
     is_sensor: Has<Sensor>,
     friction: Option<&'w Friction>,
     restitution: Option<&'w Restitution>,
+    surface_velocity: Option<&'w SurfaceVelocity>,
 }
 
 /// Iterates through broad phase collision pairs, checks which ones are actually colliding, and uses [`PenetrationConstraint`]s to resolve the collisions.
@@ -105,6 +147,7 @@ fn penetration_constraints(
     mut penetration_constraints: ResMut<PenetrationConstraints>,
     mut collisions: ResMut<Collisions>,
     time: Res<Time>,
+    #[cfg(feature = "physics-material")] physics_materials: PhysicsMaterials,
 ) {
     let delta_secs = time.delta_seconds_adjusted();
 
@@ -161,47 +204,129 @@ fn penetration_constraints(
                 commands.entity(body2.entity).remove::<Sleeping>();
             }
 
-            // Get combined friction and restitution coefficients of the colliders
-            // or the bodies they are attached to.
-            let friction = collider1
-                .friction
-                .unwrap_or(body1.friction)
-                .combine(*collider2.friction.unwrap_or(body2.friction));
-            let restitution_coefficient = collider1
-                .restitution
-                .unwrap_or(body1.restitution)
-                .combine(*collider2.restitution.unwrap_or(body2.restitution))
-                .coefficient;
+            // Get the friction and restitution of the colliders or the bodies they are attached to.
+            let friction1 = *collider1.friction.unwrap_or(body1.friction);
+            let friction2 = *collider2.friction.unwrap_or(body2.friction);
+            let restitution1 = *collider1.restitution.unwrap_or(body1.restitution);
+            let restitution2 = *collider2.restitution.unwrap_or(body2.restitution);
+
+            // Physics materials override the friction and restitution of the colliders.
+            #[cfg(feature = "physics-material")]
+            let ((friction1, restitution1), (friction2, restitution2)) = (
+                physics_materials
+                    .get(collider1.entity)
+                    .map_or((friction1, restitution1), |material| {
+                        (material.friction, material.restitution)
+                    }),
+                physics_materials
+                    .get(collider2.entity)
+                    .map_or((friction2, restitution2), |material| {
+                        (material.friction, material.restitution)
+                    }),
+            );
+
+            // Get combined friction and restitution coefficients.
+            let friction = friction1.combine(friction2);
+            let restitution_coefficient = restitution1.combine(restitution2).coefficient;
+
+            // Add collider transforms to local contact points
+            let transform_point1 = |point: Vector| {
+                collider1
+                    .transform
+                    .map_or(point, |t| t.rotation.rotate(point) + t.translation)
+            };
+            let transform_point2 = |point: Vector| {
+                collider2
+                    .transform
+                    .map_or(point, |t| t.rotation.rotate(point) + t.translation)
+            };
+
+            // Get the world-space surface velocities of the colliders.
+            // The first entity should move relative to the second one at the difference of the velocities.
+            let surface_velocity1 = collider1.surface_velocity.map_or(Vector::ZERO, |velocity| {
+                body1.rotation.rotate(
+                    collider1
+                        .transform
+                        .map_or(velocity.0, |t| t.rotation.rotate(velocity.0)),
+                )
+            });
+            let surface_velocity2 = collider2.surface_velocity.map_or(Vector::ZERO, |velocity| {
+                body2.rotation.rotate(
+                    collider2
+                        .transform
+                        .map_or(velocity.0, |t| t.rotation.rotate(velocity.0)),
+                )
+            });
+            let surface_tangent_velocity = surface_velocity2 - surface_velocity1;
+
+            // The impulses applied by the constraints during this substep
+            let mut normal_impulse = Vector::ZERO;
+            let mut friction_impulse = Vector::ZERO;
 
             // Create and solve penetration constraints for each contact.
-            for contact_manifold in contacts.manifolds.iter() {
-                for contact in contact_manifold.contacts.iter() {
-                    // Add collider transforms to local contact points
-                    let contact = ContactData {
-                        point1: collider1.transform.map_or(contact.point1, |t| {
-                            t.rotation.rotate(contact.point1) + t.translation
-                        }),
-                        point2: collider2.transform.map_or(contact.point2, |t| {
-                            t.rotation.rotate(contact.point2) + t.translation
-                        }),
-                        normal1: collider1
-                            .transform
-                            .map_or(contact.normal1, |t| t.rotation.rotate(contact.normal1)),
-                        normal2: collider2
-                            .transform
-                            .map_or(contact.normal2, |t| t.rotation.rotate(contact.normal2)),
-                        ..*contact
-                    };
+            for contact_manifold in contacts.manifolds.iter_mut() {
+                for contact in contact_manifold.contacts.iter_mut() {
+                    // Disabled contacts are not solved or warm started
+                    if !contact.enabled {
+                        contact.normal_lagrange = 0.0;
+                        contact.friction_anchors = None;
+                        continue;
+                    }
+
+                    // Contacts can override the friction and restitution of the colliders
+                    let friction = contact.friction.unwrap_or(friction);
+                    let restitution_coefficient = contact
+                        .restitution
+                        .map_or(restitution_coefficient, |restitution| {
+                            restitution.coefficient
+                        });
 
                     let mut constraint = PenetrationConstraint {
                         dynamic_friction_coefficient: friction.dynamic_coefficient,
                         static_friction_coefficient: friction.static_coefficient,
                         restitution_coefficient,
-                        ..PenetrationConstraint::new(&body1, &body2, contact)
+                        compliance: contact.compliance,
+                        ..PenetrationConstraint::new_with_colliders(
+                            &body1,
+                            &body2,
+                            collider1.entity,
+                            collider2.entity,
+                            ContactData {
+                                point1: transform_point1(contact.point1),
+                                point2: transform_point2(contact.point2),
+                                normal1: collider1.transform.map_or(contact.normal1, |t| {
+                                    t.rotation.rotate(contact.normal1)
+                                }),
+                                normal2: collider2.transform.map_or(contact.normal2, |t| {
+                                    t.rotation.rotate(contact.normal2)
+                                }),
+                                friction_anchors: contact.friction_anchors.map(
+                                    |(anchor1, anchor2)| {
+                                        (transform_point1(anchor1), transform_point2(anchor2))
+                                    },
+                                ),
+                                normal_lagrange: contact.normal_lagrange * WARM_START_COEFFICIENT,
+                                tangent_velocity: contact.tangent_velocity
+                                    + surface_tangent_velocity,
+                                ..*contact
+                            },
+                        )
                     };
                     constraint.solve([&mut body1, &mut body2], delta_secs);
                     penetration_constraints.0.push(constraint);
 
+                    // The impulse of a positional correction is the force multiplied by the substep time
+                    normal_impulse += constraint.normal_force * delta_secs;
+                    friction_impulse += constraint.static_friction_force * delta_secs;
+
+                    // Store the Lagrange multiplier and friction anchors for warm starting the next substep
+                    contact.normal_lagrange = constraint.normal_lagrange;
+                    contact.friction_anchors = constraint.contact.friction_anchors.map(|_| {
+                        contact
+                            .friction_anchors
+                            .unwrap_or((contact.point1, contact.point2))
+                    });
+
                     // Set collision as penetrating for this frame and substep.
                     // This is used for detecting when the collision has started or ended.
                     if contact.penetration > Scalar::EPSILON {
@@ -211,6 +336,9 @@ fn penetration_constraints(
                 }
             }
 
+            contacts.total_normal_impulse += normal_impulse;
+            contacts.total_friction_impulse += friction_impulse;
+
             if contacts.during_current_substep && body1.rb.is_added() || body2.rb.is_added() {
                 // if the RigidBody entity has a name, use that for debug.
                 let debug_id1 = match name1 {
@@ -414,11 +542,13 @@ fn update_ang_vel(
     }
 }
 
-/// Applies velocity corrections caused by dynamic friction and restitution.
+/// Applies velocity corrections caused by dynamic friction and restitution,
+/// and accumulates their impulses in [`ContactVelocityImpulses`].
 #[allow(clippy::type_complexity)]
 fn solve_vel(
     mut bodies: Query<RigidBodyQuery, Without<Sleeping>>,
     penetration_constraints: Res<PenetrationConstraints>,
+    mut velocity_impulses: ResMut<ContactVelocityImpulses>,
     gravity: Res<Gravity>,
     time: Res<Time>,
 ) {
@@ -460,8 +590,11 @@ fn solve_vel(
                 compute_contact_vel(body2.linear_velocity.0, body2.angular_velocity.0, r2);
             let relative_vel = contact_vel1 - contact_vel2;
 
+            // The relative tangential velocity is driven towards the target velocity of the contact
+            let target_tangent_vel = constraint.contact.tangent_velocity
+                - normal * normal.dot(constraint.contact.tangent_velocity);
             let normal_speed = normal.dot(relative_vel);
-            let tangent_vel = relative_vel - normal * normal_speed;
+            let tangent_vel = relative_vel - normal * normal_speed - target_tangent_vel;
             let tangent_speed = tangent_vel.length();
 
             let inv_mass1 = body1.effective_inv_mass();
@@ -469,7 +602,8 @@ fn solve_vel(
             let inv_inertia1 = body1.effective_world_inv_inertia();
             let inv_inertia2 = body2.effective_world_inv_inertia();
 
-            let mut p = Vector::ZERO;
+            let mut restitution_impulse = Vector::ZERO;
+            let mut friction_impulse = Vector::ZERO;
 
             // Compute restitution
             let restitution_speed = compute_restitution(
@@ -482,7 +616,7 @@ fn solve_vel(
             if restitution_speed.abs() > Scalar::EPSILON {
                 let w1 = constraint.compute_generalized_inverse_mass(&body1, r1, normal);
                 let w2 = constraint.compute_generalized_inverse_mass(&body2, r2, normal);
-                p += restitution_speed / (w1 + w2) * normal;
+                restitution_impulse = restitution_speed / (w1 + w2) * normal;
             }
 
             // Compute dynamic friction
@@ -490,14 +624,28 @@ fn solve_vel(
                 let tangent_dir = tangent_vel / tangent_speed;
                 let w1 = constraint.compute_generalized_inverse_mass(&body1, r1, tangent_dir);
                 let w2 = constraint.compute_generalized_inverse_mass(&body2, r2, tangent_dir);
-                let friction_impulse = compute_dynamic_friction(
+                friction_impulse = compute_dynamic_friction(
                     tangent_speed,
                     w1 + w2,
                     constraint.dynamic_friction_coefficient,
                     constraint.normal_lagrange,
                     delta_secs,
-                );
-                p += friction_impulse * tangent_dir;
+                ) * tangent_dir;
+            }
+
+            let p = restitution_impulse + friction_impulse;
+
+            // The constraints of each collision pair are stored next to each other,
+            // so their impulses can be summed up without looking up the pair.
+            let pair = (constraint.collider_entity1, constraint.collider_entity2);
+            match velocity_impulses.0.last_mut() {
+                Some((last_pair, normal, friction)) if *last_pair == pair => {
+                    *normal += restitution_impulse;
+                    *friction += friction_impulse;
+                }
+                _ => velocity_impulses
+                    .0
+                    .push((pair, restitution_impulse, friction_impulse)),
             }
 
             if body1.rb.is_dynamic() && body1.dominance() <= body2.dominance() {
@@ -526,6 +674,34 @@ fn solve_vel(
     }
 }
 
+/// Adds the impulses accumulated in [`ContactVelocityImpulses`] during the substeps
+/// to the total impulses of the [`Contacts`].
+fn store_contact_velocity_impulses(
+    mut collisions: ResMut<Collisions>,
+    mut velocity_impulses: ResMut<ContactVelocityImpulses>,
+) {
+    // Merge the impulses of each pair from all substeps so that each pair is only looked up once
+    velocity_impulses.0.sort_unstable_by_key(|(pair, ..)| *pair);
+    velocity_impulses.0.dedup_by(
+        |(pair, normal, friction), (prev_pair, prev_normal, prev_friction)| {
+            if pair == prev_pair {
+                *prev_normal += *normal;
+                *prev_friction += *friction;
+                true
+            } else {
+                false
+            }
+        },
+    );
+
+    for (pair, normal_impulse, friction_impulse) in velocity_impulses.0.drain(..) {
+        if let Some(contacts) = collisions.get_internal_mut().get_mut(&pair) {
+            contacts.total_normal_impulse += normal_impulse;
+            contacts.total_friction_impulse += friction_impulse;
+        }
+    }
+}
+
 /// Applies velocity corrections caused by joint damping.
 #[allow(clippy::type_complexity)]
 pub fn joint_damping<T: Joint>(
@@ -584,6 +760,44 @@ pub fn joint_damping<T: Joint>(
     }
 }
 
+/// Removes joints of type `T` whose [`BreakForce`] or [`BreakTorque`] has been exceeded
+/// and sends [`JointBroken`] events for them.
+///
+/// This is added automatically for the built-in joints. For custom joints, add it to the [`PhysicsSchedule`]
+/// after [`PhysicsStepSet::Substeps`].
+#[allow(clippy::type_complexity)]
+pub fn break_joints<T: Joint>(
+    mut commands: Commands,
+    joints: Query<
+        (Entity, &T, Option<&BreakForce>, Option<&BreakTorque>),
+        Or<(With<BreakForce>, With<BreakTorque>)>,
+    >,
+    mut joint_broken: EventWriter<JointBroken>,
+) {
+    for (entity, joint, break_force, break_torque) in &joints {
+        let force = joint.force();
+        let torque = joint.torque();
+
+        #[cfg(feature = "2d")]
+        let torque_magnitude = torque.abs();
+        #[cfg(feature = "3d")]
+        let torque_magnitude = torque.length();
+
+        let force_exceeded = break_force.is_some_and(|max| force.length() > max.0);
+        let torque_exceeded = break_torque.is_some_and(|max| torque_magnitude > max.0);
+
+        if force_exceeded || torque_exceeded {
+            commands.entity(entity).remove::<T>();
+            joint_broken.send(JointBroken {
+                joint_entity: entity,
+                entities: joint.entities(),
+                force,
+                torque,
+            });
+        }
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn apply_translation(
     mut bodies: Query<
@@ -145,6 +145,12 @@
 //! See the documentation of the components and methods for more information.
 //!
 //! To specify which colliders should be considered in the query, use a [spatial query filter](`SpatialQueryFilter`).
+//!
+//! ## Custom shapes
+//!
+//! Queries between two shapes, like shapecasting and shape intersections, use the [`PhysicsQueryDispatcher`],
+//! so they support custom shapes handled by a custom dispatcher. Raycasting and point projection
+//! use the implementations of the shapes themselves.
 
 mod pipeline;
 mod query_filter;
@@ -158,7 +164,7 @@ pub use ray_caster::*;
 pub use shape_caster::*;
 pub use system_param::*;
 
-use crate::prelude::*;
+use crate::{plugins::collision::broad_phase::AabbTree, prelude::*};
 use bevy::{prelude::*, utils::intern::Interned};
 
 /// Initializes the [`SpatialQueryPipeline`] resource and handles component-based [spatial queries](spatial_query)
@@ -187,10 +193,12 @@ impl Default for SpatialQueryPlugin {
 
 impl Plugin for SpatialQueryPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<SpatialQueryPipeline>().add_systems(
-            self.schedule,
-            (init_ray_hits, init_shape_hit).in_set(PrepareSet::Init),
-        );
+        app.init_resource::<SpatialQueryPipeline>()
+            .init_resource::<PhysicsQueryDispatcher>()
+            .add_systems(
+                self.schedule,
+                (init_ray_hits, init_shape_hit).in_set(PrepareSet::Init),
+            );
 
         let physics_schedule = app
             .get_schedule_mut(PhysicsSchedule)
@@ -200,7 +208,8 @@ impl Plugin for SpatialQueryPlugin {
             (
                 update_ray_caster_positions,
                 update_shape_caster_positions,
-                |mut spatial_query: SpatialQuery| spatial_query.update_pipeline(),
+                update_query_dispatcher,
+                update_query_pipeline,
                 raycast,
                 shapecast,
             )
@@ -210,6 +219,31 @@ impl Plugin for SpatialQueryPlugin {
     }
 }
 
+/// Uses the [`PhysicsQueryDispatcher`] for the queries of the [`SpatialQueryPipeline`] when it is changed.
+fn update_query_dispatcher(
+    mut pipeline: ResMut<SpatialQueryPipeline>,
+    dispatcher: Res<PhysicsQueryDispatcher>,
+) {
+    if dispatcher.is_changed() {
+        pipeline.set_dispatcher(dispatcher.0.clone());
+    }
+}
+
+/// Updates the [`SpatialQueryPipeline`]. With [`BroadPhaseAlgorithm::DynamicAabbTree`], the `Qbvh` of the
+/// pipeline is updated incrementally using the enlarged AABBs of the broad phase's tree instead of being rebuilt.
+fn update_query_pipeline(
+    mut spatial_query: SpatialQuery,
+    algorithm: Option<Res<BroadPhaseAlgorithm>>,
+    aabb_tree: Option<Res<AabbTree>>,
+) {
+    match (algorithm.as_deref(), aabb_tree) {
+        (Some(BroadPhaseAlgorithm::DynamicAabbTree), Some(aabb_tree)) => {
+            spatial_query.update_pipeline_incremental(|entity| aabb_tree.leaf_aabb(entity))
+        }
+        _ => spatial_query.update_pipeline(),
+    }
+}
+
 fn init_ray_hits(mut commands: Commands, rays: Query<(Entity, &RayCaster), Added<RayCaster>>) {
     for (entity, ray) in &rays {
         let max_hits = if ray.max_hits == u32::MAX {
@@ -385,3 +419,17 @@ fn shapecast(
         }
     }
 }
+
+/// Returns the [`SpatialQueryPipeline`] if it exists. If it doesn't, because the [`SpatialQueryPlugin`]
+/// hasn't been added, a warning about the `feature` that doesn't work without it is logged once per `warned` flag.
+pub(crate) fn pipeline_or_warn_once<'a>(
+    pipeline: &'a Option<Res<SpatialQueryPipeline>>,
+    warned: &mut bool,
+    feature: &str,
+) -> Option<&'a SpatialQueryPipeline> {
+    if pipeline.is_none() && !*warned {
+        warn!("{feature} requires the `SpatialQueryPlugin`, but it hasn't been added");
+        *warned = true;
+    }
+    pipeline.as_deref()
+}
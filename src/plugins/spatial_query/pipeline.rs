@@ -3,7 +3,8 @@ use std::sync::Arc;
 use crate::prelude::*;
 use bevy::{prelude::*, utils::HashMap};
 use parry::{
-    partitioning::Qbvh,
+    bounding_volume::{Aabb, BoundingVolume},
+    partitioning::{Qbvh, QbvhUpdateWorkspace},
     query::{
         details::{
             RayCompositeShapeToiAndNormalBestFirstVisitor, TOICompositeShapeShapeBestFirstVisitor,
@@ -12,31 +13,50 @@ use parry::{
         visitors::{
             BoundingVolumeIntersectionsVisitor, PointIntersectionsVisitor, RayIntersectionsVisitor,
         },
-        DefaultQueryDispatcher, QueryDispatcher,
+        PersistentQueryDispatcher,
     },
     shape::{Shape, TypedSimdCompositeShape},
     utils::DefaultStorage,
 };
 
+/// The fraction of its size that the AABB of a collider is enlarged by when the `Qbvh` of the
+/// [`SpatialQueryPipeline`] is updated incrementally and no enlarged AABB is provided for the collider.
+const LEAF_AABB_MARGIN_FACTOR: Scalar = 0.1;
+
 /// A resource for the spatial query pipeline.
 ///
 /// The pipeline maintains a quaternary bounding volume hierarchy `Qbvh` of the world's colliders
 /// as an acceleration structure for spatial queries.
+///
+/// By default, the `Qbvh` is rebuilt every physics frame. With [`BroadPhaseAlgorithm::DynamicAabbTree`],
+/// it is updated incrementally using the enlarged AABBs of the broad phase's tree instead.
 #[derive(Resource, Clone)]
 pub struct SpatialQueryPipeline {
     pub(crate) qbvh: Qbvh<u32>,
-    pub(crate) dispatcher: Arc<dyn QueryDispatcher>,
+    /// The AABBs of the leaves of the `Qbvh` when it is updated incrementally, by entity index.
+    /// Empty if the `Qbvh` was rebuilt.
+    pub(crate) leaf_aabbs: HashMap<u32, Aabb>,
+    qbvh_workspace: QbvhUpdateWorkspace,
+    pub(crate) dispatcher: Arc<dyn PersistentQueryDispatcher>,
     pub(crate) colliders: HashMap<Entity, (Isometry<Scalar>, Collider, CollisionLayers)>,
     pub(crate) entity_generations: HashMap<u32, u32>,
+    /// The [`CollisionMatrix`] used for [`SpatialQueryFilter::test_with_matrix`].
+    pub(crate) matrix: CollisionMatrix,
+    /// The [layers](CollisionMatrixLayer) of the colliders that have one in the [`CollisionMatrix`].
+    pub(crate) matrix_layers: HashMap<Entity, usize>,
 }
 
 impl Default for SpatialQueryPipeline {
     fn default() -> Self {
         Self {
             qbvh: Qbvh::new(),
-            dispatcher: Arc::new(DefaultQueryDispatcher),
+            leaf_aabbs: HashMap::default(),
+            qbvh_workspace: QbvhUpdateWorkspace::default(),
+            dispatcher: PhysicsQueryDispatcher::default().0,
             colliders: HashMap::default(),
             entity_generations: HashMap::default(),
+            matrix: CollisionMatrix::default(),
+            matrix_layers: HashMap::default(),
         }
     }
 }
@@ -47,6 +67,45 @@ impl SpatialQueryPipeline {
         SpatialQueryPipeline::default()
     }
 
+    /// Sets the query dispatcher used for shape queries like [shapecasting](spatial_query#shapecasting)
+    /// and [shape intersections](spatial_query#intersection-tests).
+    ///
+    /// This is done automatically based on the [`PhysicsQueryDispatcher`] resource.
+    pub fn set_dispatcher(&mut self, dispatcher: Arc<dyn PersistentQueryDispatcher>) {
+        self.dispatcher = dispatcher;
+    }
+
+    /// Sets the [`CollisionMatrix`] and the [layers](CollisionMatrixLayer) of the colliders in the matrix
+    /// used for [spatial query filters](SpatialQueryFilter) that have a [matrix layer](SpatialQueryFilter::with_matrix_layer).
+    ///
+    /// This is done automatically based on the [`CollisionMatrix`] resource.
+    pub fn set_collision_matrix(
+        &mut self,
+        matrix: &CollisionMatrix,
+        matrix_layers: impl Iterator<Item = (Entity, usize)>,
+    ) {
+        if self.matrix != *matrix {
+            self.matrix = matrix.clone();
+        }
+        self.matrix_layers.clear();
+        self.matrix_layers.extend(matrix_layers);
+    }
+
+    /// Tests if the given collider should be included in a query with the given filter.
+    pub(crate) fn test_filter(
+        &self,
+        query_filter: &SpatialQueryFilter,
+        entity: Entity,
+        layers: CollisionLayers,
+    ) -> bool {
+        query_filter.test_with_matrix(
+            entity,
+            layers,
+            self.matrix_layers.get(&entity).copied(),
+            &self.matrix,
+        )
+    }
+
     pub(crate) fn as_composite_shape(
         &self,
         query_filter: SpatialQueryFilter,
@@ -72,7 +131,92 @@ impl SpatialQueryPipeline {
         >,
         added_colliders: impl Iterator<Item = Entity>,
     ) {
-        let colliders = colliders
+        let colliders = Self::collect_colliders(colliders);
+        self.update_internal(colliders, added_colliders)
+    }
+
+    /// Updates the associated acceleration structures with a new set of entities like [`update`](Self::update),
+    /// but only updates the `Qbvh` for colliders that were added or removed, or that moved outside
+    /// of the AABB of their leaf, instead of rebuilding it.
+    ///
+    /// `enlarged_aabb` returns an enlarged AABB for a collider, like the AABB stored for it in the
+    /// [broad phase's](BroadPhasePlugin) tree. It is used as the new AABB of the collider's leaf if it contains
+    /// the collider. Otherwise, the AABB of the collider is enlarged by a margin.
+    pub(crate) fn update_incremental<'a>(
+        &mut self,
+        colliders: impl Iterator<
+            Item = (
+                Entity,
+                &'a Position,
+                &'a Rotation,
+                &'a Collider,
+                Option<&'a CollisionLayers>,
+            ),
+        >,
+        added_colliders: impl Iterator<Item = Entity>,
+        enlarged_aabb: impl Fn(Entity) -> Option<Aabb>,
+    ) {
+        let colliders = Self::collect_colliders(colliders);
+
+        let mut changed = false;
+
+        // Remove colliders that no longer exist
+        for entity in self.colliders.keys() {
+            if !colliders.contains_key(entity) {
+                self.qbvh.remove(entity.index());
+                self.leaf_aabbs.remove(&entity.index());
+                changed = true;
+            }
+        }
+
+        self.colliders = colliders;
+        self.update_generations(added_colliders);
+
+        // Update the leaves of colliders that are new or have moved outside of their leaf AABBs
+        for (entity, (iso, collider, _)) in self.colliders.iter() {
+            let aabb = collider.shape_scaled().compute_aabb(iso);
+            if self
+                .leaf_aabbs
+                .get(&entity.index())
+                .is_some_and(|leaf_aabb| leaf_aabb.contains(&aabb))
+            {
+                continue;
+            }
+
+            let leaf_aabb = enlarged_aabb(*entity)
+                .filter(|enlarged_aabb| enlarged_aabb.contains(&aabb))
+                .unwrap_or_else(|| {
+                    aabb.loosened(aabb.half_extents().max() * LEAF_AABB_MARGIN_FACTOR)
+                });
+            self.leaf_aabbs.insert(entity.index(), leaf_aabb);
+            self.qbvh.pre_update_or_insert(entity.index());
+            changed = true;
+        }
+
+        if changed {
+            let leaf_aabbs = &self.leaf_aabbs;
+            self.qbvh.refit(0.0, &mut self.qbvh_workspace, |index| {
+                leaf_aabbs
+                    .get(index)
+                    .copied()
+                    .unwrap_or_else(Aabb::new_invalid)
+            });
+            self.qbvh.rebalance(0.0, &mut self.qbvh_workspace);
+        }
+    }
+
+    fn collect_colliders<'a>(
+        colliders: impl Iterator<
+            Item = (
+                Entity,
+                &'a Position,
+                &'a Rotation,
+                &'a Collider,
+                Option<&'a CollisionLayers>,
+            ),
+        >,
+    ) -> HashMap<Entity, (Isometry<Scalar>, Collider, CollisionLayers)> {
+        colliders
             .map(|(entity, position, rotation, collider, layers)| {
                 (
                     entity,
@@ -83,19 +227,11 @@ impl SpatialQueryPipeline {
                     ),
                 )
             })
-            .collect();
-
-        self.update_internal(colliders, added_colliders)
+            .collect()
     }
 
-    fn update_internal(
-        &mut self,
-        colliders: HashMap<Entity, (Isometry<Scalar>, Collider, CollisionLayers)>,
-        added: impl Iterator<Item = Entity>,
-    ) {
-        self.colliders = colliders;
-
-        // Insert or update generations of added entities
+    /// Inserts or updates the generations of added entities.
+    fn update_generations(&mut self, added: impl Iterator<Item = Entity>) {
         for added in added {
             let index = added.index();
             if let Some(generation) = self.entity_generations.get_mut(&index) {
@@ -104,6 +240,18 @@ impl SpatialQueryPipeline {
                 self.entity_generations.insert(index, added.generation());
             }
         }
+    }
+
+    fn update_internal(
+        &mut self,
+        colliders: HashMap<Entity, (Isometry<Scalar>, Collider, CollisionLayers)>,
+        added: impl Iterator<Item = Entity>,
+    ) {
+        self.colliders = colliders;
+        self.update_generations(added);
+
+        // The rebuilt `Qbvh` doesn't use the leaf AABBs of incremental updates
+        self.leaf_aabbs.clear();
 
         struct DataGenerator<'a>(
             &'a HashMap<Entity, (Isometry<Scalar>, Collider, CollisionLayers)>,
@@ -244,7 +392,7 @@ impl SpatialQueryPipeline {
         let mut leaf_callback = &mut |entity_index: &u32| {
             let entity = self.entity_from_index(*entity_index);
             if let Some((iso, shape, layers)) = colliders.get(&entity) {
-                if query_filter.test(entity, *layers) {
+                if self.test_filter(&query_filter, entity, *layers) {
                     if let Some(hit) = shape.shape_scaled().cast_ray_and_get_normal(
                         iso,
                         &ray,
@@ -533,7 +681,7 @@ impl SpatialQueryPipeline {
         let mut leaf_callback = &mut |entity_index: &u32| {
             let entity = self.entity_from_index(*entity_index);
             if let Some((isometry, shape, layers)) = self.colliders.get(&entity) {
-                if query_filter.test(entity, *layers)
+                if self.test_filter(&query_filter, entity, *layers)
                     && shape.shape_scaled().contains_point(isometry, &point)
                 {
                     return callback(entity);
@@ -651,7 +799,7 @@ impl SpatialQueryPipeline {
             let entity = self.entity_from_index(*entity_index);
 
             if let Some((collider_isometry, collider, layers)) = colliders.get(&entity) {
-                if query_filter.test(entity, *layers) {
+                if self.test_filter(&query_filter, entity, *layers) {
                     let isometry = inverse_shape_isometry * collider_isometry;
 
                     if dispatcher.intersection_test(
@@ -696,7 +844,10 @@ impl<'a> TypedSimdCompositeShape for QueryPipelineAsCompositeShape<'a> {
                     *self.pipeline.entity_generations.get(&shape_id).unwrap(),
                 ))
         {
-            if self.query_filter.test(*entity, *layers) {
+            if self
+                .pipeline
+                .test_filter(&self.query_filter, *entity, *layers)
+            {
                 f(Some(iso), &**shape.shape_scaled());
             }
         }
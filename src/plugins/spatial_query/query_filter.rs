@@ -27,16 +27,22 @@ use crate::prelude::*;
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpatialQueryFilter {
     /// Specifies which [collision groups](CollisionLayers) will be included in a [spatial query](crate::spatial_query).
-    pub masks: u32,
+    pub masks: LayerMask,
     /// Entities that will not be included in [spatial queries](crate::spatial_query).
     pub excluded_entities: HashSet<Entity>,
+    /// The index of the layer in the [`CollisionMatrix`] that the query belongs to.
+    ///
+    /// Colliders with a [`CollisionLayerName`] in the matrix are included if their layer interacts with this layer,
+    /// regardless of the [`masks`](Self::masks). Other colliders are tested against the masks.
+    pub matrix_layer: Option<usize>,
 }
 
 impl Default for SpatialQueryFilter {
     fn default() -> Self {
         Self {
-            masks: 0xffff_ffff,
+            masks: LayerMask::MAX,
             excluded_entities: default(),
+            matrix_layer: None,
         }
     }
 }
@@ -49,7 +55,7 @@ impl SpatialQueryFilter {
 
     /// Sets the masks of the filter configuration using a bitmask. Colliders with the corresponding
     /// [collision group](CollisionLayers) will be included in the [spatial query](crate::spatial_query).
-    pub fn with_masks_from_bits(mut self, masks: u32) -> Self {
+    pub fn with_masks_from_bits(mut self, masks: LayerMask) -> Self {
         self.masks = masks;
         self
     }
@@ -72,12 +78,41 @@ impl SpatialQueryFilter {
         self
     }
 
+    /// Sets the [layer](CollisionMatrixLayer) of the query in the [`CollisionMatrix`]. Colliders with a
+    /// [`CollisionLayerName`] in the matrix will be included in the [spatial query](crate::spatial_query)
+    /// if their layer interacts with this layer.
+    pub fn with_matrix_layer(mut self, index: usize) -> Self {
+        self.matrix_layer = Some(index);
+        self
+    }
+
     /// Tests if an entity should be included in [spatial queries](crate::spatial_query) based on the
     /// filter configuration.
     pub fn test(&self, entity: Entity, layers: CollisionLayers) -> bool {
         !self.excluded_entities.contains(&entity)
-            && CollisionLayers::from_bits(0xffff_ffff, self.masks).interacts_with(
-                CollisionLayers::from_bits(layers.groups_bits(), 0xffff_ffff),
+            && CollisionLayers::from_bits(LayerMask::MAX, self.masks).interacts_with(
+                CollisionLayers::from_bits(layers.groups_bits(), LayerMask::MAX),
             )
     }
+
+    /// Tests if an entity should be included in [spatial queries](crate::spatial_query) based on the
+    /// filter configuration and the [`CollisionMatrix`].
+    ///
+    /// If both the filter and the entity have a [layer in the matrix](CollisionMatrixLayer), the matrix
+    /// decides whether the entity is included. Otherwise, the [`CollisionLayers`] are used like in [`test`](Self::test).
+    pub fn test_with_matrix(
+        &self,
+        entity: Entity,
+        layers: CollisionLayers,
+        matrix_layer: Option<usize>,
+        matrix: &CollisionMatrix,
+    ) -> bool {
+        match (self.matrix_layer, matrix_layer) {
+            (Some(index1), Some(index2)) => {
+                !self.excluded_entities.contains(&entity)
+                    && matrix.interacts_by_index(index1, index2)
+            }
+            _ => self.test(entity, layers),
+        }
+    }
 }
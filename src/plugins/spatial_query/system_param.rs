@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use bevy::{ecs::system::SystemParam, prelude::*};
+use parry::bounding_volume::Aabb;
 
 /// A system parameter for performing [spatial queries](spatial_query).
 ///
@@ -73,6 +74,9 @@ pub struct SpatialQuery<'w, 's> {
         ),
     >,
     pub(crate) added_colliders: Query<'w, 's, Entity, Added<Collider>>,
+    pub(crate) matrix_layers:
+        Query<'w, 's, (Entity, &'static CollisionMatrixLayer), With<Collider>>,
+    pub(crate) matrix: Option<Res<'w, CollisionMatrix>>,
     /// The [`SpatialQueryPipeline`].
     pub query_pipeline: ResMut<'w, SpatialQueryPipeline>,
 }
@@ -84,6 +88,32 @@ impl<'w, 's> SpatialQuery<'w, 's> {
     pub fn update_pipeline(&mut self) {
         self.query_pipeline
             .update(self.colliders.iter(), self.added_colliders.iter());
+        self.update_pipeline_collision_matrix();
+    }
+
+    /// Updates the colliders in the pipeline like [`update_pipeline`](Self::update_pipeline), but updates
+    /// the pipeline's `Qbvh` incrementally using the given enlarged AABBs instead of rebuilding it.
+    pub(crate) fn update_pipeline_incremental(
+        &mut self,
+        enlarged_aabb: impl Fn(Entity) -> Option<Aabb>,
+    ) {
+        self.query_pipeline.update_incremental(
+            self.colliders.iter(),
+            self.added_colliders.iter(),
+            enlarged_aabb,
+        );
+        self.update_pipeline_collision_matrix();
+    }
+
+    fn update_pipeline_collision_matrix(&mut self) {
+        if let Some(matrix) = &self.matrix {
+            self.query_pipeline.set_collision_matrix(
+                matrix,
+                self.matrix_layers
+                    .iter()
+                    .filter_map(|(entity, layer)| Some((entity, layer.index()?))),
+            );
+        }
     }
 
     /// Casts a [ray](spatial_query#raycasting) and computes the closest [hit](RayHitData) with a collider.
@@ -0,0 +1,563 @@
+//! Raycast vehicles with suspension, tire friction and engine torque.
+//!
+//! See [`VehiclePlugin`], [`Vehicle`] and [`Wheel`].
+
+use crate::prelude::*;
+use bevy::{
+    ecs::query::Has,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+/// Simulates the [wheels](Wheel) of [vehicles](Vehicle) using raycasts.
+///
+/// Each wheel casts a ray from its [anchor](Wheel::anchor) on the vehicle's body along the body's
+/// local down direction using the [`SpatialQueryPipeline`]. If the ray hits the ground, the wheel applies:
+///
+/// - A spring and damper force along the suspension that holds the vehicle up
+/// - A longitudinal tire force caused by the engine and the brakes
+/// - A lateral tire force that prevents the wheel from sliding sideways (only in 3D)
+///
+/// The tire forces are limited by the friction of the tire and the load on the wheel, so wheels can
+/// spin and skid when the forces are too large.
+///
+/// The forces are computed once per physics step and applied to the body of the vehicle as an
+/// [`ExternalImpulse`] that is scaled by the length of the step. They are not applied to the ground.
+///
+/// The plugin requires the [`SpatialQueryPlugin`]. Without it, the wheels don't apply any forces,
+/// and a warning is logged. The vehicles are updated in a system that runs
+/// after [`PhysicsStepSet::BroadPhase`], before the impulses are applied to bodies.
+pub struct VehiclePlugin;
+
+impl Plugin for VehiclePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Vehicle>()
+            .register_type::<Wheel>()
+            .register_type::<WheelState>();
+
+        let physics_schedule = app
+            .get_schedule_mut(PhysicsSchedule)
+            .expect("add PhysicsSchedule first");
+
+        physics_schedule.add_systems(
+            update_vehicles
+                .after(PhysicsStepSet::BroadPhase)
+                .before(integrator::apply_impulses),
+        );
+    }
+}
+
+/// A [dynamic](RigidBody::Dynamic) rigid body that is driven by [wheels](Wheel).
+///
+/// The wheels are child entities of the vehicle that have the [`Wheel`] component.
+/// The vehicle is controlled using the [`throttle`](Self::throttle), [`brake`](Self::brake)
+#[cfg_attr(feature = "2d", doc = "inputs.")]
+#[cfg_attr(feature = "3d", doc = "and [`steering`](Self::steering) inputs.")]
+///
+/// The forward direction of the vehicle is the local
+#[cfg_attr(
+    feature = "2d",
+    doc = "X axis, and the up direction is the local Y axis."
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "negative Z axis, and the up direction is the local Y axis."
+)]
+///
+/// See [`VehiclePlugin`] for how the wheels are simulated.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::{math::*, prelude::*};")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::{math::*, prelude::*};")]
+///
+/// fn setup(mut commands: Commands) {
+///     commands
+///         .spawn((
+///             RigidBody::Dynamic,
+#[cfg_attr(feature = "2d", doc = "            Collider::cuboid(2.0, 0.5),")]
+#[cfg_attr(feature = "3d", doc = "            Collider::cuboid(1.0, 0.5, 2.0),")]
+///             Vehicle::new(20.0, 40.0),
+///         ))
+///         .with_children(|children| {
+#[cfg_attr(
+    feature = "2d",
+    doc = "            children.spawn(Wheel::new(Vector::new(-0.8, -0.25), 0.3).with_driven(true));
+            children.spawn(Wheel::new(Vector::new(0.8, -0.25), 0.3));"
+)]
+#[cfg_attr(
+    feature = "3d",
+    doc = "            for x in [-0.5, 0.5] {
+                // Rear wheels that are driven by the engine
+                children.spawn(Wheel::new(Vector::new(x, -0.25, 0.8), 0.3).with_driven(true));
+                // Front wheels that are used for steering
+                children.spawn(Wheel::new(Vector::new(x, -0.25, -0.8), 0.3).with_steered(true));
+            }"
+)]
+///         });
+/// }
+///
+/// fn drive(keyboard_input: Res<Input<KeyCode>>, mut vehicles: Query<&mut Vehicle>) {
+///     for mut vehicle in &mut vehicles {
+///         vehicle.throttle = if keyboard_input.pressed(KeyCode::Up) { 1.0 } else { 0.0 };
+///         vehicle.brake = if keyboard_input.pressed(KeyCode::Space) { 1.0 } else { 0.0 };
+///     }
+/// }
+/// ```
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct Vehicle {
+    /// The throttle input in the range `[-1, 1]`. Negative values drive backwards.
+    pub throttle: Scalar,
+    /// The brake input in the range `[0, 1]`.
+    pub brake: Scalar,
+    /// The steering input in the range `[-1, 1]`. Positive values steer left.
+    #[cfg(feature = "3d")]
+    pub steering: Scalar,
+    /// The maximum torque of the engine. It is split evenly between the [driven](Wheel::driven) wheels.
+    pub engine_torque: Scalar,
+    /// The maximum braking torque applied to each wheel.
+    pub brake_torque: Scalar,
+    /// The angle in radians that the [steered](Wheel::steered) wheels are turned by at full steering input.
+    #[cfg(feature = "3d")]
+    pub max_steering_angle: Scalar,
+}
+
+impl Default for Vehicle {
+    fn default() -> Self {
+        Self {
+            throttle: 0.0,
+            brake: 0.0,
+            #[cfg(feature = "3d")]
+            steering: 0.0,
+            engine_torque: 0.0,
+            brake_torque: 0.0,
+            #[cfg(feature = "3d")]
+            max_steering_angle: PI / 6.0,
+        }
+    }
+}
+
+impl Vehicle {
+    /// Creates a new [`Vehicle`] with the given maximum engine torque and braking torque.
+    pub fn new(engine_torque: Scalar, brake_torque: Scalar) -> Self {
+        Self {
+            engine_torque,
+            brake_torque,
+            ..default()
+        }
+    }
+
+    /// Sets the [maximum steering angle](Self::max_steering_angle) in radians.
+    #[cfg(feature = "3d")]
+    pub fn with_max_steering_angle(mut self, angle: Scalar) -> Self {
+        self.max_steering_angle = angle;
+        self
+    }
+}
+
+/// A wheel of a [`Vehicle`], simulated using a raycast and a spring and damper suspension.
+///
+/// Wheels must be child entities of the vehicle. They don't need colliders or rigid bodies.
+/// The state of the wheel, like whether it is touching the ground and how compressed the
+/// suspension is, is stored in the [`WheelState`] component, which is added automatically.
+///
+/// See [`Vehicle`] for an example.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct Wheel {
+    /// The point where the suspension is attached to the vehicle, in the local space of the vehicle's body.
+    pub anchor: Vector,
+    /// The radius of the wheel.
+    pub radius: Scalar,
+    /// The length of the suspension when it is fully extended and no force is applied.
+    pub rest_length: Scalar,
+    /// The stiffness of the suspension spring.
+    pub stiffness: Scalar,
+    /// The damping of the suspension.
+    pub damping: Scalar,
+    /// The friction coefficient of the tire along the rolling direction.
+    /// Limits the forces caused by the engine and the brakes.
+    pub longitudinal_friction: Scalar,
+    /// The friction coefficient of the tire perpendicular to the rolling direction.
+    /// Limits the force that prevents the wheel from sliding sideways.
+    #[cfg(feature = "3d")]
+    pub lateral_friction: Scalar,
+    /// If true, the wheel is driven by the engine of the vehicle.
+    pub driven: bool,
+    /// If true, the wheel is turned by the steering input of the vehicle.
+    #[cfg(feature = "3d")]
+    pub steered: bool,
+}
+
+impl Default for Wheel {
+    fn default() -> Self {
+        Self {
+            anchor: Vector::ZERO,
+            radius: 0.5,
+            rest_length: 0.3,
+            stiffness: 100.0,
+            damping: 10.0,
+            longitudinal_friction: 1.0,
+            #[cfg(feature = "3d")]
+            lateral_friction: 1.0,
+            driven: false,
+            #[cfg(feature = "3d")]
+            steered: false,
+        }
+    }
+}
+
+impl Wheel {
+    /// Creates a new [`Wheel`] with the given [anchor](Self::anchor) on the vehicle and radius.
+    pub fn new(anchor: Vector, radius: Scalar) -> Self {
+        Self {
+            anchor,
+            radius,
+            ..default()
+        }
+    }
+
+    /// Sets the [rest length](Self::rest_length), [stiffness](Self::stiffness) and [damping](Self::damping)
+    /// of the suspension.
+    pub fn with_suspension(
+        mut self,
+        rest_length: Scalar,
+        stiffness: Scalar,
+        damping: Scalar,
+    ) -> Self {
+        self.rest_length = rest_length;
+        self.stiffness = stiffness;
+        self.damping = damping;
+        self
+    }
+
+    /// Sets the [longitudinal friction](Self::longitudinal_friction) coefficient of the tire.
+    pub fn with_longitudinal_friction(mut self, friction: Scalar) -> Self {
+        self.longitudinal_friction = friction;
+        self
+    }
+
+    /// Sets the [lateral friction](Self::lateral_friction) coefficient of the tire.
+    #[cfg(feature = "3d")]
+    pub fn with_lateral_friction(mut self, friction: Scalar) -> Self {
+        self.lateral_friction = friction;
+        self
+    }
+
+    /// Sets whether the wheel is [driven](Self::driven) by the engine.
+    pub fn with_driven(mut self, driven: bool) -> Self {
+        self.driven = driven;
+        self
+    }
+
+    /// Sets whether the wheel is [steered](Self::steered) by the steering input.
+    #[cfg(feature = "3d")]
+    pub fn with_steered(mut self, steered: bool) -> Self {
+        self.steered = steered;
+        self
+    }
+}
+
+/// The state of a [`Wheel`] after the latest physics step.
+///
+/// This component is added and updated automatically by the [`VehiclePlugin`].
+/// It can be used for things like positioning and rotating the meshes of the wheels.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct WheelState {
+    /// True if the wheel is touching the ground.
+    pub grounded: bool,
+    /// The world-space point where the wheel touches the ground.
+    pub contact_point: Vector,
+    /// The world-space normal of the ground at the contact point.
+    pub contact_normal: Vector,
+    /// The collider entity of the ground the wheel is touching.
+    pub ground_entity: Option<Entity>,
+    /// The current length of the suspension. The center of the wheel is this far from the
+    /// [anchor](Wheel::anchor) along the vehicle's down direction.
+    pub suspension_length: Scalar,
+    /// The magnitude of the force applied by the suspension.
+    pub suspension_force: Scalar,
+    /// The angle in radians that the wheel is turned by.
+    #[cfg(feature = "3d")]
+    pub steering_angle: Scalar,
+    /// The rotation speed of the wheel around its axle in radians per second.
+    /// Positive values roll the vehicle forward.
+    pub angular_velocity: Scalar,
+    /// The rotation angle of the wheel around its axle in radians, in the range `[0, 2π)`.
+    pub rotation_angle: Scalar,
+}
+
+#[cfg(feature = "2d")]
+fn effective_inverse_mass(inv_mass: Scalar, inv_inertia: Scalar, r: Vector, dir: Vector) -> Scalar {
+    inv_mass + inv_inertia * r.perp_dot(dir).powi(2)
+}
+
+#[cfg(feature = "3d")]
+fn effective_inverse_mass(
+    inv_mass: Scalar,
+    inv_inertia: Matrix3,
+    r: Vector,
+    dir: Vector,
+) -> Scalar {
+    let r_cross_dir = r.cross(dir);
+    inv_mass + r_cross_dir.dot(inv_inertia * r_cross_dir)
+}
+
+/// Computes the impulse along `dir` that is needed to cancel the given relative speed, limited by `max_impulse`.
+fn friction_impulse(speed: Scalar, inverse_mass: Scalar, max_impulse: Scalar) -> Scalar {
+    if inverse_mass <= Scalar::EPSILON {
+        return 0.0;
+    }
+    (-speed / inverse_mass).clamp(-max_impulse, max_impulse)
+}
+
+type VehicleBodyComponents = (
+    Entity,
+    Ref<'static, Vehicle>,
+    &'static RigidBody,
+    &'static Children,
+    &'static Position,
+    &'static Rotation,
+    &'static LinearVelocity,
+    &'static AngularVelocity,
+    &'static CenterOfMass,
+    &'static InverseMass,
+    &'static InverseInertia,
+    &'static mut ExternalImpulse,
+    Option<&'static CollisionLayers>,
+    Has<Sleeping>,
+);
+
+/// Casts the suspension rays of wheels and applies the suspension and tire forces to the vehicles.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_vehicles(
+    mut commands: Commands,
+    mut vehicles: Query<VehicleBodyComponents>,
+    mut wheels: Query<(&Wheel, Option<&mut WheelState>)>,
+    child_colliders: Query<(Entity, &ColliderParent)>,
+    sensors: Query<Entity, With<Sensor>>,
+    grounds: Query<
+        (
+            &Position,
+            &Rotation,
+            &CenterOfMass,
+            &LinearVelocity,
+            &AngularVelocity,
+        ),
+        Without<Vehicle>,
+    >,
+    collider_parents: Query<&ColliderParent>,
+    pipeline: Option<Res<SpatialQueryPipeline>>,
+    time: Res<Time<Physics>>,
+    mut warned_no_pipeline: Local<bool>,
+) {
+    let delta_secs = time.delta_seconds_f64().adjust_precision();
+    if delta_secs <= 0.0 {
+        return;
+    }
+    let Some(pipeline) = pipeline_or_warn_once(&pipeline, &mut warned_no_pipeline, "`Vehicle`")
+    else {
+        return;
+    };
+
+    // The rays of the wheels should ignore the colliders of their own vehicles
+    let mut vehicle_colliders = HashMap::<Entity, HashSet<Entity>>::default();
+    for (entity, parent) in &child_colliders {
+        if vehicles.contains(parent.get()) {
+            vehicle_colliders
+                .entry(parent.get())
+                .or_default()
+                .insert(entity);
+        }
+    }
+
+    for (
+        entity,
+        vehicle,
+        rb,
+        children,
+        position,
+        rotation,
+        lin_vel,
+        ang_vel,
+        center_of_mass,
+        inv_mass,
+        inv_inertia,
+        mut impulse,
+        layers,
+        is_sleeping,
+    ) in &mut vehicles
+    {
+        // Sleeping vehicles are only updated when their controls are changed
+        if !rb.is_dynamic() || (is_sleeping && !vehicle.is_changed()) {
+            continue;
+        }
+
+        let mut filter = SpatialQueryFilter::new()
+            .without_entities([entity])
+            .without_entities(&sensors);
+        if let Some(colliders) = vehicle_colliders.remove(&entity) {
+            filter = filter.without_entities(colliders);
+        }
+        if let Some(layers) = layers {
+            filter = filter.with_masks_from_bits(layers.masks_bits());
+        }
+
+        let up = rotation.rotate(Vector::Y);
+        let world_com = rotation.rotate(center_of_mass.0);
+        let inv_inertia = inv_inertia.rotated(rotation).0;
+
+        // Cast the suspension rays
+        let mut contacts = vec![];
+        for child in children.iter() {
+            let Ok((wheel, _)) = wheels.get(*child) else {
+                continue;
+            };
+
+            let origin = position.0 + rotation.rotate(wheel.anchor);
+            let hit = pipeline.cast_ray(
+                origin,
+                -up,
+                wheel.rest_length + wheel.radius,
+                true,
+                filter.clone(),
+            );
+            contacts.push((*child, origin, hit));
+        }
+
+        let grounded_count = contacts.iter().filter(|(_, _, hit)| hit.is_some()).count();
+        let driven_count = contacts
+            .iter()
+            .filter(|(child, _, _)| wheels.get(*child).is_ok_and(|(wheel, _)| wheel.driven))
+            .count();
+
+        for (child, origin, hit) in contacts {
+            let Ok((wheel, current_state)) = wheels.get_mut(child) else {
+                continue;
+            };
+
+            let previous_state = current_state.as_deref().copied().unwrap_or_default();
+            let mut state = WheelState {
+                suspension_length: wheel.rest_length,
+                angular_velocity: previous_state.angular_velocity,
+                ..default()
+            };
+
+            #[cfg(feature = "3d")]
+            {
+                if wheel.steered {
+                    state.steering_angle =
+                        vehicle.steering.clamp(-1.0, 1.0) * vehicle.max_steering_angle;
+                }
+            }
+
+            if let Some(hit) = hit {
+                state.grounded = true;
+                state.suspension_length = (hit.time_of_impact - wheel.radius).max(0.0);
+                state.contact_point = origin - up * hit.time_of_impact;
+                state.contact_normal = hit.normal;
+                state.ground_entity = Some(hit.entity);
+
+                // The velocity of the contact point relative to the ground
+                let r = state.contact_point - position.0 - world_com;
+                let ground_velocity = collider_parents
+                    .get(hit.entity)
+                    .ok()
+                    .and_then(|parent| grounds.get(parent.get()).ok())
+                    .map_or(
+                        Vector::ZERO,
+                        |(ground_pos, ground_rot, ground_com, ground_lin_vel, ground_ang_vel)| {
+                            utils::velocity_at_point(
+                                ground_lin_vel.0,
+                                ground_ang_vel.0,
+                                state.contact_point
+                                    - ground_pos.0
+                                    - ground_rot.rotate(ground_com.0),
+                            )
+                        },
+                    );
+                let velocity = utils::velocity_at_point(lin_vel.0, ang_vel.0, r) - ground_velocity;
+
+                // Suspension
+                let compression = wheel.rest_length - state.suspension_length;
+                let compression_speed = -velocity.dot(up);
+                state.suspension_force =
+                    (wheel.stiffness * compression + wheel.damping * compression_speed).max(0.0);
+                let normal_impulse = state.suspension_force * delta_secs;
+                let mut wheel_impulse = up * normal_impulse;
+
+                // Tire forces along the ground
+                #[cfg(feature = "2d")]
+                let forward = rotation.rotate(Vector::X);
+                #[cfg(feature = "3d")]
+                let forward = rotation
+                    .rotate(Quaternion::from_rotation_y(state.steering_angle) * Vector::NEG_Z);
+                let forward = forward - hit.normal * forward.dot(hit.normal);
+
+                if let Some(forward) = forward.try_normalize() {
+                    let forward_speed = velocity.dot(forward);
+                    let share = 1.0 / grounded_count as Scalar;
+
+                    let mut longitudinal_impulse = 0.0;
+                    if wheel.driven {
+                        longitudinal_impulse += vehicle.throttle.clamp(-1.0, 1.0)
+                            * vehicle.engine_torque
+                            / (driven_count as Scalar * wheel.radius)
+                            * delta_secs;
+                    }
+                    let brake_impulse = vehicle.brake.clamp(0.0, 1.0) * vehicle.brake_torque
+                        / wheel.radius
+                        * delta_secs;
+                    longitudinal_impulse += friction_impulse(
+                        forward_speed,
+                        effective_inverse_mass(inv_mass.0, inv_inertia, r, forward),
+                        brake_impulse,
+                    ) * share;
+
+                    let max_longitudinal_impulse = wheel.longitudinal_friction * normal_impulse;
+                    wheel_impulse += forward
+                        * longitudinal_impulse
+                            .clamp(-max_longitudinal_impulse, max_longitudinal_impulse);
+
+                    #[cfg(feature = "3d")]
+                    {
+                        let lateral = hit.normal.cross(forward).normalize();
+                        wheel_impulse += lateral
+                            * friction_impulse(
+                                velocity.dot(lateral),
+                                effective_inverse_mass(inv_mass.0, inv_inertia, r, lateral),
+                                wheel.lateral_friction * normal_impulse,
+                            )
+                            * share;
+                    }
+
+                    // Assume that grounded wheels roll without slipping
+                    state.angular_velocity = forward_speed / wheel.radius;
+                }
+
+                impulse.apply_impulse_at_point(
+                    wheel_impulse,
+                    state.contact_point - position.0,
+                    world_com,
+                );
+            }
+
+            state.rotation_angle = (previous_state.rotation_angle
+                + state.angular_velocity * delta_secs)
+                .rem_euclid(2.0 * PI);
+
+            if let Some(mut current_state) = current_state {
+                *current_state = state;
+            } else {
+                commands.entity(child).insert(state);
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Capturing and restoring the state of the physics world, for example for rollback networking.
+//!
+//! See [`PhysicsSnapshot`].
+
+use crate::{
+    plugins::{
+        collision::broad_phase::{AabbIntervals, AabbTree},
+        sync::PreviousGlobalTransform,
+    },
+    prelude::*,
+};
+use bevy::{ecs::query::ReadOnlyWorldQuery, prelude::*, utils::HashSet};
+
+/// A snapshot of the simulation state of the physics world.
+///
+/// A snapshot can be captured from a [`World`] with [`PhysicsSnapshot::capture`] and written back
+/// with [`PhysicsSnapshot::restore`]. This is useful for things like rollback networking,
+/// where the simulation is rewound to an earlier frame and then replayed with corrected inputs.
+///
+/// The snapshot contains:
+///
+/// - The [`Position`], [`Rotation`], [`PreviousPosition`], [`PreviousRotation`] and [`AccumulatedTranslation`]
+/// of bodies and colliders
+/// - The `Transform`, `GlobalTransform` and [`PreviousGlobalTransform`] of bodies and colliders,
+/// so that transform changes that haven't been applied to the positions yet are replayed as well
+/// - [`LinearVelocity`] and [`AngularVelocity`]
+/// - [`ExternalForce`], [`ExternalTorque`], [`ExternalImpulse`] and [`ExternalAngularImpulse`]
+/// - [`TimeSleeping`] and whether bodies are [`Sleeping`]
+/// - The built-in [joints], including their Lagrange multipliers
+/// - The contacts stored in [`Collisions`], including their impulses, the [`ContactImpulses`] of bodies
+/// and the sensor intersections stored in [`SensorOverlaps`]
+/// - The [`CharacterControllerState`] of [character controllers](CharacterController) and the [`WheelState`]
+/// of [wheels](Wheel)
+/// - [Soft bodies](SoftBody) and [fluids](Fluid), including their particles
+/// - The internal state of the broad phase
+/// - The [`Time<Physics>`] clock, including the accumulated overstep of a fixed timestep
+///
+/// Other components, like colliders and mass properties, are considered to be configuration
+/// and are not stored.
+///
+/// With the same inputs, replaying the simulation from a restored snapshot produces the same results
+/// as the original simulation. To get identical results across different machines,
+/// enable the `enhanced-determinism` feature.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+#[cfg_attr(feature = "2d", doc = "use bevy_xpbd_2d::prelude::*;")]
+#[cfg_attr(feature = "3d", doc = "use bevy_xpbd_3d::prelude::*;")]
+///
+/// fn rollback(world: &mut World) {
+///     // Capture the state of the physics world
+///     let snapshot = PhysicsSnapshot::capture(world);
+///
+///     // ...advance the simulation...
+///
+///     // Rewind the simulation back to the captured state
+///     snapshot.restore(world);
+/// }
+/// ```
+///
+/// ## Caveats
+///
+/// Only entities that exist when the snapshot is restored are affected. Entities that have been
+/// despawned after capturing the snapshot are not respawned, and entities that have been spawned
+/// after capturing the snapshot are left untouched, so spawning and despawning should be handled
+/// separately.
+///
+/// Components are only written if their values differ from the stored values, so that the
+/// [change detection](bevy::ecs::change_detection) used by the engine isn't triggered unnecessarily.
+#[derive(Clone, Debug)]
+pub struct PhysicsSnapshot {
+    positions: ComponentSnapshot<Position>,
+    rotations: ComponentSnapshot<Rotation>,
+    previous_positions: ComponentSnapshot<PreviousPosition>,
+    previous_rotations: ComponentSnapshot<PreviousRotation>,
+    accumulated_translations: ComponentSnapshot<AccumulatedTranslation>,
+    transforms: ComponentSnapshot<Transform>,
+    global_transforms: ComponentSnapshot<GlobalTransform>,
+    previous_global_transforms: ComponentSnapshot<PreviousGlobalTransform>,
+    linear_velocities: ComponentSnapshot<LinearVelocity>,
+    angular_velocities: ComponentSnapshot<AngularVelocity>,
+    external_forces: ComponentSnapshot<ExternalForce>,
+    external_torques: ComponentSnapshot<ExternalTorque>,
+    external_impulses: ComponentSnapshot<ExternalImpulse>,
+    external_angular_impulses: ComponentSnapshot<ExternalAngularImpulse>,
+    time_sleeping: ComponentSnapshot<TimeSleeping>,
+    sleeping: Vec<Entity>,
+    fixed_joints: ComponentSnapshot<FixedJoint>,
+    distance_joints: ComponentSnapshot<DistanceJoint>,
+    prismatic_joints: ComponentSnapshot<PrismaticJoint>,
+    revolute_joints: ComponentSnapshot<RevoluteJoint>,
+    spherical_joints: ComponentSnapshot<SphericalJoint>,
+    contact_impulses: ComponentSnapshot<ContactImpulses>,
+    character_controller_states: ComponentSnapshot<CharacterControllerState>,
+    wheel_states: ComponentSnapshot<WheelState>,
+    soft_bodies: ComponentSnapshot<SoftBody>,
+    fluids: ComponentSnapshot<Fluid>,
+    collisions: Option<Collisions>,
+    sensor_overlaps: Option<SensorOverlaps>,
+    aabb_intervals: Option<AabbIntervals>,
+    aabb_tree: Option<AabbTree>,
+    physics_time: Option<Time<Physics>>,
+}
+
+impl PhysicsSnapshot {
+    /// Captures a snapshot of the current state of the physics world.
+    pub fn capture(world: &mut World) -> Self {
+        Self {
+            positions: ComponentSnapshot::capture(world),
+            rotations: ComponentSnapshot::capture(world),
+            previous_positions: ComponentSnapshot::capture(world),
+            previous_rotations: ComponentSnapshot::capture(world),
+            accumulated_translations: ComponentSnapshot::capture(world),
+            transforms: ComponentSnapshot::capture_filtered::<With<PreviousGlobalTransform>>(world),
+            global_transforms: ComponentSnapshot::capture_filtered::<With<PreviousGlobalTransform>>(
+                world,
+            ),
+            previous_global_transforms: ComponentSnapshot::capture(world),
+            linear_velocities: ComponentSnapshot::capture(world),
+            angular_velocities: ComponentSnapshot::capture(world),
+            external_forces: ComponentSnapshot::capture(world),
+            external_torques: ComponentSnapshot::capture(world),
+            external_impulses: ComponentSnapshot::capture(world),
+            external_angular_impulses: ComponentSnapshot::capture(world),
+            time_sleeping: ComponentSnapshot::capture(world),
+            sleeping: world
+                .query_filtered::<Entity, With<Sleeping>>()
+                .iter(world)
+                .collect(),
+            fixed_joints: ComponentSnapshot::capture(world),
+            distance_joints: ComponentSnapshot::capture(world),
+            prismatic_joints: ComponentSnapshot::capture(world),
+            revolute_joints: ComponentSnapshot::capture(world),
+            spherical_joints: ComponentSnapshot::capture(world),
+            contact_impulses: ComponentSnapshot::capture(world),
+            character_controller_states: ComponentSnapshot::capture(world),
+            wheel_states: ComponentSnapshot::capture(world),
+            soft_bodies: ComponentSnapshot::capture(world),
+            fluids: ComponentSnapshot::capture(world),
+            collisions: world.get_resource::<Collisions>().cloned(),
+            sensor_overlaps: world.get_resource::<SensorOverlaps>().cloned(),
+            aabb_intervals: world.get_resource::<AabbIntervals>().cloned(),
+            aabb_tree: world.get_resource::<AabbTree>().cloned(),
+            physics_time: world.get_resource::<Time<Physics>>().copied(),
+        }
+    }
+
+    /// Restores the state of the physics world from the snapshot.
+    pub fn restore(&self, world: &mut World) {
+        self.positions.restore(world);
+        self.rotations.restore(world);
+        self.previous_positions.restore(world);
+        self.previous_rotations.restore(world);
+        self.accumulated_translations.restore(world);
+        self.transforms.restore(world);
+        self.global_transforms.restore(world);
+        self.previous_global_transforms.restore(world);
+        self.linear_velocities.restore(world);
+        self.angular_velocities.restore(world);
+        self.external_forces.restore(world);
+        self.external_torques.restore(world);
+        self.external_impulses.restore(world);
+        self.external_angular_impulses.restore(world);
+        self.time_sleeping.restore(world);
+        self.fixed_joints.restore(world);
+        self.distance_joints.restore(world);
+        self.prismatic_joints.restore(world);
+        self.revolute_joints.restore(world);
+        self.spherical_joints.restore(world);
+        self.contact_impulses.restore(world);
+        self.character_controller_states.restore(world);
+        self.wheel_states.restore(world);
+        self.soft_bodies.restore(world);
+        self.fluids.restore(world);
+
+        // Wake up bodies that were awake, and put bodies to sleep that were sleeping.
+        // The bodies are iterated in a fixed order, as it affects the order of the bodies in storage.
+        let sleeping: HashSet<Entity> = self.sleeping.iter().copied().collect();
+        let woken_up: Vec<Entity> = world
+            .query_filtered::<Entity, With<Sleeping>>()
+            .iter(world)
+            .filter(|entity| !sleeping.contains(entity))
+            .collect();
+        for entity in woken_up {
+            world.entity_mut(entity).remove::<Sleeping>();
+        }
+        for entity in self.sleeping.iter() {
+            if let Some(mut entity_mut) = world.get_entity_mut(*entity) {
+                if !entity_mut.contains::<Sleeping>() {
+                    entity_mut.insert(Sleeping);
+                }
+            }
+        }
+
+        if let Some(collisions) = &self.collisions {
+            world.insert_resource(collisions.clone());
+        }
+        if let Some(sensor_overlaps) = &self.sensor_overlaps {
+            world.insert_resource(sensor_overlaps.clone());
+        }
+        if let Some(aabb_intervals) = &self.aabb_intervals {
+            world.insert_resource(aabb_intervals.clone());
+        }
+        if let Some(aabb_tree) = &self.aabb_tree {
+            world.insert_resource(aabb_tree.clone());
+        }
+        if let Some(physics_time) = &self.physics_time {
+            world.insert_resource(*physics_time);
+        }
+    }
+}
+
+/// The values of a component for all entities that have it.
+#[derive(Clone, Debug)]
+struct ComponentSnapshot<C: Component>(Vec<(Entity, C)>);
+
+impl<C: Component + Clone + PartialEq> ComponentSnapshot<C> {
+    fn capture(world: &mut World) -> Self {
+        Self::capture_filtered::<()>(world)
+    }
+
+    /// Captures the component only for entities that match the query filter `F`.
+    fn capture_filtered<F: ReadOnlyWorldQuery>(world: &mut World) -> Self {
+        Self(
+            world
+                .query_filtered::<(Entity, &C), F>()
+                .iter(world)
+                .map(|(entity, component)| (entity, component.clone()))
+                .collect(),
+        )
+    }
+
+    fn restore(&self, world: &mut World) {
+        for (entity, component) in self.0.iter() {
+            if let Some(mut current) = world.get_mut::<C>(*entity) {
+                current.set_if_neq(component.clone());
+            } else if let Some(mut entity_mut) = world.get_entity_mut(*entity) {
+                entity_mut.insert(component.clone());
+            }
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use crate::prelude::*;
 use approx::assert_relative_eq;
 use bevy::{
-    ecs::schedule::ScheduleBuildSettings, prelude::*, time::TimeUpdateStrategy, utils::Instant,
+    ecs::{event::ManualEventReader, schedule::ScheduleBuildSettings},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    utils::Instant,
 };
 #[cfg(feature = "enhanced-determinism")]
 use insta::assert_debug_snapshot;
@@ -165,6 +168,1979 @@ fn body_with_velocity_moves() {
     }
 }
 
+#[test]
+fn swept_ccd_prevents_tunneling() {
+    let mut app = create_app();
+
+    app.insert_resource(Gravity::ZERO);
+
+    app.add_systems(Startup, |mut commands: Commands| {
+        // thin wall 25 units to the right
+        #[cfg(feature = "2d")]
+        let wall = Collider::cuboid(0.1, 10.0);
+        #[cfg(feature = "3d")]
+        let wall = Collider::cuboid(0.1, 10.0, 10.0);
+        commands.spawn((RigidBody::Static, Position(Vector::X * 25.0), wall));
+        // move right at 10 units per frame
+        commands.spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            LinearVelocity(Vector::X * 600.0),
+            Collider::ball(0.1),
+            SweptCcd::default(),
+        ));
+    });
+
+    for _ in 0..10 {
+        tick_60_fps(&mut app);
+    }
+
+    let mut app_query = app.world.query::<(&Position, &RigidBody)>();
+
+    for (pos, body) in app_query.iter(&app.world) {
+        if body.is_dynamic() {
+            assert!(pos.x < 25.0, "ball tunneled through the wall");
+        }
+    }
+}
+
+#[cfg(feature = "3d")]
+#[test]
+fn box_stack_does_not_creep() {
+    let mut app = create_app();
+
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::NEG_Y * 0.5),
+        Collider::cuboid(20.0, 1.0, 20.0),
+    ));
+
+    // a tall stack of boxes that is kept awake
+    let boxes: Vec<Entity> = (0..10)
+        .map(|i| {
+            app.world
+                .spawn((
+                    RigidBody::Dynamic,
+                    Position(Vector::Y * (0.5 + i as Scalar)),
+                    Collider::cuboid(1.0, 1.0, 1.0),
+                    SleepingDisabled,
+                ))
+                .id()
+        })
+        .collect();
+
+    for _ in 0..600 {
+        tick_60_fps(&mut app);
+    }
+
+    // warm starting and friction anchors keep the boxes from creeping sideways
+    for entity in boxes {
+        let position = app.world.get::<Position>(entity).unwrap();
+        assert!(Vector::new(position.x, 0.0, position.z).length() < 0.02);
+    }
+}
+
+#[test]
+fn modified_contacts_move_and_pass_through_bodies() {
+    #[derive(Component)]
+    struct Conveyor;
+
+    #[derive(Component)]
+    struct Ghost;
+
+    let mut app = create_app();
+
+    // conveyor belts move bodies along the x axis, and bodies fall through ghost floors
+    app.add_systems(
+        PostProcessCollisions,
+        |mut collisions: ResMut<Collisions>,
+         conveyors: Query<(), With<Conveyor>>,
+         ghosts: Query<(), With<Ghost>>| {
+            for contacts in collisions.iter_mut() {
+                let conveyor_sign = if conveyors.contains(contacts.entity1) {
+                    -1.0
+                } else if conveyors.contains(contacts.entity2) {
+                    1.0
+                } else {
+                    0.0
+                };
+                let is_ghost =
+                    ghosts.contains(contacts.entity1) || ghosts.contains(contacts.entity2);
+
+                for manifold in contacts.manifolds.iter_mut() {
+                    for contact in manifold.contacts.iter_mut() {
+                        contact.tangent_velocity = Vector::X * conveyor_sign;
+                        contact.enabled = !is_ghost;
+                    }
+                }
+            }
+        },
+    );
+
+    #[cfg(feature = "2d")]
+    let (floor, body) = (Collider::cuboid(20.0, 1.0), Collider::cuboid(1.0, 1.0));
+    #[cfg(feature = "3d")]
+    let (floor, body) = (
+        Collider::cuboid(20.0, 1.0, 20.0),
+        Collider::cuboid(1.0, 1.0, 1.0),
+    );
+
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::NEG_Y * 0.5),
+        floor.clone(),
+        Conveyor,
+    ));
+    app.world
+        .spawn((RigidBody::Static, Position(Vector::Y * 9.5), floor, Ghost));
+
+    let moved_body = app
+        .world
+        .spawn((RigidBody::Dynamic, Position(Vector::Y * 0.5), body.clone()))
+        .id();
+    let falling_body = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::Y * 10.5 - Vector::X * 5.0),
+            body,
+        ))
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let velocity = app.world.get::<LinearVelocity>(moved_body).unwrap();
+    assert!((velocity.x - 1.0).abs() < 0.05);
+
+    // the body fell through the ghost floor and landed on the conveyor
+    let position = app.world.get::<Position>(falling_body).unwrap();
+    assert!(position.y < 5.0);
+}
+
+#[test]
+fn surface_velocity_moves_bodies_on_conveyor() {
+    let mut app = create_app();
+
+    #[cfg(feature = "2d")]
+    let (conveyor, body) = (Collider::cuboid(20.0, 1.0), Collider::cuboid(1.0, 1.0));
+    #[cfg(feature = "3d")]
+    let (conveyor, body) = (
+        Collider::cuboid(20.0, 1.0, 20.0),
+        Collider::cuboid(1.0, 1.0, 1.0),
+    );
+
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::NEG_Y * 0.5),
+        conveyor,
+        SurfaceVelocity(Vector::X * 2.0),
+    ));
+    let body = app
+        .world
+        .spawn((RigidBody::Dynamic, Position(Vector::Y * 0.5), body))
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let velocity = app.world.get::<LinearVelocity>(body).unwrap();
+    assert!((velocity.x - 2.0).abs() < 0.1);
+}
+
+#[cfg(feature = "physics-material")]
+#[test]
+fn physics_material_overrides_friction_and_density() {
+    let mut app = create_app();
+    #[cfg(not(feature = "async-collider"))]
+    app.add_plugins(bevy::asset::AssetPlugin::default());
+    app.add_plugins(PhysicsMaterialPlugin::default());
+    app.finish();
+
+    let ice: PhysicsMaterial = ron::de::from_str(
+        "(friction: (dynamic_coefficient: 0.0, static_coefficient: 0.0, combine_rule: Min), density: 2.0)",
+    )
+    .unwrap();
+    let ice = app.world.resource_mut::<Assets<PhysicsMaterial>>().add(ice);
+
+    #[cfg(feature = "2d")]
+    let (ground, body) = (Collider::cuboid(20.0, 1.0), Collider::cuboid(1.0, 1.0));
+    #[cfg(feature = "3d")]
+    let (ground, body) = (
+        Collider::cuboid(20.0, 1.0, 20.0),
+        Collider::cuboid(1.0, 1.0, 1.0),
+    );
+
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::NEG_Y * 0.5),
+        ground,
+        Friction::new(1.0),
+    ));
+    let body = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::Y * 0.5),
+            LinearVelocity(Vector::X * 2.0),
+            body,
+            Friction::new(1.0),
+            ice.clone(),
+        ))
+        .id();
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    // The body slides without friction, and its mass uses the density of the material
+    assert!((app.world.get::<LinearVelocity>(body).unwrap().x - 2.0).abs() < 0.01);
+    assert_relative_eq!(app.world.get::<Mass>(body).unwrap().0, 2.0, epsilon = 0.001);
+
+    // Modifying the material updates the mass of the body
+    app.world
+        .resource_mut::<Assets<PhysicsMaterial>>()
+        .get_mut(&ice)
+        .unwrap()
+        .density = 3.0;
+
+    for _ in 0..2 {
+        tick_60_fps(&mut app);
+    }
+
+    assert_relative_eq!(app.world.get::<Mass>(body).unwrap().0, 3.0, epsilon = 0.001);
+}
+
+#[cfg(feature = "physics-material")]
+#[test]
+fn physics_material_falls_back_to_rigid_body() {
+    let mut app = create_app();
+    #[cfg(not(feature = "async-collider"))]
+    app.add_plugins(bevy::asset::AssetPlugin::default());
+    app.add_plugins(PhysicsMaterialPlugin::default());
+    app.finish();
+
+    let ice = app.world.resource_mut::<Assets<PhysicsMaterial>>().add(
+        PhysicsMaterial::default()
+            .with_friction(Friction::ZERO.with_combine_rule(CoefficientCombine::Min))
+            .with_density(2.0),
+    );
+
+    #[cfg(feature = "2d")]
+    let (ground, body) = (Collider::cuboid(20.0, 1.0), Collider::cuboid(1.0, 1.0));
+    #[cfg(feature = "3d")]
+    let (ground, body) = (
+        Collider::cuboid(20.0, 1.0, 20.0),
+        Collider::cuboid(1.0, 1.0, 1.0),
+    );
+
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::NEG_Y * 0.5),
+        ground,
+        Friction::new(1.0),
+    ));
+
+    // The material is on the rigid body, and the collider is a child without a material
+    let body_entity = app
+        .world
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.5, 0.0)),
+            RigidBody::Dynamic,
+            LinearVelocity(Vector::X * 2.0),
+            ice,
+        ))
+        .with_children(|children| {
+            children.spawn((body, Friction::new(1.0), TransformBundle::default()));
+        })
+        .id();
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    // The collider uses the friction and density of the body's material
+    assert!((app.world.get::<LinearVelocity>(body_entity).unwrap().x - 2.0).abs() < 0.01);
+    assert_relative_eq!(
+        app.world.get::<Mass>(body_entity).unwrap().0,
+        2.0,
+        epsilon = 0.001
+    );
+}
+
+#[test]
+fn highest_layer_of_layer_mask_interacts() {
+    let highest = 1 << (LayerMask::BITS - 1);
+    let layers = CollisionLayers::from_bits(highest, highest);
+
+    assert!(layers.interacts_with(CollisionLayers::default()));
+    assert!(!layers.interacts_with(CollisionLayers::from_bits(1, LayerMask::MAX)));
+    assert!(SpatialQueryFilter::new()
+        .with_masks_from_bits(highest)
+        .test(Entity::PLACEHOLDER, layers));
+    assert!(!SpatialQueryFilter::new()
+        .with_masks_from_bits(highest >> 1)
+        .test(Entity::PLACEHOLDER, layers));
+}
+
+#[test]
+fn collision_groups_override_layers() {
+    let mut app = create_app();
+
+    #[cfg(feature = "2d")]
+    let ground = Collider::cuboid(2.0, 1.0);
+    #[cfg(feature = "3d")]
+    let ground = Collider::cuboid(2.0, 1.0, 2.0);
+
+    // A shared negative group never collides, even though the layers interact
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::X * -5.0 + Vector::NEG_Y * 0.5),
+        ground.clone(),
+        CollisionGroup(-1),
+    ));
+    let falling = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * -5.0 + Vector::Y * 0.5),
+            Collider::ball(0.5),
+            CollisionGroup(-1),
+        ))
+        .id();
+
+    // A shared positive group always collides, even though the layers don't interact
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::X * 5.0 + Vector::NEG_Y * 0.5),
+        ground,
+        CollisionGroup(2),
+        CollisionLayers::none(),
+    ));
+    let landing = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * 5.0 + Vector::Y * 0.5),
+            Collider::ball(0.5),
+            CollisionGroup(2),
+            CollisionLayers::none(),
+        ))
+        .id();
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    assert!(app.world.get::<Position>(falling).unwrap().y < -1.0);
+    assert!((app.world.get::<Position>(landing).unwrap().y - 0.5).abs() < 0.05);
+}
+
+#[test]
+fn collision_filter_rejects_pairs() {
+    #[derive(Component)]
+    struct Ghost;
+
+    #[derive(bevy::ecs::system::SystemParam)]
+    struct GhostFilter<'w, 's> {
+        ghosts: Query<'w, 's, (), With<Ghost>>,
+    }
+
+    impl CollisionFilter for GhostFilter<'_, '_> {
+        fn filter_pair(&self, entity1: Entity, entity2: Entity) -> bool {
+            !self.ghosts.contains(entity1) && !self.ghosts.contains(entity2)
+        }
+    }
+
+    let mut app = create_app();
+    app.add_plugins(CollisionFilterPlugin::<GhostFilter>::default());
+
+    #[cfg(feature = "2d")]
+    let ground = Collider::cuboid(20.0, 1.0);
+    #[cfg(feature = "3d")]
+    let ground = Collider::cuboid(20.0, 1.0, 20.0);
+
+    app.world
+        .spawn((RigidBody::Static, Position(Vector::NEG_Y * 0.5), ground));
+    let ghost = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * -2.0 + Vector::Y * 0.5),
+            Collider::ball(0.5),
+            Ghost,
+        ))
+        .id();
+    let ball = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * 2.0 + Vector::Y * 0.5),
+            Collider::ball(0.5),
+        ))
+        .id();
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    assert!(app.world.get::<Position>(ghost).unwrap().y < -1.0);
+    assert!((app.world.get::<Position>(ball).unwrap().y - 0.5).abs() < 0.05);
+    assert!(app
+        .world
+        .resource::<BroadCollisionPairs>()
+        .0
+        .iter()
+        .all(|(entity1, entity2)| *entity1 != ghost && *entity2 != ghost));
+}
+
+#[test]
+fn contact_impulses_measure_weight_and_send_force_events() {
+    let mut app = create_app();
+
+    #[cfg(feature = "2d")]
+    let ground = Collider::cuboid(20.0, 1.0);
+    #[cfg(feature = "3d")]
+    let ground = Collider::cuboid(20.0, 1.0, 20.0);
+
+    let ground = app
+        .world
+        .spawn((
+            RigidBody::Static,
+            Position(Vector::NEG_Y * 0.5),
+            ground,
+            ContactImpulses::default(),
+        ))
+        .id();
+    let light = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * -2.0 + Vector::Y * 0.5),
+            Collider::ball(0.5),
+            ContactImpulses::default(),
+            ContactForceThreshold(0.01),
+            SleepingDisabled,
+        ))
+        .id();
+    let heavy = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * 2.0 + Vector::Y * 0.5),
+            Collider::ball(0.5),
+            ColliderDensity(4.0),
+            ContactImpulses::default(),
+            ContactForceThreshold(1000.0),
+            SleepingDisabled,
+        ))
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+    app.world
+        .resource_mut::<Events<ContactForceEvent>>()
+        .clear();
+    tick_60_fps(&mut app);
+
+    // Resting bodies are pushed up by an impulse that cancels gravity over the frame
+    let delta_secs = app.world.resource::<Time<Physics>>().delta_seconds_f64() as Scalar;
+    let gravity = app.world.resource::<Gravity>().0;
+    for entity in [light, heavy] {
+        let mass = app.world.get::<Mass>(entity).unwrap().0;
+        let impulses = app.world.get::<ContactImpulses>(entity).unwrap();
+        assert_relative_eq!(
+            impulses.normal_impulse.y,
+            -gravity.y * mass * delta_secs,
+            max_relative = 0.1
+        );
+    }
+
+    // The ground carries the weight of both bodies
+    let light_impulses = *app.world.get::<ContactImpulses>(light).unwrap();
+    let heavy_impulses = *app.world.get::<ContactImpulses>(heavy).unwrap();
+    let ground_impulses = *app.world.get::<ContactImpulses>(ground).unwrap();
+    assert_relative_eq!(
+        ground_impulses.normal_impulse.y,
+        -(light_impulses.normal_impulse.y + heavy_impulses.normal_impulse.y),
+        max_relative = 0.001
+    );
+
+    // Only the contact of the light body exceeds its threshold
+    let events = app.world.resource::<Events<ContactForceEvent>>();
+    let events: Vec<&ContactForceEvent> = events.iter_current_update_events().collect();
+    assert_eq!(events.len(), 1);
+    assert!([events[0].entity1, events[0].entity2].contains(&light));
+}
+
+#[test]
+fn sensors_report_overlaps_without_contacts() {
+    let mut app = create_app();
+
+    #[cfg(feature = "2d")]
+    let sensor_shape = Collider::cuboid(2.0, 2.0);
+    #[cfg(feature = "3d")]
+    let sensor_shape = Collider::cuboid(2.0, 2.0, 2.0);
+
+    let sensor = app
+        .world
+        .spawn((
+            RigidBody::Static,
+            sensor_shape,
+            Sensor,
+            CollisionEventsEnabled,
+        ))
+        .id();
+    let ball = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::Y * 3.0),
+            Collider::ball(0.5),
+        ))
+        .id();
+
+    // Events can stay in the buffers for several frames, so read each event exactly once
+    let mut started_reader = ManualEventReader::<CollisionStarted>::default();
+    let mut ended_reader = ManualEventReader::<CollisionEnded>::default();
+    let mut entered_reader = ManualEventReader::<SensorEntered>::default();
+    let mut exited_reader = ManualEventReader::<SensorExited>::default();
+    let mut entered = vec![];
+    let mut exited = vec![];
+    let mut started = vec![];
+    let mut ended = vec![];
+    let mut was_inside = false;
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+
+        let world = &app.world;
+        started.extend(
+            started_reader
+                .read(world.resource::<Events<CollisionStarted>>())
+                .cloned(),
+        );
+        ended.extend(
+            ended_reader
+                .read(world.resource::<Events<CollisionEnded>>())
+                .cloned(),
+        );
+        entered.extend(
+            entered_reader
+                .read(world.resource::<Events<SensorEntered>>())
+                .cloned(),
+        );
+        exited.extend(
+            exited_reader
+                .read(world.resource::<Events<SensorExited>>())
+                .cloned(),
+        );
+        was_inside |= world
+            .get::<CollidingEntities>(sensor)
+            .unwrap()
+            .contains(&ball);
+
+        // Sensors never generate contacts
+        assert!(!world.resource::<Collisions>().contains(sensor, ball));
+    }
+
+    // The ball falls through the sensor
+    assert!(app.world.get::<Position>(ball).unwrap().y < -2.0);
+    assert!(was_inside);
+    assert_eq!(entered, vec![SensorEntered(sensor, ball)]);
+    assert_eq!(exited, vec![SensorExited(sensor, ball)]);
+    assert_eq!(started, vec![CollisionStarted(sensor, ball)]);
+    assert_eq!(ended, vec![CollisionEnded(sensor, ball)]);
+    assert!(!app
+        .world
+        .get::<CollidingEntities>(ball)
+        .unwrap()
+        .contains(&sensor));
+}
+
+#[test]
+fn sensor_overlaps_are_kept_for_sleeping_bodies() {
+    for algorithm in [
+        BroadPhaseAlgorithm::SweepAndPrune,
+        BroadPhaseAlgorithm::DynamicAabbTree,
+    ] {
+        let mut app = create_app();
+
+        app.insert_resource(Gravity::ZERO)
+            .insert_resource(algorithm);
+
+        #[cfg(feature = "2d")]
+        let sensor_shape = Collider::cuboid(2.0, 2.0);
+        #[cfg(feature = "3d")]
+        let sensor_shape = Collider::cuboid(2.0, 2.0, 2.0);
+
+        // A kinematic sensor that doesn't move and a dynamic ball inside of it that falls asleep.
+        // The broad phase skips the pair once neither of them moves.
+        let sensor = app
+            .world
+            .spawn((RigidBody::Kinematic, sensor_shape, Sensor))
+            .id();
+        let ball = app
+            .world
+            .spawn((RigidBody::Dynamic, Collider::ball(0.5)))
+            .id();
+
+        let mut exited_reader = ManualEventReader::<SensorExited>::default();
+        let mut exited = vec![];
+        for _ in 0..120 {
+            tick_60_fps(&mut app);
+            exited.extend(
+                exited_reader
+                    .read(app.world.resource::<Events<SensorExited>>())
+                    .cloned(),
+            );
+        }
+
+        assert!(app.world.get::<Sleeping>(ball).is_some());
+        assert!(exited.is_empty(), "{algorithm:?}");
+        assert!(app
+            .world
+            .resource::<SensorOverlaps>()
+            .contains(sensor, ball));
+        assert!(app
+            .world
+            .get::<CollidingEntities>(ball)
+            .unwrap()
+            .contains(&sensor));
+    }
+}
+
+#[test]
+fn spatial_query_pipeline_is_updated_from_aabb_tree() {
+    let mut app = create_app();
+
+    app.insert_resource(Gravity::ZERO)
+        .insert_resource(BroadPhaseAlgorithm::DynamicAabbTree);
+
+    // Balls that move at different speeds, so that they leave their enlarged AABBs at different times
+    let mut balls = (0..5)
+        .map(|i| {
+            app.world
+                .spawn((
+                    RigidBody::Dynamic,
+                    Position(Vector::X * 3.0 * i as Scalar),
+                    LinearVelocity(Vector::Y * 5.0 * (i + 1) as Scalar),
+                    Collider::ball(0.5),
+                ))
+                .id()
+        })
+        .collect::<Vec<_>>();
+
+    for frame in 0..60 {
+        if frame == 30 {
+            let ball = balls.remove(0);
+            app.world.despawn(ball);
+        }
+
+        tick_60_fps(&mut app);
+
+        let pipeline = app.world.resource::<SpatialQueryPipeline>();
+
+        // The pipeline is updated incrementally instead of being rebuilt
+        assert_eq!(pipeline.leaf_aabbs.len(), balls.len());
+
+        // Cast rays down at every ball and between the balls
+        for i in 0..5 {
+            let x = 3.0 * i as Scalar;
+            let ball = balls
+                .iter()
+                .copied()
+                .find(|&ball| app.world.get::<Position>(ball).unwrap().x == x);
+            let hit = pipeline.cast_ray(
+                Vector::X * x + Vector::Y * 1000.0,
+                Vector::NEG_Y,
+                2000.0,
+                true,
+                SpatialQueryFilter::default(),
+            );
+
+            if let Some(ball) = ball {
+                let hit = hit.expect("ray should hit the ball");
+                let y = app.world.get::<Position>(ball).unwrap().y;
+                assert_eq!(hit.entity, ball);
+                assert_relative_eq!(hit.time_of_impact, 1000.0 - y - 0.5, epsilon = 0.001);
+            } else {
+                assert!(hit.is_none());
+            }
+
+            assert!(pipeline
+                .cast_ray(
+                    Vector::X * (x + 1.5) + Vector::Y * 1000.0,
+                    Vector::NEG_Y,
+                    2000.0,
+                    true,
+                    SpatialQueryFilter::default(),
+                )
+                .is_none());
+        }
+    }
+}
+
+#[test]
+fn collision_events_are_only_sent_for_opted_in_entities() {
+    // Returns the `CollisionStarted` events, the collisions in the `OnCollisionStart` of the listener
+    // and the ball, and the entities of the listener, ball and ground
+    let run = |config: ContactReportingConfig| {
+        let mut app = create_app();
+        app.insert_resource(config);
+
+        #[cfg(feature = "2d")]
+        let ground = Collider::cuboid(20.0, 1.0);
+        #[cfg(feature = "3d")]
+        let ground = Collider::cuboid(20.0, 1.0, 20.0);
+
+        let ground = app
+            .world
+            .spawn((RigidBody::Static, Position(Vector::NEG_Y * 0.5), ground))
+            .id();
+        let listener = app
+            .world
+            .spawn((
+                RigidBody::Dynamic,
+                Position(Vector::X * -2.0 + Vector::Y),
+                Collider::ball(0.5),
+                CollisionEventsEnabled,
+                OnCollisionStart::default(),
+            ))
+            .id();
+        // The ball has a buffer, but it hasn't opted in to collision events
+        let ball = app
+            .world
+            .spawn((
+                RigidBody::Dynamic,
+                Position(Vector::X * 2.0 + Vector::Y),
+                Collider::ball(0.5),
+                OnCollisionStart::default(),
+            ))
+            .id();
+
+        // Events can stay in the buffers for several frames, so read each event exactly once
+        let mut started_reader = ManualEventReader::<CollisionStarted>::default();
+        let mut started_events = vec![];
+        let mut started_buffer = vec![];
+        let mut ball_buffer = vec![];
+        for _ in 0..60 {
+            tick_60_fps(&mut app);
+            started_events.extend(
+                started_reader
+                    .read(app.world.resource::<Events<CollisionStarted>>())
+                    .cloned(),
+            );
+            started_buffer.extend(
+                app.world
+                    .get::<OnCollisionStart>(listener)
+                    .unwrap()
+                    .iter()
+                    .copied(),
+            );
+            ball_buffer.extend(
+                app.world
+                    .get::<OnCollisionStart>(ball)
+                    .unwrap()
+                    .iter()
+                    .copied(),
+            );
+        }
+
+        assert!(app
+            .world
+            .get::<CollidingEntities>(ball)
+            .unwrap()
+            .contains(&ground));
+
+        (
+            started_events,
+            started_buffer,
+            ball_buffer,
+            [listener, ball, ground],
+        )
+    };
+    let involves = |entity: Entity| {
+        move |CollisionStarted(entity1, entity2): &CollisionStarted| {
+            *entity1 == entity || *entity2 == entity
+        }
+    };
+
+    // By default, only the collisions of the listener are sent as events,
+    // and the ball that hasn't opted in gets no events
+    let (started_events, started_buffer, ball_buffer, [listener, ball, ground]) =
+        run(ContactReportingConfig::default());
+    assert!(!started_events.is_empty());
+    assert!(started_events.iter().all(involves(listener)));
+    assert!(!started_events.iter().any(involves(ball)));
+    assert!(ball_buffer.is_empty());
+
+    // Only the listener receives collisions in its `OnCollisionStart`
+    assert!(!started_buffer.is_empty());
+    assert!(started_buffer.iter().all(|data| *data
+        == CollisionEventData {
+            collider: listener,
+            other_collider: ground,
+            other_body: Some(ground),
+        }));
+
+    // Without requiring opting in, the collisions of both balls are sent as events,
+    // but the ball still doesn't receive collisions in its `OnCollisionStart`
+    let (started_events, started_buffer, ball_buffer, [listener, ball, _]) =
+        run(ContactReportingConfig {
+            require_opt_in: false,
+        });
+    assert!(started_events.iter().any(involves(listener)));
+    assert!(started_events.iter().any(involves(ball)));
+    assert!(!started_buffer.is_empty());
+    assert!(ball_buffer.is_empty());
+}
+
+#[test]
+fn query_dispatcher_is_used_by_collisions_and_spatial_queries() {
+    use parry::{
+        math::{Isometry, Real, Vector as ParryVector},
+        query::{
+            ClosestPoints, Contact, ContactManifold, ContactManifoldsWorkspace,
+            DefaultQueryDispatcher, NonlinearRigidMotion, PersistentQueryDispatcher,
+            QueryDispatcher, Unsupported, TOI,
+        },
+        shape::Shape,
+    };
+
+    /// A dispatcher that makes balls pass through each other and leaves
+    /// the other pairs to the next dispatcher in the chain.
+    struct GhostBallDispatcher;
+
+    impl GhostBallDispatcher {
+        fn handles(g1: &dyn Shape, g2: &dyn Shape) -> Result<(), Unsupported> {
+            if g1.as_ball().is_some() && g2.as_ball().is_some() {
+                Ok(())
+            } else {
+                Err(Unsupported)
+            }
+        }
+    }
+
+    impl QueryDispatcher for GhostBallDispatcher {
+        fn intersection_test(
+            &self,
+            _: &Isometry<Real>,
+            g1: &dyn Shape,
+            g2: &dyn Shape,
+        ) -> Result<bool, Unsupported> {
+            Self::handles(g1, g2).map(|_| false)
+        }
+
+        fn distance(
+            &self,
+            _: &Isometry<Real>,
+            g1: &dyn Shape,
+            g2: &dyn Shape,
+        ) -> Result<Real, Unsupported> {
+            Self::handles(g1, g2).map(|_| Real::MAX)
+        }
+
+        fn contact(
+            &self,
+            _: &Isometry<Real>,
+            g1: &dyn Shape,
+            g2: &dyn Shape,
+            _: Real,
+        ) -> Result<Option<Contact>, Unsupported> {
+            Self::handles(g1, g2).map(|_| None)
+        }
+
+        fn closest_points(
+            &self,
+            _: &Isometry<Real>,
+            g1: &dyn Shape,
+            g2: &dyn Shape,
+            _: Real,
+        ) -> Result<ClosestPoints, Unsupported> {
+            Self::handles(g1, g2).map(|_| ClosestPoints::Disjoint)
+        }
+
+        fn time_of_impact(
+            &self,
+            _: &Isometry<Real>,
+            _: &ParryVector<Real>,
+            g1: &dyn Shape,
+            g2: &dyn Shape,
+            _: Real,
+            _: bool,
+        ) -> Result<Option<TOI>, Unsupported> {
+            Self::handles(g1, g2).map(|_| None)
+        }
+
+        fn nonlinear_time_of_impact(
+            &self,
+            _: &NonlinearRigidMotion,
+            g1: &dyn Shape,
+            _: &NonlinearRigidMotion,
+            g2: &dyn Shape,
+            _: Real,
+            _: Real,
+            _: bool,
+        ) -> Result<Option<TOI>, Unsupported> {
+            Self::handles(g1, g2).map(|_| None)
+        }
+    }
+
+    impl PersistentQueryDispatcher for GhostBallDispatcher {
+        fn contact_manifolds(
+            &self,
+            _: &Isometry<Real>,
+            g1: &dyn Shape,
+            g2: &dyn Shape,
+            _: Real,
+            manifolds: &mut Vec<ContactManifold<(), ()>>,
+            _: &mut Option<ContactManifoldsWorkspace>,
+        ) -> Result<(), Unsupported> {
+            Self::handles(g1, g2).map(|_| manifolds.clear())
+        }
+
+        fn contact_manifold_convex_convex(
+            &self,
+            _: &Isometry<Real>,
+            g1: &dyn Shape,
+            g2: &dyn Shape,
+            _: Real,
+            manifold: &mut ContactManifold<(), ()>,
+        ) -> Result<(), Unsupported> {
+            Self::handles(g1, g2).map(|_| manifold.clear())
+        }
+    }
+
+    let mut app = create_app();
+    app.insert_resource(PhysicsQueryDispatcher::new(
+        GhostBallDispatcher.chain(DefaultQueryDispatcher),
+    ));
+
+    let ghost = app
+        .world
+        .spawn((RigidBody::Static, Collider::ball(1.0)))
+        .id();
+    #[cfg(feature = "2d")]
+    let ground_shape = Collider::cuboid(2.0, 0.5);
+    #[cfg(feature = "3d")]
+    let ground_shape = Collider::cuboid(2.0, 0.5, 2.0);
+    let ground = app
+        .world
+        .spawn((
+            RigidBody::Static,
+            Position(Vector::NEG_Y * 3.0),
+            ground_shape,
+        ))
+        .id();
+    let ball = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::Y * 3.0),
+            Collider::ball(0.5),
+        ))
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+        assert!(!app.world.resource::<Collisions>().contains(ghost, ball));
+    }
+
+    // The ball falls through the other ball and lands on the ground
+    let position = app.world.get::<Position>(ball).unwrap();
+    assert!(position.y < -2.0 && position.y > -3.0);
+    assert!(app.world.resource::<Collisions>().contains(ground, ball));
+
+    // Spatial queries use the same dispatcher
+    let intersections = |shape: Collider| {
+        app.world
+            .resource::<SpatialQueryPipeline>()
+            .shape_intersections(
+                &shape,
+                Vector::ZERO,
+                default(),
+                SpatialQueryFilter::default(),
+            )
+    };
+    assert!(intersections(Collider::ball(0.5)).is_empty());
+    #[cfg(feature = "2d")]
+    assert_eq!(intersections(Collider::cuboid(0.5, 0.5)), vec![ghost]);
+    #[cfg(feature = "3d")]
+    assert_eq!(intersections(Collider::cuboid(0.5, 0.5, 0.5)), vec![ghost]);
+}
+
+#[test]
+fn voxels_collide_without_bumps_and_update_incrementally() {
+    let mut app = create_app();
+
+    // A floor with its top surface at y = 0. In 3D, the bodies are placed
+    // at the centers of the voxels along the Z axis.
+    #[cfg(feature = "2d")]
+    let (voxels, hole, offset) = (
+        (-8..8).map(|x| IVec2::new(x, -1)).collect::<Vec<_>>(),
+        IVec2::new(4, -1),
+        Vector::ZERO,
+    );
+    #[cfg(feature = "3d")]
+    let (voxels, hole, offset) = (
+        (-8..8)
+            .flat_map(|x| (-2..2).map(move |z| IVec3::new(x, -1, z)))
+            .collect::<Vec<_>>(),
+        IVec3::new(4, -1, 0),
+        Vector::Z * 0.5,
+    );
+    let floor = app
+        .world
+        .spawn((RigidBody::Static, Collider::voxels(Vector::ONE, &voxels)))
+        .id();
+
+    #[cfg(feature = "2d")]
+    let box_shape = Collider::cuboid(0.5, 0.5);
+    #[cfg(feature = "3d")]
+    let box_shape = Collider::cuboid(0.5, 0.5, 0.5);
+    let slider = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * -6.0 + Vector::Y * 0.25 + offset),
+            box_shape,
+            LinearVelocity(Vector::X * 3.0),
+            Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
+        ))
+        .id();
+    let ball = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * 4.5 + Vector::Y + offset),
+            Collider::ball(0.25),
+        ))
+        .id();
+
+    // The box slides over the seams between the voxels without bumping up
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+        let velocity = app.world.get::<LinearVelocity>(slider).unwrap();
+        assert!(velocity.y.abs() < 0.5, "box bumped: {velocity:?}");
+    }
+    let slider_position = app.world.get::<Position>(slider).unwrap();
+    assert!(slider_position.x > -4.0);
+    assert!((slider_position.y - 0.25).abs() < 0.05);
+    let ball_position = app.world.get::<Position>(ball).unwrap();
+    assert!((ball_position.y - 0.25).abs() < 0.05);
+
+    // Raycasts hit the top of the voxels
+    let cast_ray = |app: &App| {
+        app.world.resource::<SpatialQueryPipeline>().cast_ray(
+            Vector::X * 4.5 + Vector::Y * 5.0 + offset,
+            Vector::NEG_Y,
+            100.0,
+            true,
+            SpatialQueryFilter::default().without_entities([ball, slider]),
+        )
+    };
+    let hit = cast_ray(&app).unwrap();
+    assert_eq!(hit.entity, floor);
+    assert_relative_eq!(hit.time_of_impact, 5.0, epsilon = 0.001);
+    assert_relative_eq!(hit.normal, Vector::Y, epsilon = 0.001);
+
+    // Clearing a voxel makes a hole that the ball falls through
+    let mut collider = app.world.get_mut::<Collider>(floor).unwrap();
+    assert!(collider.set_voxel(hole, false));
+    assert!(!collider.set_voxel(hole, false));
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    assert!(app.world.get::<Position>(ball).unwrap().y < -1.0);
+    assert!(cast_ray(&app).is_none());
+}
+
+#[test]
+fn voxels_far_apart_are_stored_sparsely() {
+    #[cfg(feature = "2d")]
+    let (min, zero, max) = (IVec2::MIN, IVec2::ZERO, IVec2::MAX);
+    #[cfg(feature = "3d")]
+    let (min, zero, max) = (IVec3::MIN, IVec3::ZERO, IVec3::MAX);
+    let mut voxels = Voxels::new(Vector::ONE, &[min, max]);
+    assert_eq!(voxels.len(), 2);
+    assert!(voxels.contains(min) && voxels.contains(max));
+    assert_eq!(voxels.iter().collect::<Vec<_>>(), vec![min, max]);
+    assert!(voxels.is_exposed(max));
+
+    assert!(voxels.set_voxel(zero, true));
+    assert!(voxels.set_voxel(min, false));
+    assert_eq!(voxels.iter().collect::<Vec<_>>(), vec![zero, max]);
+}
+
+#[test]
+fn scaled_voxels_collide() {
+    let mut app = create_app();
+
+    // A floor with its top surface at y = 0, scaled to twice its size
+    #[cfg(feature = "2d")]
+    let (voxels, offset) = (
+        (-8..8).map(|x| IVec2::new(x, -1)).collect::<Vec<_>>(),
+        Vector::ZERO,
+    );
+    #[cfg(feature = "3d")]
+    let (voxels, offset) = (
+        (-8..8)
+            .flat_map(|x| (-2..2).map(move |z| IVec3::new(x, -1, z)))
+            .collect::<Vec<_>>(),
+        Vector::Z,
+    );
+    app.world.spawn((
+        RigidBody::Static,
+        Collider::voxels(Vector::ONE, &voxels),
+        TransformBundle::from_transform(Transform::from_scale(Vec3::splat(2.0))),
+    ));
+
+    // The ball is above a voxel that only exists in the scaled floor
+    let ball = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * 12.0 + Vector::Y + offset),
+            Collider::ball(0.25),
+        ))
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let ball_position = app.world.get::<Position>(ball).unwrap();
+    assert!(
+        (ball_position.y - 0.25).abs() < 0.05,
+        "ball fell through the scaled voxels: {ball_position:?}"
+    );
+}
+
+#[test]
+fn sdf_colliders_collide_and_support_queries() {
+    let mut app = create_app();
+
+    // Flat ground at y = 0 with a crater of radius 2 subtracted from it
+    let crater = Vector::X * 4.0;
+    let terrain =
+        SignedDistanceField::from_fn(Vector::splat(-10.0), Vector::splat(10.0), move |point| {
+            point.y.max(2.0 - point.distance(crater))
+        });
+    let ground = app
+        .world
+        .spawn((RigidBody::Static, Collider::sdf(terrain)))
+        .id();
+
+    #[cfg(feature = "2d")]
+    let box_shape = Collider::cuboid(0.5, 0.5);
+    #[cfg(feature = "3d")]
+    let box_shape = Collider::cuboid(0.5, 0.5, 0.5);
+    let resting_box = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(Vector::X * -4.0 + Vector::Y * 0.5),
+            box_shape,
+        ))
+        .id();
+    let ball = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Position(crater + Vector::Y),
+            Collider::ball(0.25),
+        ))
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    // The box rests on the flat ground and the ball at the bottom of the crater
+    let box_position = app.world.get::<Position>(resting_box).unwrap();
+    assert_relative_eq!(box_position.y, 0.25, epsilon = 0.05);
+    let ball_position = app.world.get::<Position>(ball).unwrap();
+    assert_relative_eq!(ball_position.0, crater - Vector::Y * 1.75, epsilon = 0.05);
+
+    // Raycasts march to the surface of the field
+    let cast_ray = |origin: Vector| {
+        app.world.resource::<SpatialQueryPipeline>().cast_ray(
+            origin,
+            Vector::NEG_Y,
+            100.0,
+            true,
+            SpatialQueryFilter::default().without_entities([resting_box, ball]),
+        )
+    };
+    let hit = cast_ray(Vector::X * -2.0 + Vector::Y * 5.0).unwrap();
+    assert_eq!(hit.entity, ground);
+    assert_relative_eq!(hit.time_of_impact, 5.0, epsilon = 0.01);
+    assert_relative_eq!(hit.normal, Vector::Y, epsilon = 0.01);
+    let hit = cast_ray(crater + Vector::Y * 5.0).unwrap();
+    assert_relative_eq!(hit.time_of_impact, 7.0, epsilon = 0.01);
+
+    // Grids of samples are interpolated between the samples
+    #[cfg(feature = "2d")]
+    let dimensions = UVec2::splat(5);
+    #[cfg(feature = "3d")]
+    let dimensions = UVec3::splat(5);
+    let samples = (0..dimensions.to_array().iter().product::<u32>())
+        .map(|i| (i / 5 % 5) as Scalar - 2.5)
+        .collect();
+    let grid = SignedDistanceField::from_grid(Vector::splat(-2.0), 1.0, dimensions, samples);
+    assert_relative_eq!(grid.distance(Vector::Y * 0.25), -0.25, epsilon = 0.001);
+    assert_relative_eq!(grid.normal(Vector::ZERO), Vector::Y, epsilon = 0.001);
+
+    // The mass properties are integrated over the volume of the field
+    let sphere = SignedDistanceField::from_fn(Vector::splat(-1.5), Vector::splat(1.5), |point| {
+        point.length() - 1.0
+    });
+    #[cfg(feature = "2d")]
+    let volume = std::f64::consts::PI as Scalar;
+    #[cfg(feature = "3d")]
+    let volume = std::f64::consts::PI as Scalar * 4.0 / 3.0;
+    let mass = Collider::sdf(sphere).mass_properties(1.0).mass();
+    assert_relative_eq!(mass, volume, max_relative = 0.02);
+}
+
+#[cfg(all(
+    feature = "2d",
+    feature = "collider-from-image",
+    feature = "collider-from-mesh"
+))]
+#[test]
+fn colliders_are_generated_from_image_alpha_and_2d_meshes() {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    let image_from_alpha = |alpha: fn(u32, u32) -> u8| {
+        Image::new(
+            Extent3d {
+                width: 16,
+                height: 16,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            (0..16 * 16)
+                .flat_map(|i| [255, 255, 255, alpha(i % 16, i / 16)])
+                .collect(),
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    };
+
+    // An opaque 8x8 square in the middle of the image is outlined halfway between the pixel centers
+    let image = image_from_alpha(|x, y| {
+        if (4..12).contains(&x) && (4..12).contains(&y) {
+            255
+        } else {
+            0
+        }
+    });
+    let polyline = Collider::from_image_alpha(&image).unwrap();
+    assert!(polyline.shape().as_polyline().is_some());
+    let aabb = polyline.shape().compute_local_aabb();
+    assert_relative_eq!(Vector::from(aabb.mins), Vector::splat(-4.0), epsilon = 0.5);
+    assert_relative_eq!(Vector::from(aabb.maxs), Vector::splat(4.0), epsilon = 0.5);
+
+    let config = ImageColliderConfig {
+        shape: ImageColliderShape::ConvexDecomposition(VHACDParameters::default()),
+        ..default()
+    };
+    let decomposition = Collider::from_image_alpha_with_config(&image, &config).unwrap();
+    assert!(decomposition.shape().as_compound().is_some());
+
+    // Fully transparent images have no outlines
+    assert!(Collider::from_image_alpha(&image_from_alpha(|_, _| 0)).is_none());
+
+    let mesh = Mesh::from(shape::Quad::new(Vec2::new(2.0, 1.0)));
+    let hull = Collider::from_mesh_2d(&mesh, &ComputedCollider::ConvexHull).unwrap();
+    let aabb = hull.shape().compute_local_aabb();
+    assert_relative_eq!(
+        Vector::from(aabb.maxs),
+        Vector::new(1.0, 0.5),
+        epsilon = 0.001
+    );
+    let decomposition = Collider::from_mesh_2d(
+        &mesh,
+        &ComputedCollider::ConvexDecomposition(VHACDParameters::default()),
+    )
+    .unwrap();
+    assert!(decomposition.shape().as_compound().is_some());
+}
+
+#[test]
+fn collision_matrix_filters_named_layers() {
+    let mut app = create_app();
+
+    #[cfg(feature = "collision-matrix-asset")]
+    let matrix: CollisionMatrix = ron::de::from_str(
+        r#"(layers: ["Player", "Ground"], interactions: [("Player", "Ground")])"#,
+    )
+    .unwrap();
+    #[cfg(not(feature = "collision-matrix-asset"))]
+    let matrix = CollisionMatrix::new()
+        .with_layer("Player")
+        .with_interaction("Player", "Ground");
+
+    assert!(matrix.interacts("Player", "Ground"));
+    assert!(!matrix.interacts("Player", "Player"));
+
+    let player_index = matrix.layer_index("Player").unwrap();
+    let ground_index = matrix.layer_index("Ground").unwrap();
+    assert!(matrix.interacts_by_index(player_index, ground_index));
+    assert!(!matrix.interacts_by_index(player_index, player_index));
+
+    app.insert_resource(matrix);
+
+    #[cfg(feature = "2d")]
+    let ground = Collider::cuboid(20.0, 1.0);
+    #[cfg(feature = "3d")]
+    let ground = Collider::cuboid(20.0, 1.0, 20.0);
+
+    let ground = app
+        .world
+        .spawn((
+            RigidBody::Static,
+            Position(Vector::NEG_Y * 0.5),
+            ground,
+            CollisionLayerName::new("Ground"),
+        ))
+        .id();
+
+    // Two players on top of each other fall through each other but land on the ground
+    let players = [1.0, 1.5].map(|y| {
+        app.world
+            .spawn((
+                RigidBody::Dynamic,
+                Position(Vector::Y * y),
+                Collider::ball(0.5),
+                CollisionLayerName::new("Player"),
+                CollisionLayers::from_bits(1, 1),
+            ))
+            .id()
+    });
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    for player in players {
+        let position = app.world.get::<Position>(player).unwrap();
+        assert!((position.y - 0.5).abs() < 0.05);
+
+        // The players interact according to their own layers, but the matrix takes precedence
+        // and the layers are left untouched
+        assert_eq!(
+            app.world.get::<CollisionLayers>(player),
+            Some(&CollisionLayers::from_bits(1, 1))
+        );
+        assert_eq!(
+            app.world
+                .get::<CollisionMatrixLayer>(player)
+                .and_then(CollisionMatrixLayer::index),
+            Some(player_index)
+        );
+    }
+
+    // Ray casts on the player layer pass through the players and hit the ground,
+    // while ray casts without a matrix layer use the masks
+    let pipeline = app.world.resource::<SpatialQueryPipeline>();
+    let cast_down = |query_filter| {
+        pipeline
+            .cast_ray(Vector::Y * 5.0, Vector::NEG_Y, 10.0, true, query_filter)
+            .map(|hit| hit.entity)
+    };
+    assert_eq!(
+        cast_down(SpatialQueryFilter::new().with_matrix_layer(player_index)),
+        Some(ground)
+    );
+    assert!(cast_down(SpatialQueryFilter::new()).is_some_and(|entity| players.contains(&entity)));
+}
+
+#[test]
+fn character_controller_lands_and_slides_along_wall() {
+    let mut app = create_app();
+
+    app.add_systems(
+        Update,
+        |mut characters: Query<&mut LinearVelocity, With<CharacterController>>| {
+            for mut lin_vel in &mut characters {
+                lin_vel.x = 2.0;
+                lin_vel.y -= 9.81 / 60.0;
+            }
+        },
+    );
+
+    // ground with its top at y = 0 and a wall at x = 3
+    #[cfg(feature = "2d")]
+    let (ground, wall) = (Collider::cuboid(20.0, 1.0), Collider::cuboid(0.2, 10.0));
+    #[cfg(feature = "3d")]
+    let (ground, wall) = (
+        Collider::cuboid(20.0, 1.0, 20.0),
+        Collider::cuboid(0.2, 10.0, 20.0),
+    );
+    app.world
+        .spawn((RigidBody::Static, Position(Vector::NEG_Y * 0.5), ground));
+    app.world
+        .spawn((RigidBody::Static, Position(Vector::X * 3.0), wall));
+
+    // The 2D defaults are in pixels, so use the 3D defaults that are in meters
+    #[cfg(feature = "2d")]
+    let controller = CharacterController::default()
+        .with_step_height(0.25)
+        .with_snap_to_ground(0.2)
+        .with_skin_width(0.01);
+    #[cfg(feature = "3d")]
+    let controller = CharacterController::default();
+
+    let character = app
+        .world
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, 2.0, 0.0)),
+            RigidBody::Kinematic,
+            Collider::ball(0.5),
+            controller,
+        ))
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let position = app.world.get::<Position>(character).unwrap();
+    let state = app
+        .world
+        .get::<CharacterControllerState>(character)
+        .unwrap();
+
+    assert!(state.grounded);
+    assert!(state.touching_wall);
+    assert!(position.y > 0.49 && position.y < 0.55);
+    assert!(position.x > 2.3 && position.x < 2.4);
+}
+
+#[test]
+fn soft_body_lands_on_ground_and_keeps_its_shape() {
+    let mut app = create_app();
+
+    // ground with its top at y = 0
+    #[cfg(feature = "2d")]
+    let ground = Collider::cuboid(20.0, 1.0);
+    #[cfg(feature = "3d")]
+    let ground = Collider::cuboid(20.0, 1.0, 20.0);
+    app.world
+        .spawn((RigidBody::Static, Position(Vector::NEG_Y * 0.5), ground));
+
+    // a 5x5 grid of particles
+    let mut positions = vec![];
+    let mut triangles = vec![];
+    for i in 0..5 {
+        for j in 0..5 {
+            #[cfg(feature = "2d")]
+            positions.push(Vector::new(i as Scalar * 0.25, 1.0 + j as Scalar * 0.25));
+            #[cfg(feature = "3d")]
+            positions.push(Vector::new(i as Scalar * 0.25, 1.0, j as Scalar * 0.25));
+
+            if i < 4 && j < 4 {
+                let index = i * 5 + j;
+                triangles.push([index, index + 5, index + 1]);
+                triangles.push([index + 1, index + 5, index + 6]);
+            }
+        }
+    }
+
+    let soft_body = app
+        .world
+        .spawn(
+            SoftBody::from_triangles(positions, triangles)
+                .with_particle_radius(0.05)
+                .with_bending_compliance(0.0),
+        )
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let soft_body = app.world.get::<SoftBody>(soft_body).unwrap();
+    let lowest = soft_body
+        .particles
+        .iter()
+        .map(|particle| particle.position.y)
+        .fold(Scalar::MAX, Scalar::min);
+
+    // the particles rest on the ground without stretching
+    assert!(lowest > 0.04 && lowest < 0.06);
+    for constraint in soft_body.distance_constraints.iter() {
+        let length = soft_body.particles[constraint.particle1]
+            .position
+            .distance(soft_body.particles[constraint.particle2].position);
+        assert_relative_eq!(length, constraint.rest_length, epsilon = 0.01);
+    }
+    assert!(soft_body
+        .particles
+        .iter()
+        .all(|particle| particle.velocity.length() < 0.01));
+}
+
+#[test]
+fn light_body_floats_on_fluid() {
+    let mut app = create_app();
+
+    // a tank with its floor at y = 0 and walls around the area from 0 to 1
+    #[cfg(feature = "2d")]
+    let tank = [
+        (Vector::new(0.5, -0.5), Collider::cuboid(3.0, 1.0)),
+        (Vector::new(-0.5, 1.5), Collider::cuboid(1.0, 3.0)),
+        (Vector::new(1.5, 1.5), Collider::cuboid(1.0, 3.0)),
+    ];
+    #[cfg(feature = "3d")]
+    let tank = [
+        (Vector::new(0.5, -0.5, 0.5), Collider::cuboid(3.0, 1.0, 3.0)),
+        (Vector::new(-0.5, 1.5, 0.5), Collider::cuboid(1.0, 3.0, 3.0)),
+        (Vector::new(1.5, 1.5, 0.5), Collider::cuboid(1.0, 3.0, 3.0)),
+        (Vector::new(0.5, 1.5, -0.5), Collider::cuboid(3.0, 3.0, 1.0)),
+        (Vector::new(0.5, 1.5, 1.5), Collider::cuboid(3.0, 3.0, 1.0)),
+    ];
+    for (position, collider) in tank {
+        app.world
+            .spawn((RigidBody::Static, Position(position), collider));
+    }
+
+    #[cfg(feature = "2d")]
+    let (max, collider) = (Vector::new(1.0, 0.5), Collider::cuboid(0.3, 0.3));
+    #[cfg(feature = "3d")]
+    let (max, collider) = (Vector::new(1.0, 0.5, 1.0), Collider::cuboid(0.3, 0.3, 0.3));
+
+    app.world
+        .spawn(Fluid::new(0.1).with_particles_in_aabb(Vector::ZERO, max));
+
+    let body = app
+        .world
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.5, 0.8, 0.5)),
+            RigidBody::Dynamic,
+            collider,
+            ColliderDensity(0.5),
+        ))
+        .id();
+
+    for _ in 0..180 {
+        tick_60_fps(&mut app);
+    }
+
+    // the body floats instead of sinking to the floor, where its center would be at y = 0.15
+    let position = app.world.get::<Position>(body).unwrap();
+    assert!(position.y > 0.25 && position.y < 0.6);
+}
+
+#[cfg(feature = "3d")]
+#[test]
+fn vehicle_rests_on_suspension_and_drives_forward() {
+    let mut app = create_app();
+
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::NEG_Y * 0.5),
+        Collider::cuboid(100.0, 1.0, 100.0),
+    ));
+
+    let vehicle = app
+        .world
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, 1.0, 0.0)),
+            RigidBody::Dynamic,
+            Collider::cuboid(1.0, 0.5, 2.0),
+            Vehicle::new(20.0, 40.0),
+        ))
+        .with_children(|children| {
+            for x in [-0.5, 0.5] {
+                children.spawn(Wheel::new(Vector::new(x, -0.25, 0.8), 0.3).with_driven(true));
+                children.spawn(Wheel::new(Vector::new(x, -0.25, -0.8), 0.3));
+            }
+        })
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    // the suspension holds the vehicle up above the ground
+    let position = app.world.get::<Position>(vehicle).unwrap();
+    assert!(position.y > 0.75 && position.y < 0.9);
+    assert!(app
+        .world
+        .query::<&WheelState>()
+        .iter(&app.world)
+        .all(|state| state.grounded));
+
+    app.world.get_mut::<Vehicle>(vehicle).unwrap().throttle = 1.0;
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    // the vehicle drives forward along the negative Z axis
+    let lin_vel = app.world.get::<LinearVelocity>(vehicle).unwrap();
+    assert!(lin_vel.z < -1.0);
+    assert!(lin_vel.x.abs() < 0.1);
+}
+
+#[cfg(feature = "2d")]
+#[test]
+fn vehicle_rests_on_suspension_and_drives_forward() {
+    let mut app = create_app();
+
+    app.world.spawn((
+        RigidBody::Static,
+        Position(Vector::NEG_Y * 0.5),
+        Collider::cuboid(100.0, 1.0),
+    ));
+
+    let vehicle = app
+        .world
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, 1.0, 0.0)),
+            RigidBody::Dynamic,
+            Collider::cuboid(2.0, 0.5),
+            Vehicle::new(20.0, 40.0),
+        ))
+        .with_children(|children| {
+            children.spawn(Wheel::new(Vector::new(0.8, -0.25), 0.3).with_driven(true));
+            children.spawn(Wheel::new(Vector::new(-0.8, -0.25), 0.3));
+        })
+        .id();
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    // the suspension holds the vehicle up above the ground
+    let position = app.world.get::<Position>(vehicle).unwrap();
+    assert!(position.y > 0.75 && position.y < 0.9);
+    assert!(app
+        .world
+        .query::<&WheelState>()
+        .iter(&app.world)
+        .all(|state| state.grounded));
+
+    app.world.get_mut::<Vehicle>(vehicle).unwrap().throttle = 1.0;
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    // the vehicle drives forward along the positive X axis
+    let lin_vel = app.world.get::<LinearVelocity>(vehicle).unwrap();
+    assert!(lin_vel.x > 1.0);
+}
+
+#[test]
+fn plugins_run_without_spatial_query_plugin() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        PhysicsPlugins::default()
+            .build()
+            .disable::<SpatialQueryPlugin>(),
+    ));
+    #[cfg(feature = "async-collider")]
+    {
+        app.add_plugins((
+            bevy::asset::AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+        ))
+        .init_resource::<Assets<Mesh>>();
+    }
+    app.insert_resource(TimeUpdateStrategy::ManualInstant(Instant::now()));
+
+    let character = app
+        .world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Kinematic,
+            Collider::ball(0.5),
+            CharacterController::default(),
+            LinearVelocity(Vector::X),
+        ))
+        .id();
+    app.world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            Collider::ball(0.5),
+            Vehicle::new(20.0, 40.0),
+        ))
+        .with_children(|children| {
+            children.spawn(Wheel::new(Vector::NEG_Y * 0.5, 0.3));
+        });
+    app.world
+        .spawn(Fluid::new(0.1).with_particles_in_aabb(Vector::ZERO, Vector::ONE * 0.3));
+
+    for _ in 0..10 {
+        tick_60_fps(&mut app);
+    }
+
+    // the character is moved like a normal kinematic body
+    let position = app.world.get::<Position>(character).unwrap();
+    assert!(position.x > 0.1);
+}
+
+#[test]
+fn broad_phase_algorithms_find_same_pairs() {
+    fn run_broad_phase(algorithm: BroadPhaseAlgorithm) -> Vec<Vec<(Entity, Entity)>> {
+        let mut app = create_app();
+
+        app.insert_resource(algorithm);
+
+        app.add_systems(Startup, |mut commands: Commands| {
+            #[cfg(feature = "2d")]
+            let floor = Collider::cuboid(100.0, 1.0);
+            #[cfg(feature = "3d")]
+            let floor = Collider::cuboid(100.0, 1.0, 100.0);
+            commands.spawn((RigidBody::Static, Position(Vector::NEG_Y), floor));
+
+            // A tightly packed grid of balls that mostly overlap on the x-axis. The rows move past
+            // each other at different speeds. The balls are sensors, so that the motion doesn't depend
+            // on the order in which the contacts of the pairs are solved.
+            for x in 0..4 {
+                for y in 0..10 {
+                    commands.spawn((
+                        SpatialBundle::default(),
+                        RigidBody::Dynamic,
+                        Position(Vector::X * x as Scalar * 1.1 + Vector::Y * y as Scalar * 1.1),
+                        LinearVelocity(Vector::X * (y as Scalar - 4.5)),
+                        Collider::ball(0.5),
+                        Sensor,
+                    ));
+                }
+            }
+        });
+
+        let mut pairs_per_frame = vec![];
+
+        for _ in 0..60 {
+            tick_60_fps(&mut app);
+
+            let mut pairs: Vec<(Entity, Entity)> = app
+                .world
+                .resource::<BroadCollisionPairs>()
+                .0
+                .iter()
+                .map(|&(entity1, entity2)| (entity1.min(entity2), entity1.max(entity2)))
+                .collect();
+            pairs.sort();
+            pairs_per_frame.push(pairs);
+        }
+
+        pairs_per_frame
+    }
+
+    let sweep_and_prune = run_broad_phase(BroadPhaseAlgorithm::SweepAndPrune);
+    let dynamic_aabb_tree = run_broad_phase(BroadPhaseAlgorithm::DynamicAabbTree);
+
+    assert!(sweep_and_prune.iter().any(|pairs| !pairs.is_empty()));
+    assert_eq!(sweep_and_prune, dynamic_aabb_tree);
+}
+
+#[test]
+fn connected_bodies_sleep_and_wake_together() {
+    let mut app = create_app();
+
+    app.insert_resource(Gravity::ZERO);
+
+    let body1 = app
+        .world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            Collider::ball(0.5),
+        ))
+        .id();
+    let body2 = app
+        .world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            Position(Vector::X * 5.0),
+            Collider::ball(0.5),
+        ))
+        .id();
+    let body3 = app
+        .world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            Position(Vector::X * 10.0),
+            Collider::ball(0.5),
+        ))
+        .id();
+    app.world
+        .spawn(DistanceJoint::new(body1, body2).with_rest_length(5.0));
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let islands = app.world.resource::<SimulationIslands>();
+    assert_eq!(islands.len(), 2);
+    assert_eq!(islands.island(body1), islands.island(body2));
+    assert_ne!(islands.island(body1), islands.island(body3));
+
+    for entity in [body1, body2, body3] {
+        assert!(app.world.get::<Sleeping>(entity).is_some());
+    }
+
+    // waking up one body of an island should wake up the whole island
+    app.world.get_mut::<LinearVelocity>(body1).unwrap().0 = Vector::Y;
+
+    tick_60_fps(&mut app);
+
+    assert!(app.world.get::<Sleeping>(body1).is_none());
+    assert!(app.world.get::<Sleeping>(body2).is_none());
+    assert!(app.world.get::<Sleeping>(body3).is_some());
+}
+
+#[test]
+fn joint_motors_reach_targets() {
+    let mut app = create_app();
+
+    app.insert_resource(Gravity::ZERO);
+
+    let anchor = app.world.spawn(RigidBody::Static).id();
+    let wheel = app
+        .world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            MassPropertiesBundle::new_computed(&Collider::ball(0.5), 1.0),
+        ))
+        .id();
+    let piston = app
+        .world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            MassPropertiesBundle::new_computed(&Collider::ball(0.5), 1.0),
+        ))
+        .id();
+
+    // spin the wheel around the z axis and move the piston along the x axis
+    app.world.spawn(
+        RevoluteJoint::new(anchor, wheel)
+            .with_motor(JointMotor::velocity(5.0).with_max_force(100.0)),
+    );
+    app.world
+        .spawn(PrismaticJoint::new(anchor, piston).with_motor(JointMotor::position(2.0)));
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let ang_vel = app.world.get::<AngularVelocity>(wheel).unwrap();
+    #[cfg(feature = "2d")]
+    assert_relative_eq!(ang_vel.0, 5.0, epsilon = 0.01);
+    #[cfg(feature = "3d")]
+    assert_relative_eq!(ang_vel.z, 5.0, epsilon = 0.01);
+
+    let piston_pos = app.world.get::<Position>(piston).unwrap();
+    assert_relative_eq!(piston_pos.x, 2.0, epsilon = 0.01);
+}
+
+#[test]
+#[cfg(feature = "3d")]
+fn revolute_position_motor_reaches_target_on_diagonal_axis() {
+    let mut app = create_app();
+
+    app.insert_resource(Gravity::ZERO);
+
+    let anchor = app.world.spawn(RigidBody::Static).id();
+    let wheel = app
+        .world
+        .spawn((
+            SpatialBundle::default(),
+            RigidBody::Dynamic,
+            MassPropertiesBundle::new_computed(&Collider::ball(0.5), 1.0),
+        ))
+        .id();
+
+    // an axis that isn't aligned with any coordinate axis
+    let axis = Vector::ONE.normalize();
+    app.world.spawn(
+        RevoluteJoint::new(anchor, wheel)
+            .with_aligned_axis(axis)
+            .with_motor(JointMotor::position(1.0)),
+    );
+
+    for _ in 0..120 {
+        tick_60_fps(&mut app);
+    }
+
+    let (rotation_axis, angle) = app.world.get::<Rotation>(wheel).unwrap().to_axis_angle();
+    assert_relative_eq!(angle, 1.0, epsilon = 0.01);
+    assert_relative_eq!(rotation_axis.dot(axis), 1.0, epsilon = 0.01);
+}
+
+#[test]
+fn joint_breaks_when_break_force_is_exceeded() {
+    #[derive(Resource, Default)]
+    struct BrokenJoints(Vec<Entity>);
+
+    let mut app = create_app();
+
+    app.init_resource::<BrokenJoints>().add_systems(
+        PostUpdate,
+        |mut events: EventReader<JointBroken>, mut broken: ResMut<BrokenJoints>| {
+            broken
+                .0
+                .extend(events.read().map(|event| event.joint_entity));
+        },
+    );
+
+    let anchor = app.world.spawn(RigidBody::Static).id();
+    let mut spawn_body = |x: f32| {
+        app.world
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(x, 0.0, 0.0)),
+                RigidBody::Dynamic,
+                MassPropertiesBundle::new_computed(&Collider::ball(0.5), 1.0),
+            ))
+            .id()
+    };
+    let body1 = spawn_body(-2.0);
+    let body2 = spawn_body(2.0);
+    let weight = app.world.get::<Mass>(body1).unwrap().0 * 9.81;
+
+    // the strong joint can hold the weight of the body, but the weak joint can't
+    let strong_joint = app
+        .world
+        .spawn((
+            FixedJoint::new(anchor, body1).with_local_anchor_1(Vector::X * -2.0),
+            BreakForce(weight * 2.0),
+        ))
+        .id();
+    let weak_joint = app
+        .world
+        .spawn((
+            FixedJoint::new(anchor, body2).with_local_anchor_1(Vector::X * 2.0),
+            BreakForce(weight * 0.5),
+        ))
+        .id();
+
+    for _ in 0..10 {
+        tick_60_fps(&mut app);
+    }
+
+    assert!(app.world.get::<FixedJoint>(strong_joint).is_some());
+    assert!(app.world.get::<FixedJoint>(weak_joint).is_none());
+    assert_eq!(app.world.resource::<BrokenJoints>().0, vec![weak_joint]);
+}
+
 #[derive(Component, Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
 struct Id(usize);
 
@@ -224,6 +2200,152 @@ fn cubes_simulation_is_locally_deterministic() {
     }
 }
 
+#[cfg(feature = "3d")]
+#[test]
+fn restored_snapshot_replays_identically() {
+    fn positions(app: &mut App) -> Vec<(Id, Position, Rotation)> {
+        let mut query = app.world.query::<(&Id, &Position, &Rotation)>();
+        let mut bodies: Vec<(Id, Position, Rotation)> = query
+            .iter(&app.world)
+            .map(|(id, pos, rot)| (*id, *pos, *rot))
+            .collect();
+        bodies.sort_by_key(|b| b.0);
+        bodies
+    }
+
+    let mut app = create_app();
+
+    app.add_systems(Startup, setup_cubes_simulation);
+
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+    }
+
+    let snapshot = PhysicsSnapshot::capture(&mut app.world);
+
+    let mut original = vec![];
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+        original.push(positions(&mut app));
+    }
+
+    snapshot.restore(&mut app.world);
+
+    let mut replayed = vec![];
+    for _ in 0..60 {
+        tick_60_fps(&mut app);
+        replayed.push(positions(&mut app));
+    }
+
+    assert_eq!(original, replayed);
+}
+
+#[cfg(all(feature = "3d", feature = "enhanced-determinism"))]
+#[test]
+fn restored_snapshots_replay_identically_while_bodies_come_to_rest() {
+    // The positions of the bodies and the pairs collected by the broad phase after a step
+    type StepState = (Vec<(Id, Position, Rotation)>, Vec<(Entity, Entity)>);
+
+    fn step(app: &mut App) -> StepState {
+        tick_60_fps(app);
+
+        let mut query = app.world.query::<(&Id, &Position, &Rotation)>();
+        let mut bodies: Vec<(Id, Position, Rotation)> = query
+            .iter(&app.world)
+            .map(|(id, pos, rot)| (*id, *pos, *rot))
+            .collect();
+        bodies.sort_by_key(|b| b.0);
+
+        let mut pairs = app.world.resource::<BroadCollisionPairs>().0.clone();
+        pairs.sort();
+
+        (bodies, pairs)
+    }
+
+    let mut app = create_app();
+
+    app.add_systems(Startup, setup_cubes_simulation);
+
+    // Capture snapshots while the cubes land, settle and fall asleep. The broad phase skips pairs
+    // of colliders that haven't moved, so the replay has to agree with the original simulation
+    // on which bodies moved right before the snapshot, even when they fell asleep in that frame.
+    let mut sleeping_count = 0;
+    for frame in 0..1200 {
+        tick_60_fps(&mut app);
+
+        let previous_sleeping_count = sleeping_count;
+        sleeping_count = app
+            .world
+            .query_filtered::<(), (With<Id>, With<Sleeping>)>()
+            .iter(&app.world)
+            .count();
+        if frame % 20 != 0 && sleeping_count <= previous_sleeping_count {
+            continue;
+        }
+
+        let snapshot = PhysicsSnapshot::capture(&mut app.world);
+        let original: Vec<StepState> = (0..10).map(|_| step(&mut app)).collect();
+
+        snapshot.restore(&mut app.world);
+        let replayed: Vec<StepState> = (0..10).map(|_| step(&mut app)).collect();
+
+        assert!(original == replayed, "replay diverged after frame {frame}");
+    }
+
+    assert_eq!(sleeping_count, 64);
+}
+
+#[test]
+fn snapshot_restores_particles_and_controller_state() {
+    let mut app = create_app();
+
+    #[cfg(feature = "2d")]
+    let ground = Collider::cuboid(20.0, 1.0);
+    #[cfg(feature = "3d")]
+    let ground = Collider::cuboid(20.0, 1.0, 20.0);
+
+    app.world
+        .spawn((RigidBody::Static, Position(Vector::NEG_Y * 0.5), ground));
+    let character = app
+        .world
+        .spawn((
+            RigidBody::Kinematic,
+            Position(Vector::X * 5.0 + Vector::Y),
+            Collider::ball(0.5),
+            CharacterController::default(),
+            LinearVelocity(Vector::NEG_Y),
+        ))
+        .id();
+    let fluid = app
+        .world
+        .spawn(Fluid::new(0.1).with_particles_in_aabb(Vector::ZERO, Vector::ONE * 0.3))
+        .id();
+
+    for _ in 0..30 {
+        tick_60_fps(&mut app);
+    }
+
+    let snapshot = PhysicsSnapshot::capture(&mut app.world);
+    let state = *app
+        .world
+        .get::<CharacterControllerState>(character)
+        .unwrap();
+    let fluid_state = app.world.get::<Fluid>(fluid).unwrap().clone();
+
+    for _ in 0..30 {
+        tick_60_fps(&mut app);
+    }
+    assert_ne!(app.world.get::<Fluid>(fluid), Some(&fluid_state));
+
+    snapshot.restore(&mut app.world);
+
+    assert_eq!(
+        app.world.get::<CharacterControllerState>(character),
+        Some(&state)
+    );
+    assert_eq!(app.world.get::<Fluid>(fluid), Some(&fluid_state));
+}
+
 #[test]
 fn no_ambiguity_errors() {
     #[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
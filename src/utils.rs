@@ -32,6 +32,18 @@ pub(crate) fn get_rotated_inertia_tensor(inertia_tensor: Matrix3, rot: Quaternio
     (rot_mat3 * inertia_tensor) * rot_mat3.transpose()
 }
 
+/// Computes the velocity of a body at a point offset by `r` from its center of mass.
+#[cfg(feature = "2d")]
+pub(crate) fn velocity_at_point(lin_vel: Vector, ang_vel: Scalar, r: Vector) -> Vector {
+    lin_vel + ang_vel * r.perp()
+}
+
+/// Computes the velocity of a body at a point offset by `r` from its center of mass.
+#[cfg(feature = "3d")]
+pub(crate) fn velocity_at_point(lin_vel: Vector, ang_vel: Vector, r: Vector) -> Vector {
+    lin_vel + ang_vel.cross(r)
+}
+
 /// Computes translation of `Position` based on center of mass rotation and translation
 pub(crate) fn get_pos_translation(
     com_translation: &AccumulatedTranslation,
@@ -78,3 +90,150 @@ pub(crate) fn compute_restitution(
 
     -normal_speed + (-coefficient * pre_solve_normal_speed).min(0.0)
 }
+
+/// Computes the line segments where a grid of samples crosses zero using the marching squares algorithm.
+///
+/// `value(x, y)` returns the sample at the given grid index, and the endpoints of the segments
+/// are returned in grid coordinates. Segments of neighboring cells share exactly the same endpoints.
+#[cfg(any(
+    feature = "debug-plugin",
+    all(feature = "2d", feature = "collider-from-image")
+))]
+pub(crate) fn marching_squares(
+    size: [usize; 2],
+    value: impl Fn(usize, usize) -> Scalar,
+) -> Vec<[[Scalar; 2]; 2]> {
+    let mut segments = vec![];
+    if size[0] < 2 || size[1] < 2 {
+        return segments;
+    }
+    let samples: Vec<Scalar> = (0..size[0] * size[1])
+        .map(|i| value(i % size[0], i / size[0]))
+        .collect();
+
+    for y in 0..size[1] - 1 {
+        for x in 0..size[0] - 1 {
+            // The corners of the cell in counterclockwise order
+            let corners = [[x, y], [x + 1, y], [x + 1, y + 1], [x, y + 1]];
+            let values = corners.map(|[cx, cy]| samples[cy * size[0] + cx]);
+
+            // The points where the edges between the corners cross zero
+            let crossings: Vec<[Scalar; 2]> = (0..4)
+                .filter_map(|i| {
+                    // Interpolate in the same direction as the neighboring cell sharing the edge
+                    let (i, j) = if i < 2 { (i, i + 1) } else { ((i + 1) % 4, i) };
+                    if (values[i] < 0.0) == (values[j] < 0.0) {
+                        return None;
+                    }
+                    let t = values[i] / (values[i] - values[j]);
+                    let [ax, ay] = corners[i].map(|c| c as Scalar);
+                    let [bx, by] = corners[j].map(|c| c as Scalar);
+                    Some([ax + (bx - ax) * t, ay + (by - ay) * t])
+                })
+                .collect();
+
+            match crossings.as_slice() {
+                [a, b] => segments.push([*a, *b]),
+                [a, b, c, d] => {
+                    // Saddle points are resolved using the average of the corners
+                    let center = values.iter().sum::<Scalar>() * 0.25;
+                    if (center < 0.0) == (values[0] < 0.0) {
+                        segments.extend([[*a, *b], [*c, *d]]);
+                    } else {
+                        segments.extend([[*d, *a], [*b, *c]]);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    segments
+}
+
+/// Connects line segments that share endpoints into closed outlines.
+#[cfg(all(feature = "2d", feature = "collider-from-image"))]
+pub(crate) fn connect_segments(segments: &[[[Scalar; 2]; 2]]) -> Vec<Vec<[Scalar; 2]>> {
+    let mut ids = bevy::utils::HashMap::new();
+    let mut points = vec![];
+    let mut neighbors: Vec<Vec<usize>> = vec![];
+    for segment in segments {
+        let [a, b] = segment.map(|point| {
+            *ids.entry(point.map(Scalar::to_bits)).or_insert_with(|| {
+                points.push(point);
+                neighbors.push(vec![]);
+                points.len() - 1
+            })
+        });
+        if a != b {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+    }
+
+    let mut visited = vec![false; points.len()];
+    let mut outlines = vec![];
+    for start in 0..points.len() {
+        if visited[start] {
+            continue;
+        }
+        let mut outline = vec![];
+        let mut current = start;
+        loop {
+            visited[current] = true;
+            outline.push(points[current]);
+            match neighbors[current].iter().find(|next| !visited[**next]) {
+                Some(next) => current = *next,
+                None => break,
+            }
+        }
+        outlines.push(outline);
+    }
+    outlines
+}
+
+/// Simplifies a closed polyline using the Ramer-Douglas-Peucker algorithm, removing vertices
+/// that are closer than `tolerance` to the simplified polyline.
+#[cfg(all(feature = "2d", feature = "collider-from-image"))]
+pub(crate) fn simplify_closed_polyline(points: &[Vector], tolerance: Scalar) -> Vec<Vector> {
+    if points.len() < 4 {
+        return points.to_vec();
+    }
+
+    // Split the polyline into two open polylines at the vertex farthest from the first vertex
+    let farthest = (1..points.len())
+        .max_by(|a, b| {
+            points[*a]
+                .distance_squared(points[0])
+                .total_cmp(&points[*b].distance_squared(points[0]))
+        })
+        .unwrap_or(1);
+    let mut keep = vec![false; points.len() + 1];
+    keep[0] = true;
+    keep[farthest] = true;
+    let mut ranges = vec![(0, farthest), (farthest, points.len())];
+
+    while let Some((start, end)) = ranges.pop() {
+        let (a, b) = (points[start], points[end % points.len()]);
+        let distance_to_segment = |point: Vector| {
+            let ab = b - a;
+            let t =
+                ((point - a).dot(ab) / ab.length_squared().max(Scalar::EPSILON)).clamp(0.0, 1.0);
+            point.distance(a + ab * t)
+        };
+        let farthest = (start + 1..end).max_by(|i, j| {
+            distance_to_segment(points[*i]).total_cmp(&distance_to_segment(points[*j]))
+        });
+        if let Some(i) = farthest.filter(|i| distance_to_segment(points[*i]) > tolerance) {
+            keep[i] = true;
+            ranges.push((start, i));
+            ranges.push((i, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(*point))
+        .collect()
+}